@@ -0,0 +1,177 @@
+//! Compares the point-to-point searches on a generated grid network of about 100k roads.
+//!
+//! Run with `cargo bench --bench routing`.
+
+use std::time::{Duration, Instant};
+
+use route_rs::managers::intersection::{Control, Intersection};
+use route_rs::managers::road::{Direction, Road};
+use route_rs::routing::astar::astar;
+use route_rs::routing::bidirectional::bidirectional;
+use route_rs::routing::dijkstra::shortest_path;
+use route_rs::routing::graph::Graph;
+use route_rs::routing::{Metric, Route};
+
+/// Intersections per side of the grid, which gives 2 * 224 * 223 = 99,904 roads
+const SIDE: usize = 224;
+const SPACING: f64 = 100.0;
+const QUERIES: usize = 100;
+
+type Search<'a> = &'a dyn Fn(usize, usize) -> Option<Route>;
+
+/// A small xorshift generator, so every run benchmarks the same network and queries
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+}
+
+/// Generates a grid of intersections connected by roads to their right and lower neighbours. Every fifth row and
+/// column is a faster arterial, a few streets are one-way and some intersections have signals or stop signs.
+fn generate(rng: &mut Rng) -> (Vec<Road>, Vec<Intersection>) {
+    let mut intersections = Vec::new();
+    let mut roads = Vec::new();
+
+    for x in 0..SIDE {
+        for y in 0..SIDE {
+            let control = match rng.below(10) {
+                0 => Control::TrafficSignals,
+                1 => Control::AllWayStop,
+                _ => Control::Uncontrolled,
+            };
+
+            intersections.push(Intersection {
+                _id: Some((x * SIDE + y) as i32 + 1),
+                lat: x as f64 * SPACING,
+                lon: y as f64 * SPACING,
+                control,
+                signal_offset: 0.0,
+            });
+        }
+    }
+
+    for x in 0..SIDE {
+        for y in 0..SIDE {
+            let neighbours = [(x + 1, y, y % 5 == 0), (x, y + 1, x % 5 == 0)];
+
+            for (to_x, to_y, arterial) in neighbours {
+                if to_x == SIDE || to_y == SIDE {
+                    continue;
+                }
+
+                let direction = match (arterial, rng.below(20)) {
+                    (false, 0) => Direction::Forward,
+                    (false, 1) => Direction::Backward,
+                    _ => Direction::Both,
+                };
+
+                roads.push(Road {
+                    _id: Some(roads.len() as i32 + 1),
+                    name: format!("Road {}", roads.len() + 1),
+                    start_lat: x as f64 * SPACING,
+                    start_lon: y as f64 * SPACING,
+                    stop_lat: to_x as f64 * SPACING,
+                    stop_lon: to_y as f64 * SPACING,
+                    speed_limit: if arterial {
+                        70.0
+                    } else {
+                        30.0 + 10.0 * rng.below(3) as f64
+                    },
+                    lane_count: if arterial { 4.0 } else { 2.0 },
+                    road_type: "asphalt".to_string(),
+                    direction,
+                    lanes_forward: None,
+                    lanes_backward: None,
+                });
+            }
+        }
+    }
+
+    (roads, intersections)
+}
+
+/// Runs a search for every query, returning the total time and the cost of every route found
+fn run(
+    queries: &[(usize, usize)],
+    metric: Metric,
+    search: impl Fn(usize, usize) -> Option<Route>,
+) -> (Duration, Vec<Option<f64>>) {
+    let started = Instant::now();
+    let costs = queries
+        .iter()
+        .map(|&(from, to)| {
+            search(from, to).map(|route| match metric {
+                Metric::Distance => route.distance,
+                Metric::Time => route.time,
+            })
+        })
+        .collect();
+
+    (started.elapsed(), costs)
+}
+
+fn main() {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+    let started = Instant::now();
+    let (roads, intersections) = generate(&mut rng);
+    let graph = Graph::build(&roads, &intersections, 6.0);
+    println!(
+        "built a graph of {} roads, {} nodes and {} edges in {:?}",
+        roads.len(),
+        graph.nodes.len(),
+        graph.edges.len(),
+        started.elapsed()
+    );
+
+    let queries: Vec<(usize, usize)> = (0..QUERIES)
+        .map(|_| (rng.below(graph.nodes.len()), rng.below(graph.nodes.len())))
+        .collect();
+
+    for metric in [Metric::Distance, Metric::Time] {
+        let (baseline, expected) = run(&queries, metric, |from, to| {
+            shortest_path(&graph, from, to, metric)
+        });
+        println!("\n{:?}, {} queries", metric, QUERIES);
+        println!(
+            "  dijkstra       {:>10.2?} per query",
+            baseline / QUERIES as u32
+        );
+
+        let searches: [(&str, Search); 2] = [
+            ("a*", &|from, to| astar(&graph, from, to, metric)),
+            ("bidirectional", &|from, to| {
+                bidirectional(&graph, from, to, metric)
+            }),
+        ];
+
+        for (name, search) in searches {
+            let (elapsed, costs) = run(&queries, metric, search);
+            let same = expected.iter().zip(&costs).all(|(a, b)| match (a, b) {
+                (Some(a), Some(b)) => (a - b).abs() < 1e-6,
+                (a, b) => a.is_none() && b.is_none(),
+            });
+
+            println!(
+                "  {:<14} {:>10.2?} per query, {:.1}x, {}",
+                name,
+                elapsed / QUERIES as u32,
+                baseline.as_secs_f64() / elapsed.as_secs_f64(),
+                if same {
+                    "same costs"
+                } else {
+                    "DIFFERENT COSTS"
+                }
+            );
+        }
+    }
+}
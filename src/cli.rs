@@ -0,0 +1,473 @@
+use std::path::PathBuf;
+
+/// The help text printed for `--help` and after invalid arguments
+pub const USAGE: &str = "Usage: route-rs [OPTIONS] [COMMAND]
+
+Options:
+    --config <FILE>        Path to the config file
+    --data-dir <DIR>       Directory roads.csv and intersections.csv are stored in
+    --assets-dir <DIR>     Directory containing the font assets
+    --sample-dir <DIR>     Directory containing the sample csv files
+    --record <FILE>        Record the editor input to a session file
+    --replay <FILE>        Replay a recorded session in the window
+    --headless             With --replay, replay without a window and store the result in the data directory
+    -h, --help             Print this message
+
+Commands:
+    matrix                 Write the distance and travel time matrix between intersections, then exit
+        --ids <IDS>        Comma separated intersection ids, all intersections when omitted
+        --ids-file <FILE>  File with intersection ids separated by commas or whitespace
+        --metric <METRIC>  Optimize routes for `time` or `distance`, defaults to the routing config
+        --profile <NAME>   Routing profile, `car`, `bicycle`, `pedestrian` or one from the config, defaults to the routing config
+        --avoid <RULE>     Avoid roads, e.g. `type=dirt`, `lanes<2`, `road=4,7` or `name=Main St`; `*<FACTOR>` makes it a cost factor
+        --prefer <RULE>    Prefer roads matching a rule like --avoid, making them cheaper by the routing config's prefer_factor
+        --format <FORMAT>  `csv` or `json`, defaults to csv
+        --output <FILE>    File to write to instead of stdout
+    isochrone              Write the area reachable from an intersection as GeoJSON polygons, then exit
+        --from <ID>        Intersection to start from
+        --budgets <LIST>   Comma separated budgets, defaults to the isochrone config
+        --metric <METRIC>  Budgets in seconds for `time` or meters for `distance`, defaults to the isochrone config
+        --profile <NAME>   Routing profile, `car`, `bicycle`, `pedestrian` or one from the config, defaults to the routing config
+        --output <FILE>    File to write to instead of stdout
+    assign                 Assign the trips of a demand file to the network and write the flow of every road, then exit
+        --demand <FILE>    Demand file, defaults to demand.csv in the data directory
+        --output <FILE>    File to write to instead of stdout
+    contract               Preprocess the network into contraction hierarchies stored in the data directory, then exit
+        --metric <METRIC>  Only preprocess for `time` or `distance`, both when omitted
+        --profile <NAME>   Routing profile, `car`, `bicycle`, `pedestrian` or one from the config, defaults to the routing config
+    route                  Write the best routes between intersections using the stored hierarchy, then exit
+        --from <ID>        Intersection to start from
+        --to <ID>          Intersection to go to
+        --pairs <FILE>     Csv file with `from,to` columns, instead of --from and --to
+        --metric <METRIC>  Optimize routes for `time` or `distance`, defaults to the routing config
+        --profile <NAME>   Routing profile, `car`, `bicycle`, `pedestrian` or one from the config, defaults to the routing config
+        --avoid <RULE>     Avoid roads, e.g. `type=dirt`, `lanes<2`, `road=4,7` or `name=Main St`; `*<FACTOR>` makes it a cost factor
+        --prefer <RULE>    Prefer roads matching a rule like --avoid, making them cheaper by the routing config's prefer_factor
+        --depart <TIME>    Find the fastest route departing at `YYYY-MM-DD HH:MM` or today's `HH:MM`, following speed profiles and closures
+        --format <FORMAT>  `csv`, `json` with turn-by-turn instructions, or `text` instructions, defaults to csv
+        --output <FILE>    File to write to instead of stdout
+    tour                   Write the route through several intersections with the distance and time of every leg, then exit
+        --stops <IDS>      Comma separated intersections to visit, starting with the first
+        --optimize         Visit the stops after the first in the order that makes the route cheapest
+        --end <ID>         Intersection to finish at after all stops
+        --metric <METRIC>  Optimize routes for `time` or `distance`, defaults to the routing config
+        --profile <NAME>   Routing profile, `car`, `bicycle`, `pedestrian` or one from the config, defaults to the routing config
+        --avoid <RULE>     Avoid roads, e.g. `type=dirt`, `lanes<2`, `road=4,7` or `name=Main St`; `*<FACTOR>` makes it a cost factor
+        --prefer <RULE>    Prefer roads matching a rule like --avoid, making them cheaper by the routing config's prefer_factor
+        --format <FORMAT>  `csv` or `json`, defaults to csv
+        --output <FILE>    File to write to instead of stdout
+    vrp                    Plan the tours of a delivery fleet and write the visits of every vehicle, then exit
+        --depot <ID>       Intersection the vehicles leave from and return to, defaults to the vrp config
+        --vehicles <FILE>  Fleet file, defaults to vehicles.csv in the data directory
+        --stops <FILE>     Deliveries file, defaults to stops.csv in the data directory
+        --profile <NAME>   Routing profile, `car`, `bicycle`, `pedestrian` or one from the config, defaults to the routing config
+        --format <FORMAT>  `csv` or `json`, defaults to csv
+        --output <FILE>    File to write to instead of stdout
+
+Environment:
+    ROUTE_RS_CONFIG, ROUTE_RS_DATA_DIR, ROUTE_RS_ASSETS_DIR, ROUTE_RS_SAMPLE_DIR";
+
+/// Command line arguments passed to route-rs
+#[derive(Debug, Default)]
+pub struct Args {
+    pub config: Option<PathBuf>,
+    pub data_dir: Option<PathBuf>,
+    pub assets_dir: Option<PathBuf>,
+    pub sample_dir: Option<PathBuf>,
+    pub record: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    pub headless: bool,
+    pub command: Option<Command>,
+}
+
+/// A command to run instead of opening the window
+#[derive(Debug)]
+pub enum Command {
+    Matrix(MatrixArgs),
+    Isochrone(IsochroneArgs),
+    Assign(AssignArgs),
+    Contract(ContractArgs),
+    Route(RouteArgs),
+    Tour(TourArgs),
+    Vrp(VrpArgs),
+}
+
+/// Arguments of the `matrix` command
+#[derive(Debug, Default)]
+pub struct MatrixArgs {
+    pub ids: Option<String>,
+    pub ids_file: Option<PathBuf>,
+    pub metric: Option<String>,
+    pub profile: Option<String>,
+    /// Rules of roads to avoid, see `RouteOptions::from_rules`
+    pub avoid: Vec<String>,
+    /// Rules of roads to prefer
+    pub prefer: Vec<String>,
+    pub format: Option<String>,
+    pub output: Option<PathBuf>,
+}
+
+/// Arguments of the `isochrone` command
+#[derive(Debug, Default)]
+pub struct IsochroneArgs {
+    pub from: Option<String>,
+    pub budgets: Option<String>,
+    pub metric: Option<String>,
+    pub profile: Option<String>,
+    pub output: Option<PathBuf>,
+}
+
+/// Arguments of the `assign` command
+#[derive(Debug, Default)]
+pub struct AssignArgs {
+    pub demand: Option<PathBuf>,
+    pub output: Option<PathBuf>,
+}
+
+/// Arguments of the `contract` command
+#[derive(Debug, Default)]
+pub struct ContractArgs {
+    pub metric: Option<String>,
+    pub profile: Option<String>,
+}
+
+/// Arguments of the `route` command
+#[derive(Debug, Default)]
+pub struct RouteArgs {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub pairs: Option<PathBuf>,
+    pub metric: Option<String>,
+    pub profile: Option<String>,
+    /// Rules of roads to avoid, see `RouteOptions::from_rules`
+    pub avoid: Vec<String>,
+    /// Rules of roads to prefer
+    pub prefer: Vec<String>,
+    pub format: Option<String>,
+    /// `YYYY-MM-DD HH:MM` or `HH:MM` for today
+    pub depart: Option<String>,
+    pub output: Option<PathBuf>,
+}
+
+/// Arguments of the `tour` command
+#[derive(Debug, Default)]
+pub struct TourArgs {
+    pub stops: Option<String>,
+    pub optimize: bool,
+    pub end: Option<String>,
+    pub metric: Option<String>,
+    pub profile: Option<String>,
+    /// Rules of roads to avoid, see `RouteOptions::from_rules`
+    pub avoid: Vec<String>,
+    /// Rules of roads to prefer
+    pub prefer: Vec<String>,
+    pub format: Option<String>,
+    pub output: Option<PathBuf>,
+}
+
+/// Arguments of the `vrp` command
+#[derive(Debug, Default)]
+pub struct VrpArgs {
+    pub depot: Option<String>,
+    pub profile: Option<String>,
+    pub vehicles: Option<PathBuf>,
+    pub stops: Option<PathBuf>,
+    pub format: Option<String>,
+    pub output: Option<PathBuf>,
+}
+
+impl Args {
+    /// Parses the arguments the program was started with. Returns `None` when the usage was requested with `--help`
+    /// and a message on invalid input; printing `USAGE` is left to the caller.
+    ///
+    /// Example
+    /// ```rust
+    /// let args = Args::parse()?.unwrap_or_default(); // `route-rs --data-dir ~/roads` sets args.data_dir
+    /// ```
+    pub fn parse() -> Result<Option<Args>, String> {
+        Args::parse_from(std::env::args().skip(1))
+    }
+
+    /// Parses the given arguments, returns `None` when the usage was requested
+    ///
+    /// @internal
+    fn parse_from(mut raw: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+        let mut args = Args::default();
+
+        while let Some(arg) = raw.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+
+            if let Some(Command::Matrix(matrix)) = &mut args.command {
+                match flag.as_str() {
+                    "--ids" => {
+                        matrix.ids = Some(value(&flag, inline_value, &mut raw)?);
+                        continue;
+                    }
+                    "--ids-file" => {
+                        matrix.ids_file =
+                            Some(PathBuf::from(value(&flag, inline_value, &mut raw)?));
+                        continue;
+                    }
+                    "--metric" => {
+                        matrix.metric = Some(value(&flag, inline_value, &mut raw)?);
+                        continue;
+                    }
+                    "--profile" => {
+                        matrix.profile = Some(value(&flag, inline_value, &mut raw)?);
+                        continue;
+                    }
+                    "--avoid" => {
+                        matrix.avoid.push(value(&flag, inline_value, &mut raw)?);
+                        continue;
+                    }
+                    "--prefer" => {
+                        matrix.prefer.push(value(&flag, inline_value, &mut raw)?);
+                        continue;
+                    }
+                    "--format" => {
+                        matrix.format = Some(value(&flag, inline_value, &mut raw)?);
+                        continue;
+                    }
+                    "--output" => {
+                        matrix.output = Some(PathBuf::from(value(&flag, inline_value, &mut raw)?));
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+
+            if let Some(Command::Isochrone(isochrone)) = &mut args.command {
+                let target = match flag.as_str() {
+                    "--from" => Some(&mut isochrone.from),
+                    "--budgets" => Some(&mut isochrone.budgets),
+                    "--metric" => Some(&mut isochrone.metric),
+                    "--profile" => Some(&mut isochrone.profile),
+                    "--output" => {
+                        isochrone.output =
+                            Some(PathBuf::from(value(&flag, inline_value, &mut raw)?));
+                        continue;
+                    }
+                    _ => None,
+                };
+
+                if let Some(target) = target {
+                    *target = Some(value(&flag, inline_value, &mut raw)?);
+                    continue;
+                }
+            }
+
+            if let Some(Command::Assign(assign)) = &mut args.command {
+                let target = match flag.as_str() {
+                    "--demand" => Some(&mut assign.demand),
+                    "--output" => Some(&mut assign.output),
+                    _ => None,
+                };
+
+                if let Some(target) = target {
+                    *target = Some(PathBuf::from(value(&flag, inline_value, &mut raw)?));
+                    continue;
+                }
+            }
+
+            if let Some(Command::Contract(contract)) = &mut args.command {
+                let target = match flag.as_str() {
+                    "--metric" => Some(&mut contract.metric),
+                    "--profile" => Some(&mut contract.profile),
+                    _ => None,
+                };
+
+                if let Some(target) = target {
+                    *target = Some(value(&flag, inline_value, &mut raw)?);
+                    continue;
+                }
+            }
+
+            if let Some(Command::Route(route)) = &mut args.command {
+                let target = match flag.as_str() {
+                    "--from" => Some(&mut route.from),
+                    "--to" => Some(&mut route.to),
+                    "--metric" => Some(&mut route.metric),
+                    "--profile" => Some(&mut route.profile),
+                    "--format" => Some(&mut route.format),
+                    "--depart" => Some(&mut route.depart),
+                    "--avoid" => {
+                        route.avoid.push(value(&flag, inline_value, &mut raw)?);
+                        continue;
+                    }
+                    "--prefer" => {
+                        route.prefer.push(value(&flag, inline_value, &mut raw)?);
+                        continue;
+                    }
+                    "--pairs" => {
+                        route.pairs = Some(PathBuf::from(value(&flag, inline_value, &mut raw)?));
+                        continue;
+                    }
+                    "--output" => {
+                        route.output = Some(PathBuf::from(value(&flag, inline_value, &mut raw)?));
+                        continue;
+                    }
+                    _ => None,
+                };
+
+                if let Some(target) = target {
+                    *target = Some(value(&flag, inline_value, &mut raw)?);
+                    continue;
+                }
+            }
+
+            if let Some(Command::Tour(tour)) = &mut args.command {
+                let target = match flag.as_str() {
+                    "--stops" => Some(&mut tour.stops),
+                    "--end" => Some(&mut tour.end),
+                    "--metric" => Some(&mut tour.metric),
+                    "--profile" => Some(&mut tour.profile),
+                    "--avoid" => {
+                        tour.avoid.push(value(&flag, inline_value, &mut raw)?);
+                        continue;
+                    }
+                    "--prefer" => {
+                        tour.prefer.push(value(&flag, inline_value, &mut raw)?);
+                        continue;
+                    }
+                    "--format" => Some(&mut tour.format),
+                    "--optimize" => {
+                        tour.optimize = true;
+                        continue;
+                    }
+                    "--output" => {
+                        tour.output = Some(PathBuf::from(value(&flag, inline_value, &mut raw)?));
+                        continue;
+                    }
+                    _ => None,
+                };
+
+                if let Some(target) = target {
+                    *target = Some(value(&flag, inline_value, &mut raw)?);
+                    continue;
+                }
+            }
+
+            if let Some(Command::Vrp(vrp)) = &mut args.command {
+                let target = match flag.as_str() {
+                    "--vehicles" => Some(&mut vrp.vehicles),
+                    "--stops" => Some(&mut vrp.stops),
+                    "--output" => Some(&mut vrp.output),
+                    "--depot" => {
+                        vrp.depot = Some(value(&flag, inline_value, &mut raw)?);
+                        continue;
+                    }
+                    "--profile" => {
+                        vrp.profile = Some(value(&flag, inline_value, &mut raw)?);
+                        continue;
+                    }
+                    "--format" => {
+                        vrp.format = Some(value(&flag, inline_value, &mut raw)?);
+                        continue;
+                    }
+                    _ => None,
+                };
+
+                if let Some(target) = target {
+                    *target = Some(PathBuf::from(value(&flag, inline_value, &mut raw)?));
+                    continue;
+                }
+            }
+
+            let target = match flag.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "--headless" => {
+                    args.headless = true;
+                    continue;
+                }
+                "matrix" if args.command.is_none() => {
+                    args.command = Some(Command::Matrix(MatrixArgs::default()));
+                    continue;
+                }
+                "isochrone" if args.command.is_none() => {
+                    args.command = Some(Command::Isochrone(IsochroneArgs::default()));
+                    continue;
+                }
+                "assign" if args.command.is_none() => {
+                    args.command = Some(Command::Assign(AssignArgs::default()));
+                    continue;
+                }
+                "contract" if args.command.is_none() => {
+                    args.command = Some(Command::Contract(ContractArgs::default()));
+                    continue;
+                }
+                "route" if args.command.is_none() => {
+                    args.command = Some(Command::Route(RouteArgs::default()));
+                    continue;
+                }
+                "tour" if args.command.is_none() => {
+                    args.command = Some(Command::Tour(TourArgs::default()));
+                    continue;
+                }
+                "vrp" if args.command.is_none() => {
+                    args.command = Some(Command::Vrp(VrpArgs::default()));
+                    continue;
+                }
+                "--config" => &mut args.config,
+                "--data-dir" => &mut args.data_dir,
+                "--assets-dir" => &mut args.assets_dir,
+                "--sample-dir" => &mut args.sample_dir,
+                "--record" => &mut args.record,
+                "--replay" => &mut args.replay,
+                _ => return Err(format!("Unknown argument `{}`", flag)),
+            };
+
+            *target = Some(PathBuf::from(value(&flag, inline_value, &mut raw)?));
+        }
+
+        if args.headless && args.replay.is_none() {
+            return Err("`--headless` requires `--replay`".to_string());
+        }
+
+        if let Some(Command::Matrix(matrix)) = &args.command {
+            if matrix.ids.is_some() && matrix.ids_file.is_some() {
+                return Err("`--ids` and `--ids-file` cannot be used together".to_string());
+            }
+        }
+
+        if let Some(Command::Isochrone(isochrone)) = &args.command {
+            if isochrone.from.is_none() {
+                return Err("`isochrone` requires `--from`".to_string());
+            }
+        }
+
+        if let Some(Command::Route(route)) = &args.command {
+            match (&route.from, &route.to, &route.pairs) {
+                (Some(_), Some(_), None) | (None, None, Some(_)) => {}
+                _ => {
+                    return Err(
+                        "`route` requires either `--from` and `--to` or `--pairs`".to_string()
+                    )
+                }
+            }
+        }
+
+        if let Some(Command::Tour(tour)) = &args.command {
+            if tour.stops.is_none() {
+                return Err("`tour` requires `--stops`".to_string());
+            }
+        }
+
+        Ok(Some(args))
+    }
+}
+
+/// Takes the value of `flag`, either given inline as `--flag=value` or as the next argument
+///
+/// @internal
+fn value(
+    flag: &str,
+    inline_value: Option<String>,
+    raw: &mut impl Iterator<Item = String>,
+) -> Result<String, String> {
+    inline_value
+        .or_else(|| raw.next())
+        .ok_or_else(|| format!("Missing value for `{}`", flag))
+}
@@ -0,0 +1,62 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::cli::AssignArgs;
+use crate::routing::assignment::{assign, load_demand};
+use crate::routing::graph::Graph;
+use crate::{get_config, get_intersection_manager, get_logger, get_paths, get_road_manager};
+
+/// Assigns the trips of a demand file to the network in the data directory and writes the flow of every road
+///
+/// Example
+/// ```rust
+/// // route-rs assign --demand peak.csv --output flows.csv
+/// assign::run(&args)?;
+/// ```
+pub fn run(args: &AssignArgs) -> Result<(), Box<dyn Error>> {
+    let logger = get_logger();
+    let config = get_config().read().unwrap().clone();
+
+    let demand_path = args
+        .demand
+        .clone()
+        .unwrap_or_else(|| get_paths().data_file("demand.csv"));
+    let demand = load_demand(&demand_path)?;
+
+    let mut road_manager = get_road_manager().write().unwrap();
+    let mut intersection_manager = get_intersection_manager().lock().unwrap();
+    road_manager.load(None)?;
+    intersection_manager.load(None)?;
+
+    let graph = Graph::from_managers(
+        &road_manager,
+        &intersection_manager,
+        config.routing.snap_radius,
+    );
+    drop(road_manager);
+    drop(intersection_manager);
+
+    let assignment = assign(&graph, &demand, &config.assignment)?;
+
+    let writer: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout().lock()),
+    };
+    let mut writer = BufWriter::new(writer);
+    assignment.write_csv(&graph, &mut writer)?;
+    writer.flush()?;
+
+    // stdout holds the flows themselves, so only report where they went when writing to a file
+    if let Some(path) = &args.output {
+        logger.info(&format!(
+            "(assignment) assigned {} origin-destination pairs with a relative gap of {:.6} after {} iterations, wrote flows to {}",
+            demand.len(),
+            assignment.gap,
+            assignment.iterations,
+            path.display()
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use std::error::Error;
+
+use crate::cli::ContractArgs;
+use crate::routing::graph::Graph;
+use crate::routing::hierarchy::ContractionHierarchy;
+use crate::routing::Metric;
+use crate::{get_config, get_intersection_manager, get_logger, get_paths, get_road_manager};
+
+/// Preprocesses the network in the data directory into contraction hierarchies and stores them next to the data
+/// files, so later queries can skip the preprocessing
+///
+/// Example
+/// ```rust
+/// // route-rs contract --metric time
+/// contract::run(&args)?;
+/// ```
+pub fn run(args: &ContractArgs) -> Result<(), Box<dyn Error>> {
+    let logger = get_logger();
+    let config = get_config().read().unwrap().clone();
+
+    let metrics = match args.metric.as_deref() {
+        None => vec![Metric::Time, Metric::Distance],
+        Some("time") => vec![Metric::Time],
+        Some("distance") => vec![Metric::Distance],
+        Some(other) => return Err(format!("Unknown metric `{}`", other).into()),
+    };
+
+    let profile_name = args.profile.as_deref().unwrap_or(&config.routing.profile);
+    let profile = config
+        .profile(profile_name)
+        .ok_or_else(|| format!("Unknown profile `{}`", profile_name))?;
+
+    let mut road_manager = get_road_manager().write().unwrap();
+    let mut intersection_manager = get_intersection_manager().lock().unwrap();
+    road_manager.load(None)?;
+    intersection_manager.load(None)?;
+
+    let graph = Graph::from_managers_for_profile(
+        &road_manager,
+        &intersection_manager,
+        config.routing.snap_radius,
+        &profile,
+    );
+    drop(road_manager);
+    drop(intersection_manager);
+
+    for metric in metrics {
+        let path = get_paths().data_file(&ContractionHierarchy::file_name(profile_name, metric));
+        let hierarchy = ContractionHierarchy::build(&graph, metric);
+        hierarchy.store(&path)?;
+
+        logger.info(&format!(
+            "(hierarchy) stored hierarchy of {} edges to {}",
+            graph.edges.len(),
+            path.display()
+        ));
+    }
+
+    Ok(())
+}
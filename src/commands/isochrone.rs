@@ -0,0 +1,91 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::cli::IsochroneArgs;
+use crate::routing::graph::Graph;
+use crate::routing::isochrone::{isochrone, write_geojson};
+use crate::routing::{Metric, RoutingError};
+use crate::{get_config, get_intersection_manager, get_logger, get_road_manager};
+
+/// Writes the area reachable from an intersection of the network in the data directory as GeoJSON
+///
+/// Example
+/// ```rust
+/// // route-rs isochrone --from 3 --budgets 300,600
+/// isochrone::run(&args)?;
+/// ```
+pub fn run(args: &IsochroneArgs) -> Result<(), Box<dyn Error>> {
+    let logger = get_logger();
+    let config = get_config().read().unwrap().clone();
+
+    let from = args.from.as_deref().unwrap_or_default();
+    let from: i32 = from
+        .parse()
+        .map_err(|_| format!("Invalid intersection id `{}`", from))?;
+
+    let metric = match args.metric.as_deref() {
+        None => config.isochrone.metric,
+        Some("time") => Metric::Time,
+        Some("distance") => Metric::Distance,
+        Some(other) => return Err(format!("Unknown metric `{}`", other).into()),
+    };
+
+    let budgets = match &args.budgets {
+        Some(budgets) => budgets
+            .split(',')
+            .map(|budget| {
+                budget
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("Invalid budget `{}`", budget))
+            })
+            .collect::<Result<Vec<f64>, String>>()?,
+        None => config.isochrone.budgets.clone(),
+    };
+
+    let profile_name = args.profile.as_deref().unwrap_or(&config.routing.profile);
+    let profile = config
+        .profile(profile_name)
+        .ok_or_else(|| format!("Unknown profile `{}`", profile_name))?;
+
+    let mut road_manager = get_road_manager().write().unwrap();
+    let mut intersection_manager = get_intersection_manager().lock().unwrap();
+    road_manager.load(None)?;
+    intersection_manager.load(None)?;
+
+    let graph = Graph::from_managers_for_profile(
+        &road_manager,
+        &intersection_manager,
+        config.routing.snap_radius,
+        &profile,
+    );
+    drop(road_manager);
+    drop(intersection_manager);
+
+    let start = graph
+        .intersection_node(from)
+        .ok_or(RoutingError::UnknownIntersection(from))?;
+    let bands = isochrone(&graph, start, metric, &budgets);
+
+    let writer: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout().lock()),
+    };
+    let mut writer = BufWriter::new(writer);
+    write_geojson(&bands, metric, config.isochrone.hull_edge, &mut writer)?;
+    writeln!(writer)?;
+    writer.flush()?;
+
+    // stdout holds the polygons themselves, so only report where they went when writing to a file
+    if let Some(path) = &args.output {
+        logger.info(&format!(
+            "(router) wrote {} isochrone bands from intersection {} to {}",
+            bands.len(),
+            from,
+            path.display()
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+
+use crate::cli::MatrixArgs;
+use crate::routing::graph::Graph;
+use crate::routing::matrix::matrix;
+use crate::routing::options::RouteOptions;
+use crate::routing::Metric;
+use crate::{get_config, get_intersection_manager, get_logger, get_paths, get_road_manager};
+
+/// Writes the distance and travel time matrix between the intersections in the data directory
+///
+/// Example
+/// ```rust
+/// // route-rs matrix --ids 1,2,3 --format json
+/// matrix::run(&args)?;
+/// ```
+pub fn run(args: &MatrixArgs) -> Result<(), Box<dyn Error>> {
+    let logger = get_logger();
+    let config = get_config().read().unwrap().clone();
+
+    let metric = match args.metric.as_deref() {
+        None => config.routing.metric,
+        Some("time") => Metric::Time,
+        Some("distance") => Metric::Distance,
+        Some(other) => return Err(format!("Unknown metric `{}`", other).into()),
+    };
+
+    let profile_name = args.profile.as_deref().unwrap_or(&config.routing.profile);
+    let profile = config
+        .profile(profile_name)
+        .ok_or_else(|| format!("Unknown profile `{}`", profile_name))?;
+    let options =
+        RouteOptions::from_rules(&args.avoid, &args.prefer, config.routing.prefer_factor)?;
+
+    let mut road_manager = get_road_manager().write().unwrap();
+    let mut intersection_manager = get_intersection_manager().lock().unwrap();
+    road_manager.load(None)?;
+    intersection_manager.load(None)?;
+
+    let ids = match (&args.ids, &args.ids_file) {
+        (Some(ids), _) => parse_ids(ids)?,
+        (None, Some(path)) => parse_ids(&fs::read_to_string(path)?)?,
+        (None, None) => intersection_manager
+            .cache
+            .iter()
+            .flatten()
+            .filter_map(|intersection| intersection._id)
+            .collect(),
+    };
+
+    let mut graph = Graph::from_managers_for_profile(
+        &road_manager,
+        &intersection_manager,
+        config.routing.snap_radius,
+        &profile,
+    );
+    if !options.is_empty() {
+        graph.apply_options(road_manager.cache.as_deref().unwrap_or_default(), &options);
+    }
+    drop(road_manager);
+    drop(intersection_manager);
+
+    let matrix = matrix(&graph, &ids, metric)?;
+
+    let writer: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout().lock()),
+    };
+    let mut writer = BufWriter::new(writer);
+
+    match args.format.as_deref() {
+        None | Some("csv") => matrix.write_csv(&mut writer)?,
+        Some("json") => {
+            matrix.write_json(&mut writer)?;
+            writeln!(writer)?;
+        }
+        Some(other) => return Err(format!("Unknown format `{}`", other).into()),
+    }
+    writer.flush()?;
+
+    // stdout holds the matrix itself, so only report where it went when writing to a file
+    if let Some(path) = &args.output {
+        logger.info(&format!(
+            "(matrix) wrote {0}x{0} matrix of {1} to {2}",
+            ids.len(),
+            get_paths().data_dir.display(),
+            path.display()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Parses intersection ids separated by commas and/or whitespace
+///
+/// @internal
+fn parse_ids(ids: &str) -> Result<Vec<i32>, String> {
+    ids.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|id| !id.is_empty())
+        .map(|id| {
+            id.parse()
+                .map_err(|_| format!("Invalid intersection id `{}`", id))
+        })
+        .collect()
+}
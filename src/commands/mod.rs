@@ -0,0 +1,31 @@
+pub mod assign;
+pub mod contract;
+pub mod isochrone;
+pub mod matrix;
+pub mod route;
+pub mod tour;
+pub mod vrp;
+
+use std::error::Error;
+
+use crate::cli::Command;
+
+/// Runs a command given on the command line instead of opening the window
+///
+/// Example
+/// ```rust
+/// if let Some(command) = &args.command {
+///     commands::run(command)?;
+/// }
+/// ```
+pub fn run(command: &Command) -> Result<(), Box<dyn Error>> {
+    match command {
+        Command::Matrix(args) => matrix::run(args),
+        Command::Isochrone(args) => isochrone::run(args),
+        Command::Assign(args) => assign::run(args),
+        Command::Contract(args) => contract::run(args),
+        Command::Route(args) => route::run(args),
+        Command::Tour(args) => tour::run(args),
+        Command::Vrp(args) => vrp::run(args),
+    }
+}
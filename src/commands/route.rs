@@ -0,0 +1,292 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::cli::RouteArgs;
+use crate::managers::road::Road;
+use crate::managers::schedule::{format_datetime, parse_datetime, parse_time_of_day};
+use crate::routing::astar::astar;
+use crate::routing::dijkstra::fastest_path_at;
+use crate::routing::graph::Graph;
+use crate::routing::hierarchy::ContractionHierarchy;
+use crate::routing::instructions::{self, format_distance, instructions};
+use crate::routing::options::RouteOptions;
+use crate::routing::{Metric, Route, RoutingError};
+use crate::{get_config, get_intersection_manager, get_logger, get_paths, get_road_manager};
+
+/// A pair of intersections to route between, one row of the `--pairs` file
+///
+/// @internal
+#[derive(Debug, serde::Deserialize)]
+struct Pair {
+    from: i32,
+    to: i32,
+}
+
+/// The best route between a pair of intersections, one row of the output
+///
+/// @internal
+#[derive(Debug, serde::Serialize)]
+struct Row {
+    from: i32,
+    to: i32,
+    distance: Option<f64>,
+    time: Option<f64>,
+    /// Ids of the roads driven, separated by spaces
+    roads: String,
+}
+
+/// Writes the best routes between pairs of intersections as csv, JSON or turn-by-turn text, answering them with the
+/// contraction hierarchy stored in the data directory. The hierarchy is rebuilt first if the roads changed since it was
+/// stored. With a departure time, routes follow the speed factors and closures of the roads at that time instead.
+///
+/// Example
+/// ```rust
+/// // route-rs route --pairs trips.csv --output routes.csv
+/// route::run(&args)?;
+/// ```
+pub fn run(args: &RouteArgs) -> Result<(), Box<dyn Error>> {
+    let logger = get_logger();
+    let config = get_config().read().unwrap().clone();
+
+    let metric = match args.metric.as_deref() {
+        None => config.routing.metric,
+        Some("time") => Metric::Time,
+        Some("distance") => Metric::Distance,
+        Some(other) => return Err(format!("Unknown metric `{}`", other).into()),
+    };
+
+    let pairs = match (&args.from, &args.to, &args.pairs) {
+        (Some(from), Some(to), _) => vec![Pair {
+            from: parse_id(from)?,
+            to: parse_id(to)?,
+        }],
+        (_, _, Some(path)) => csv::Reader::from_path(path)?
+            .deserialize()
+            .collect::<csv::Result<Vec<Pair>>>()?,
+        _ => return Err("`route` requires either `--from` and `--to` or `--pairs`".into()),
+    };
+
+    let profile_name = args.profile.as_deref().unwrap_or(&config.routing.profile);
+    let profile = config
+        .profile(profile_name)
+        .ok_or_else(|| format!("Unknown profile `{}`", profile_name))?;
+    let options =
+        RouteOptions::from_rules(&args.avoid, &args.prefer, config.routing.prefer_factor)?;
+
+    let depart = args.depart.as_deref().map(parse_departure).transpose()?;
+    if depart.is_some() && (metric != Metric::Time || !options.is_empty()) {
+        return Err(
+            "`--depart` only finds the fastest route and cannot be used with route options".into(),
+        );
+    }
+
+    let format = args.format.as_deref().unwrap_or("csv");
+    if !["csv", "json", "text"].contains(&format) {
+        return Err(format!("Unknown format `{}`", format).into());
+    }
+
+    let mut road_manager = get_road_manager().write().unwrap();
+    let mut intersection_manager = get_intersection_manager().lock().unwrap();
+    road_manager.load(None)?;
+    intersection_manager.load(None)?;
+    let roads = road_manager.cache.clone().unwrap_or_default();
+
+    let mut graph = Graph::from_managers_for_profile(
+        &road_manager,
+        &intersection_manager,
+        config.routing.snap_radius,
+        &profile,
+    );
+    if !options.is_empty() {
+        graph.apply_options(&roads, &options);
+    }
+    drop(road_manager);
+    drop(intersection_manager);
+
+    // stored hierarchies are built without route options, so routes with options are searched for directly
+    let hierarchy = if options.is_empty() && depart.is_none() {
+        let path = get_paths().data_file(&ContractionHierarchy::file_name(profile_name, metric));
+        Some(ContractionHierarchy::load_or_build(&graph, metric, &path))
+    } else {
+        None
+    };
+
+    let mut routes = Vec::with_capacity(pairs.len());
+    for pair in &pairs {
+        let start = graph
+            .intersection_node(pair.from)
+            .ok_or(RoutingError::UnknownIntersection(pair.from))?;
+        let end = graph
+            .intersection_node(pair.to)
+            .ok_or(RoutingError::UnknownIntersection(pair.to))?;
+
+        routes.push(match &hierarchy {
+            Some(hierarchy) => hierarchy.query(&graph, start, end),
+            None => match depart {
+                Some(depart) => fastest_path_at(&graph, start, end, depart),
+                None => astar(&graph, start, end, metric),
+            },
+        });
+    }
+
+    let writer: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout().lock()),
+    };
+    let mut writer = BufWriter::new(writer);
+
+    match format {
+        "json" => {
+            write_json(&pairs, &routes, &graph, &roads, &mut writer)?;
+            writeln!(writer)?;
+        }
+        "text" => write_text(&pairs, &routes, depart, &graph, &roads, &mut writer)?,
+        _ => write_csv(&pairs, &routes, &mut writer)?,
+    }
+    writer.flush()?;
+
+    // stdout holds the routes themselves, so only report where they went when writing to a file
+    if let Some(path) = &args.output {
+        logger.info(&format!(
+            "(router) wrote {} routes, {} without a route, to {}",
+            pairs.len(),
+            routes.iter().filter(|route| route.is_none()).count(),
+            path.display()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Writes one `from,to,distance,time,roads` row per pair
+///
+/// @internal
+fn write_csv<W: Write>(pairs: &[Pair], routes: &[Option<Route>], writer: W) -> csv::Result<()> {
+    let mut wtr = csv::Writer::from_writer(writer);
+
+    for (pair, route) in pairs.iter().zip(routes) {
+        wtr.serialize(Row {
+            from: pair.from,
+            to: pair.to,
+            distance: route.as_ref().map(|route| route.distance),
+            time: route.as_ref().map(|route| route.time),
+            roads: route.as_ref().map_or(String::new(), |route| {
+                route
+                    .roads
+                    .iter()
+                    .map(|road| road.to_string())
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            }),
+        })?;
+    }
+
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Writes a JSON array with the `from`, `to`, `distance`, `time`, `roads` and turn-by-turn `instructions` of every
+/// pair, `null` where there is no route
+///
+/// @internal
+fn write_json<W: Write>(
+    pairs: &[Pair],
+    routes: &[Option<Route>],
+    graph: &Graph,
+    roads: &[Road],
+    writer: W,
+) -> serde_json::Result<()> {
+    let rows: Vec<serde_json::Value> = pairs
+        .iter()
+        .zip(routes)
+        .map(|(pair, route)| match route {
+            Some(route) => serde_json::json!({
+                "from": pair.from,
+                "to": pair.to,
+                "distance": route.distance,
+                "time": route.time,
+                "roads": route.roads,
+                "instructions": instructions(graph, roads, route),
+            }),
+            None => serde_json::json!({
+                "from": pair.from,
+                "to": pair.to,
+                "distance": null,
+                "time": null,
+                "roads": [],
+                "instructions": [],
+            }),
+        })
+        .collect();
+
+    serde_json::to_writer(writer, &rows)
+}
+
+/// Writes the numbered turn-by-turn instructions of every pair under a line with its distance and time, and when it
+/// departs and arrives if a departure time is given
+///
+/// @internal
+fn write_text<W: Write>(
+    pairs: &[Pair],
+    routes: &[Option<Route>],
+    depart: Option<f64>,
+    graph: &Graph,
+    roads: &[Road],
+    mut writer: W,
+) -> io::Result<()> {
+    for (i, (pair, route)) in pairs.iter().zip(routes).enumerate() {
+        if i > 0 {
+            writeln!(writer)?;
+        }
+
+        match route {
+            Some(route) => {
+                write!(
+                    writer,
+                    "From {} to {}: {}, {:.0} s",
+                    pair.from,
+                    pair.to,
+                    format_distance(route.distance),
+                    route.time
+                )?;
+                match depart {
+                    Some(depart) => writeln!(
+                        writer,
+                        ", departing {} and arriving {}",
+                        format_datetime(depart),
+                        format_datetime(depart + route.time)
+                    )?,
+                    None => writeln!(writer)?,
+                }
+                instructions::write_text(&instructions(graph, roads, route), &mut writer)?;
+            }
+            None => writeln!(writer, "No route from {} to {}", pair.from, pair.to)?,
+        }
+    }
+
+    Ok(())
+}
+
+/// @internal
+fn parse_id(id: &str) -> Result<i32, String> {
+    id.parse()
+        .map_err(|_| format!("Invalid intersection id `{}`", id))
+}
+
+/// Parses a departure time, `YYYY-MM-DD HH:MM` or `HH:MM` for today
+///
+/// @internal
+fn parse_departure(text: &str) -> Result<f64, String> {
+    if text.contains('-') {
+        return parse_datetime(text);
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0.0, |elapsed| elapsed.as_secs_f64());
+    let today = (now / 86_400.0).floor() * 86_400.0;
+
+    Ok(today + parse_time_of_day(text)?)
+}
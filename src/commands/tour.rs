@@ -0,0 +1,107 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::cli::TourArgs;
+use crate::routing::graph::Graph;
+use crate::routing::options::RouteOptions;
+use crate::routing::tour::{optimize_order, route_via};
+use crate::routing::Metric;
+use crate::{get_config, get_intersection_manager, get_logger, get_road_manager};
+
+/// Writes the route through several intersections of the network in the data directory, optionally reordering the
+/// stops to make it as cheap as possible
+///
+/// Example
+/// ```rust
+/// // route-rs tour --stops 1,7,3,9 --optimize --end 1
+/// tour::run(&args)?;
+/// ```
+pub fn run(args: &TourArgs) -> Result<(), Box<dyn Error>> {
+    let logger = get_logger();
+    let config = get_config().read().unwrap().clone();
+
+    let metric = match args.metric.as_deref() {
+        None => config.routing.metric,
+        Some("time") => Metric::Time,
+        Some("distance") => Metric::Distance,
+        Some(other) => return Err(format!("Unknown metric `{}`", other).into()),
+    };
+
+    let stops = args
+        .stops
+        .as_deref()
+        .ok_or("`tour` requires `--stops`")?
+        .split(',')
+        .map(parse_id)
+        .collect::<Result<Vec<i32>, String>>()?;
+    let end = args.end.as_deref().map(parse_id).transpose()?;
+
+    let profile_name = args.profile.as_deref().unwrap_or(&config.routing.profile);
+    let profile = config
+        .profile(profile_name)
+        .ok_or_else(|| format!("Unknown profile `{}`", profile_name))?;
+    let options =
+        RouteOptions::from_rules(&args.avoid, &args.prefer, config.routing.prefer_factor)?;
+
+    let mut road_manager = get_road_manager().write().unwrap();
+    let mut intersection_manager = get_intersection_manager().lock().unwrap();
+    road_manager.load(None)?;
+    intersection_manager.load(None)?;
+
+    let mut graph = Graph::from_managers_for_profile(
+        &road_manager,
+        &intersection_manager,
+        config.routing.snap_radius,
+        &profile,
+    );
+    if !options.is_empty() {
+        graph.apply_options(road_manager.cache.as_deref().unwrap_or_default(), &options);
+    }
+    drop(road_manager);
+    drop(intersection_manager);
+
+    let tour = if args.optimize {
+        optimize_order(&graph, stops[0], &stops[1..], end, metric)?
+    } else {
+        let mut stops = stops;
+        stops.extend(end);
+        route_via(&graph, &stops, metric)?
+    };
+
+    let writer: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout().lock()),
+    };
+    let mut writer = BufWriter::new(writer);
+
+    match args.format.as_deref() {
+        None | Some("csv") => tour.write_csv(&mut writer)?,
+        Some("json") => {
+            tour.write_json(&mut writer)?;
+            writeln!(writer)?;
+        }
+        Some(other) => return Err(format!("Unknown format `{}`", other).into()),
+    }
+    writer.flush()?;
+
+    // stdout holds the tour itself, so only report where it went when writing to a file
+    if let Some(path) = &args.output {
+        logger.info(&format!(
+            "(router) wrote tour through {:?}, {:.0} m, {:.0} s, to {}",
+            tour.stops,
+            tour.distance,
+            tour.time,
+            path.display()
+        ));
+    }
+
+    Ok(())
+}
+
+/// @internal
+fn parse_id(id: &str) -> Result<i32, String> {
+    id.trim()
+        .parse()
+        .map_err(|_| format!("Invalid intersection id `{}`", id))
+}
@@ -0,0 +1,91 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::cli::VrpArgs;
+use crate::routing::graph::Graph;
+use crate::routing::vrp::{load_stops, load_vehicles, solve};
+use crate::{get_config, get_intersection_manager, get_logger, get_paths, get_road_manager};
+
+/// Plans the tours of a delivery fleet over the network in the data directory and writes the visits of every
+/// vehicle
+///
+/// Example
+/// ```rust
+/// // route-rs vrp --depot 4 --format json --output tours.json
+/// vrp::run(&args)?;
+/// ```
+pub fn run(args: &VrpArgs) -> Result<(), Box<dyn Error>> {
+    let logger = get_logger();
+    let config = get_config().read().unwrap().clone();
+
+    let depot = match args.depot.as_deref() {
+        Some(depot) => depot
+            .parse()
+            .map_err(|_| format!("Invalid intersection id `{}`", depot))?,
+        None => config.vrp.depot,
+    };
+
+    let vehicles_path = args
+        .vehicles
+        .clone()
+        .unwrap_or_else(|| get_paths().data_file("vehicles.csv"));
+    let stops_path = args
+        .stops
+        .clone()
+        .unwrap_or_else(|| get_paths().data_file("stops.csv"));
+    let vehicles = load_vehicles(&vehicles_path)?;
+    let stops = load_stops(&stops_path)?;
+
+    let profile_name = args.profile.as_deref().unwrap_or(&config.routing.profile);
+    let profile = config
+        .profile(profile_name)
+        .ok_or_else(|| format!("Unknown profile `{}`", profile_name))?;
+
+    let mut road_manager = get_road_manager().write().unwrap();
+    let mut intersection_manager = get_intersection_manager().lock().unwrap();
+    road_manager.load(None)?;
+    intersection_manager.load(None)?;
+
+    let graph = Graph::from_managers_for_profile(
+        &road_manager,
+        &intersection_manager,
+        config.routing.snap_radius,
+        &profile,
+    );
+    drop(road_manager);
+    drop(intersection_manager);
+
+    let solution = solve(&graph, depot, &vehicles, &stops, &config.vrp)?;
+
+    let writer: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout().lock()),
+    };
+    let mut writer = BufWriter::new(writer);
+
+    match args.format.as_deref() {
+        None | Some("csv") => solution.write_csv(&mut writer)?,
+        Some("json") => {
+            solution.write_json(&mut writer)?;
+            writeln!(writer)?;
+        }
+        Some(other) => return Err(format!("Unknown format `{}`", other).into()),
+    }
+    writer.flush()?;
+
+    // stdout holds the tours themselves, so only report where they went when writing to a file
+    if let Some(path) = &args.output {
+        logger.info(&format!(
+            "(router) wrote {} tours, {:.0} m, {:.0} s, {:.0} s late, {} stops unserved, to {}",
+            solution.tours.len(),
+            solution.distance,
+            solution.time,
+            solution.lateness,
+            solution.unserved.len(),
+            path.display()
+        ));
+    }
+
+    Ok(())
+}
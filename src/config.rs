@@ -0,0 +1,932 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use piston::input::Key;
+
+use crate::constants::colors::*;
+use crate::get_logger;
+use crate::helpers::logger::{LogFormat, LogLevel, RotatingFile, Sink};
+use crate::managers::intersection::Control;
+use crate::managers::road::Direction;
+use crate::routing::profile::{Profile, BUILTIN_PROFILES};
+use crate::routing::Metric;
+
+/// The config written on first run. Keep the values in sync with the `Default` implementations below.
+pub const DEFAULT_CONFIG: &str = r##"# route-rs configuration
+#
+# This file is reloaded automatically while route-rs is running.
+# Removing a key restores its default value.
+
+# Directories route-rs reads from and writes to. These are only read at startup.
+# [paths]
+# data_dir = "data"
+# assets_dir = "assets"
+# sample_dir = "sample"
+
+[editor]
+# Defaults for newly drawn roads
+road_name = "test"
+lane_count = 1.0
+speed_limit = 50.0
+road_type = "asphalt"
+# "both", "forward" (one-way in the drawing direction) or "backward"
+direction = "both"
+# Control of newly placed intersections: "uncontrolled", "all_way_stop", "two_way_stop", "yield",
+# "traffic_signals" or "roundabout"
+intersection_control = "uncontrolled"
+
+# How close (in pixels) the cursor has to be to destroy a road or intersection
+road_hit_radius = 5.0
+intersection_hit_radius = 6.0
+
+# Number of actions that can be undone
+history_depth = 15
+
+[routing]
+# Road endpoints closer than this (in pixels) to an intersection or another endpoint are connected
+snap_radius = 6.0
+# What routes are optimized for, "distance" or "time"
+metric = "time"
+# Who the routes are for: "car", "bicycle", "pedestrian" or a profile from the [profiles] table
+profile = "car"
+# Factor the cost of roads passed to --prefer is multiplied by, unless the rule gives its own
+prefer_factor = 0.8
+
+# Profiles of your own, or changes to the built-in ones. A profile starts out as the car profile.
+# [profiles.scooter]
+# # Speed in km/h per road type, roads of other types are not used
+# speeds = { asphalt = 25.0, gravel = 15.0 }
+# # Whether speeds are capped at the speed limit of the road
+# speed_limit = true
+# # Whether one-way roads may only be used in their direction of travel
+# oneway = true
+# # Factor for the expected wait at stop signs, yield signs, signals and roundabouts
+# delay_factor = 1.0
+# # Seconds added for passing through an intersection
+# intersection_penalty = 2.0
+
+[alternatives]
+# Number of routes shown in the route state, 1 for only the best route
+count = 3
+# Highest share of a route's length that may be shared with a route shown before it
+max_overlap = 0.6
+# Routes costing more than max_stretch times the best route are not shown
+max_stretch = 1.5
+# Factor the cost of the roads of each route found is multiplied by before searching for the next
+penalty = 1.4
+
+[vrp]
+# Intersection the delivery vehicles leave from and return to
+depot = 1
+# Cost of serving a stop one second late, in seconds of driving
+lateness_penalty = 10.0
+# Stops that would be served later than this (in seconds) after their latest time are left unserved
+max_lateness = 1800.0
+# Rounds of improving the tours after building them
+iterations = 50
+
+[simulation]
+# Simulated seconds per step, and simulated seconds per real second
+step = 0.1
+speed = 1.0
+# A vehicle is sent between two random intersections every spawn_interval simulated seconds
+spawn_interval = 2.0
+max_vehicles = 200
+# Runs with the same seed and network spawn the same vehicles
+seed = 1
+
+[isochrone]
+# What the budgets are in: "time" for seconds or "distance" for meters
+metric = "time"
+# Each budget is drawn as its own band
+budgets = [60.0, 120.0, 300.0]
+# Longest edge (in pixels, above 0) of exported polygons where the roads allow it, smaller values follow the roads closer
+hull_edge = 20.0
+
+[assignment]
+# Volume-delay function: travel time = free flow time * (1 + alpha * (volume / capacity) ^ beta)
+alpha = 0.15
+beta = 4.0
+# The assignment stops after max_iterations, or once the relative gap drops below gap
+max_iterations = 100
+gap = 0.0001
+# Vehicles per hour per lane for road types not listed below
+default_capacity = 1000.0
+
+# Vehicles per hour per lane for each road_type
+[assignment.capacities]
+asphalt = 1800.0
+gravel = 1000.0
+dirt = 600.0
+
+[logging]
+# One of "trace", "debug", "info", "warn" or "error". The ROUTE_RS_LOG environment variable takes precedence.
+level = "trace"
+
+# "text" for colored, human readable lines or "json" for one JSON object per line
+format = "text"
+
+# Per-target levels. A target is the name in front of a message, such as "roadmanager" in
+# "(roadmanager) load roads", or a module path such as "route_rs::managers" which includes its submodules.
+# [logging.filters]
+# roadmanager = "warn"
+# "route_rs::managers::history" = "info"
+
+# Where log lines are written to, any of "stdout", "stderr" and "file"
+sinks = ["stdout"]
+
+# The log file, relative paths are resolved against the data directory
+file = "route-rs.log"
+
+# Size in bytes after which the log file is rotated, and the number of rotated files that are kept
+max_file_size = 10485760
+max_files = 5
+
+# Number of recent messages kept for the in-window console
+console_lines = 200
+
+[keybindings]
+# Key names as listed by piston, e.g. "Space", "Z", "Tab", "F1"
+cycle_state = "Space"
+undo = "Z"
+save = "S"
+toggle_console = "Backquote"
+# Cycles the control type of the intersection under the cursor
+cycle_control = "C"
+# Starts or stops the traffic simulation
+toggle_simulation = "P"
+# Shows or hides the traffic assignment heatmap
+toggle_heatmap = "H"
+# Writes the isochrone shown to isochrone.geojson in the data directory
+export_isochrone = "E"
+# Picks the next of the alternative routes shown
+cycle_route = "Tab"
+# Switches to the next routing profile in the routing state
+cycle_profile = "R"
+# Plans the delivery tours of vehicles.csv and stops.csv, or hides them
+toggle_deliveries = "V"
+
+[colors]
+# "#rrggbb" or "#rrggbbaa"
+asphalt = "#282828"
+dirt = "#3b2e26"
+gravel = "#323232"
+unknown_road = "#000000"
+intersection = "#999999"
+background = "#121212"
+text = "#ffffff"
+warn = "#e5c07b"
+error = "#e06c75"
+console_background = "#000000cc"
+toast_background = "#1e1e1ee6"
+arrow = "#b0b0b0"
+route = "#4fc3f7"
+forbidden_turn = "#e06c75"
+mandatory_turn = "#98c379"
+stop_sign = "#c0392b"
+yield_sign = "#f5f5f5"
+signal_housing = "#2b2b2b"
+signal_red = "#e74c3c"
+signal_amber = "#f1c40f"
+signal_green = "#2ecc71"
+vehicle = "#ffd166"
+# Roads in the traffic assignment heatmap, from empty through half to full capacity
+heatmap_low = "#2ecc71"
+heatmap_mid = "#f1c40f"
+heatmap_high = "#e74c3c"
+# Isochrone bands, from the smallest to the largest budget
+isochrone_near = "#7cf29c"
+isochrone_far = "#2c6e91"
+# Alternative routes that are not picked, used in turn
+alternative_routes = ["#ffb74d", "#ba68c8", "#81c784"]
+# Delivery tours, one per vehicle in turn, and the stops no vehicle serves
+delivery_tours = ["#ff8a65", "#4dd0e1", "#aed581", "#f06292", "#9575cd"]
+unserved_stop = "#e06c75"
+"##;
+
+const RELOAD_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Settings for the editor in the window
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct EditorConfig {
+    pub road_name: String,
+    pub lane_count: f64,
+    pub speed_limit: f64,
+    pub road_type: String,
+    pub direction: Direction,
+    pub intersection_control: Control,
+    pub road_hit_radius: f64,
+    pub intersection_hit_radius: f64,
+    pub history_depth: usize,
+}
+
+impl Default for EditorConfig {
+    fn default() -> Self {
+        Self {
+            road_name: "test".to_string(),
+            lane_count: 1.0,
+            speed_limit: 50.0,
+            road_type: "asphalt".to_string(),
+            direction: Direction::Both,
+            intersection_control: Control::Uncontrolled,
+            road_hit_radius: 5.0,
+            intersection_hit_radius: 6.0,
+            history_depth: 15,
+        }
+    }
+}
+
+/// Settings for route computation
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct RoutingConfig {
+    pub snap_radius: f64,
+    pub metric: Metric,
+    /// Name of the routing profile, see `Config::profile`
+    pub profile: String,
+    pub prefer_factor: f64,
+}
+
+impl Default for RoutingConfig {
+    fn default() -> Self {
+        Self {
+            snap_radius: 6.0,
+            metric: Metric::Time,
+            profile: "car".to_string(),
+            prefer_factor: 0.8,
+        }
+    }
+}
+
+/// Settings for alternative routes
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct AlternativesConfig {
+    pub count: usize,
+    pub max_overlap: f64,
+    pub max_stretch: f64,
+    pub penalty: f64,
+}
+
+impl Default for AlternativesConfig {
+    fn default() -> Self {
+        Self {
+            count: 3,
+            max_overlap: 0.6,
+            max_stretch: 1.5,
+            penalty: 1.4,
+        }
+    }
+}
+
+/// Settings for vehicle routing
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct VrpConfig {
+    pub depot: i32,
+    pub lateness_penalty: f64,
+    pub max_lateness: f64,
+    pub iterations: usize,
+}
+
+impl Default for VrpConfig {
+    fn default() -> Self {
+        Self {
+            depot: 1,
+            lateness_penalty: 10.0,
+            max_lateness: 1800.0,
+            iterations: 50,
+        }
+    }
+}
+
+/// Settings for the traffic simulation
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct SimulationConfig {
+    pub step: f64,
+    pub speed: f64,
+    pub spawn_interval: f64,
+    pub max_vehicles: usize,
+    pub seed: u64,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            step: 0.1,
+            speed: 1.0,
+            spawn_interval: 2.0,
+            max_vehicles: 200,
+            seed: 1,
+        }
+    }
+}
+
+/// Settings for isochrones
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct IsochroneConfig {
+    pub metric: Metric,
+    pub budgets: Vec<f64>,
+    pub hull_edge: f64,
+}
+
+impl Default for IsochroneConfig {
+    fn default() -> Self {
+        Self {
+            metric: Metric::Time,
+            budgets: vec![60.0, 120.0, 300.0],
+            hull_edge: 20.0,
+        }
+    }
+}
+
+/// Settings for the traffic assignment
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct AssignmentConfig {
+    pub alpha: f64,
+    pub beta: f64,
+    pub max_iterations: usize,
+    pub gap: f64,
+    pub default_capacity: f64,
+    pub capacities: BTreeMap<String, f64>,
+}
+
+impl Default for AssignmentConfig {
+    fn default() -> Self {
+        Self {
+            alpha: 0.15,
+            beta: 4.0,
+            max_iterations: 100,
+            gap: 0.0001,
+            default_capacity: 1000.0,
+            capacities: BTreeMap::from([
+                ("asphalt".to_string(), 1800.0),
+                ("gravel".to_string(), 1000.0),
+                ("dirt".to_string(), 600.0),
+            ]),
+        }
+    }
+}
+
+impl AssignmentConfig {
+    /// Returns the capacity in vehicles per hour of a road direction with the given type and number of lanes.
+    /// Directions count as at least half a lane, so a single-lane two-way road still carries traffic both ways.
+    ///
+    /// Example
+    /// ```rust
+    /// config.assignment.capacity("asphalt", 2.0); // 3600.0
+    /// ```
+    pub fn capacity(&self, road_type: &str, lanes: f64) -> f64 {
+        let per_lane = self
+            .capacities
+            .get(road_type)
+            .copied()
+            .unwrap_or(self.default_capacity);
+
+        per_lane * lanes.max(0.5)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SinkKind {
+    Stdout,
+    Stderr,
+    File,
+}
+
+/// Settings for the logger
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    pub level: LogLevel,
+    pub format: LogFormat,
+    pub filters: BTreeMap<String, LogLevel>,
+    pub sinks: Vec<SinkKind>,
+    pub file: PathBuf,
+    pub max_file_size: u64,
+    pub max_files: usize,
+    pub console_lines: usize,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: LogLevel::Trace,
+            format: LogFormat::Text,
+            filters: BTreeMap::new(),
+            sinks: vec![SinkKind::Stdout],
+            file: PathBuf::from("route-rs.log"),
+            max_file_size: 10 * 1024 * 1024,
+            max_files: 5,
+            console_lines: 200,
+        }
+    }
+}
+
+impl LoggingConfig {
+    /// Applies the level and sinks to the global logger. The `ROUTE_RS_LOG` environment variable overrides the level.
+    ///
+    /// Example
+    /// ```rust
+    /// config.logging.apply(); // the logger now writes to the configured sinks
+    /// ```
+    pub fn apply(&self) {
+        let logger = get_logger();
+        logger.set_level(LogLevel::from_env().unwrap_or(self.level));
+        logger.set_format(self.format);
+        logger.set_recent_capacity(self.console_lines);
+        logger.set_filters(
+            self.filters
+                .iter()
+                .map(|(target, level)| (target.clone(), *level))
+                .collect(),
+        );
+
+        let mut sinks = Vec::new();
+        for kind in &self.sinks {
+            match kind {
+                SinkKind::Stdout => sinks.push(Sink::Stdout),
+                SinkKind::Stderr => sinks.push(Sink::Stderr),
+                SinkKind::File => {
+                    let path = crate::get_paths().data_dir.join(&self.file);
+
+                    match RotatingFile::open(&path, self.max_file_size, self.max_files) {
+                        Ok(file) => sinks.push(Sink::File(file)),
+                        Err(err) => eprintln!(
+                            "[logger] unable to open log file {}: {}",
+                            path.display(),
+                            err
+                        ),
+                    }
+                }
+            }
+        }
+
+        logger.set_sinks(sinks);
+    }
+}
+
+/// Keys bound to editor actions
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct Keybindings {
+    pub cycle_state: Key,
+    pub undo: Key,
+    pub save: Key,
+    pub toggle_console: Key,
+    pub cycle_control: Key,
+    pub toggle_simulation: Key,
+    pub toggle_heatmap: Key,
+    pub export_isochrone: Key,
+    pub cycle_route: Key,
+    pub cycle_profile: Key,
+    pub toggle_deliveries: Key,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Self {
+            cycle_state: Key::Space,
+            undo: Key::Z,
+            save: Key::S,
+            toggle_console: Key::Backquote,
+            cycle_control: Key::C,
+            toggle_simulation: Key::P,
+            toggle_heatmap: Key::H,
+            export_isochrone: Key::E,
+            cycle_route: Key::Tab,
+            cycle_profile: Key::R,
+            toggle_deliveries: Key::V,
+        }
+    }
+}
+
+/// Colors used to render the window
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct Colors {
+    #[serde(with = "hex_color")]
+    pub asphalt: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub dirt: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub gravel: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub unknown_road: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub intersection: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub background: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub text: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub warn: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub error: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub console_background: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub toast_background: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub arrow: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub route: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub forbidden_turn: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub mandatory_turn: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub stop_sign: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub yield_sign: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub signal_housing: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub signal_red: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub signal_amber: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub signal_green: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub vehicle: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub heatmap_low: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub heatmap_mid: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub heatmap_high: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub isochrone_near: [f32; 4],
+    #[serde(with = "hex_color")]
+    pub isochrone_far: [f32; 4],
+    #[serde(with = "hex_colors")]
+    pub alternative_routes: Vec<[f32; 4]>,
+    #[serde(with = "hex_colors")]
+    pub delivery_tours: Vec<[f32; 4]>,
+    #[serde(with = "hex_color")]
+    pub unserved_stop: [f32; 4],
+}
+
+impl Default for Colors {
+    fn default() -> Self {
+        Self {
+            asphalt: COLOR_ASPHALT,
+            dirt: COLOR_DIRT,
+            gravel: COLOR_GRAVEL,
+            unknown_road: COLOR_SOLID_BLACK,
+            intersection: COLOR_INTERSECTION,
+            background: COLOR_BACKGROUND,
+            text: COLOR_SOLID_WHITE,
+            warn: COLOR_WARN,
+            error: COLOR_ERROR,
+            console_background: COLOR_CONSOLE_BACKGROUND,
+            toast_background: COLOR_TOAST_BACKGROUND,
+            arrow: COLOR_ARROW,
+            route: COLOR_ROUTE,
+            forbidden_turn: COLOR_FORBIDDEN_TURN,
+            mandatory_turn: COLOR_MANDATORY_TURN,
+            stop_sign: COLOR_STOP_SIGN,
+            yield_sign: COLOR_YIELD_SIGN,
+            signal_housing: COLOR_SIGNAL_HOUSING,
+            signal_red: COLOR_SIGNAL_RED,
+            signal_amber: COLOR_SIGNAL_AMBER,
+            signal_green: COLOR_SIGNAL_GREEN,
+            vehicle: COLOR_VEHICLE,
+            heatmap_low: COLOR_HEATMAP_LOW,
+            heatmap_mid: COLOR_HEATMAP_MID,
+            heatmap_high: COLOR_HEATMAP_HIGH,
+            isochrone_near: COLOR_ISOCHRONE_NEAR,
+            isochrone_far: COLOR_ISOCHRONE_FAR,
+            alternative_routes: COLOR_ALTERNATIVE_ROUTES.to_vec(),
+            delivery_tours: COLOR_DELIVERY_TOURS.to_vec(),
+            unserved_stop: COLOR_UNSERVED_STOP,
+        }
+    }
+}
+
+impl Colors {
+    /// Returns the color a road of the given type is drawn with
+    ///
+    /// Example
+    /// ```rust
+    /// let color = config.colors.road("dirt"); // config.colors.dirt
+    /// ```
+    pub fn road(&self, road_type: &str) -> [f32; 4] {
+        match road_type {
+            "asphalt" => self.asphalt,
+            "dirt" => self.dirt,
+            "gravel" => self.gravel,
+            _ => self.unknown_road,
+        }
+    }
+
+    /// Returns the heatmap color of a road at the given volume/capacity ratio, blending from `heatmap_low` at 0
+    /// through `heatmap_mid` at 0.5 to `heatmap_high` at 1 and above
+    ///
+    /// Example
+    /// ```rust
+    /// let color = config.colors.volume_capacity(0.75); // halfway between heatmap_mid and heatmap_high
+    /// ```
+    pub fn volume_capacity(&self, ratio: f64) -> [f32; 4] {
+        let ratio = ratio.clamp(0.0, 1.0) as f32;
+        let (from, to, t) = if ratio < 0.5 {
+            (self.heatmap_low, self.heatmap_mid, ratio * 2.0)
+        } else {
+            (self.heatmap_mid, self.heatmap_high, ratio * 2.0 - 1.0)
+        };
+
+        blend(from, to, t)
+    }
+
+    /// Returns the color of one of `count` isochrone bands, blending from `isochrone_near` for the first band to
+    /// `isochrone_far` for the last
+    pub fn isochrone_band(&self, band: usize, count: usize) -> [f32; 4] {
+        let t = if count > 1 {
+            band as f32 / (count - 1) as f32
+        } else {
+            0.0
+        };
+
+        blend(self.isochrone_near, self.isochrone_far, t)
+    }
+
+    /// Returns the color of an alternative route that is not picked, going through `alternative_routes` in turn.
+    /// Falls back to `route` if no colors are configured.
+    pub fn alternative_route(&self, index: usize) -> [f32; 4] {
+        if self.alternative_routes.is_empty() {
+            return self.route;
+        }
+
+        self.alternative_routes[index % self.alternative_routes.len()]
+    }
+
+    /// Returns the color of the tour of the `index`th vehicle, going through `delivery_tours` in turn. Falls back to
+    /// `route` if no colors are configured.
+    pub fn delivery_tour(&self, index: usize) -> [f32; 4] {
+        if self.delivery_tours.is_empty() {
+            return self.route;
+        }
+
+        self.delivery_tours[index % self.delivery_tours.len()]
+    }
+
+    /// Returns the color a log message of the given level is drawn with
+    pub fn log_level(&self, level: LogLevel) -> [f32; 4] {
+        match level {
+            LogLevel::Warn => self.warn,
+            LogLevel::Error => self.error,
+            _ => self.text,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub editor: EditorConfig,
+    pub routing: RoutingConfig,
+    /// User-defined routing profiles by name
+    pub profiles: BTreeMap<String, Profile>,
+    pub simulation: SimulationConfig,
+    pub isochrone: IsochroneConfig,
+    pub assignment: AssignmentConfig,
+    pub alternatives: AlternativesConfig,
+    pub vrp: VrpConfig,
+    pub logging: LoggingConfig,
+    pub keybindings: Keybindings,
+    pub colors: Colors,
+}
+
+impl Config {
+    /// Loads the config file, writing the default config to it first if it does not exist yet.
+    /// Falls back to the default config if the file cannot be read.
+    ///
+    /// Example
+    /// ```rust
+    /// let config = Config::load_or_create(&get_paths().config_file);
+    /// ```
+    pub fn load_or_create(path: &Path) -> Config {
+        if !path.exists() {
+            let written = path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::write(path, DEFAULT_CONFIG));
+
+            match written {
+                Ok(_) => get_logger().info(&format!(
+                    "(config) wrote default config to {}",
+                    path.display()
+                )),
+                Err(err) => get_logger().warn(&format!(
+                    "(config) unable to write default config to {}: {}",
+                    path.display(),
+                    err
+                )),
+            }
+        }
+
+        Config::load(path).unwrap_or_default()
+    }
+
+    /// Returns the routing profile with the given name from the `[profiles]` table, or the built-in profile of that
+    /// name if the table does not define it
+    ///
+    /// Example
+    /// ```rust
+    /// let profile = config.profile(&config.routing.profile).unwrap_or_default();
+    /// ```
+    pub fn profile(&self, name: &str) -> Option<Profile> {
+        self.profiles
+            .get(name)
+            .cloned()
+            .or_else(|| Profile::builtin(name))
+    }
+
+    /// Returns the names of all routing profiles, the built-in ones first
+    pub fn profile_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = BUILTIN_PROFILES
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        names.extend(
+            self.profiles
+                .keys()
+                .filter(|name| !BUILTIN_PROFILES.contains(&name.as_str()))
+                .cloned(),
+        );
+        names
+    }
+
+    /// Loads the config file, logging and returning `None` if it cannot be read or parsed or holds invalid values
+    ///
+    /// Example
+    /// ```rust
+    /// let config = Config::load(Path::new("config.toml")).unwrap_or_default();
+    /// ```
+    pub fn load(path: &Path) -> Option<Config> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) => {
+                get_logger().warn(&format!(
+                    "(config) unable to read {}: {}",
+                    path.display(),
+                    err
+                ));
+                return None;
+            }
+        };
+
+        match Config::parse(&contents) {
+            Ok(config) => Some(config),
+            Err(err) => {
+                get_logger().error(&format!(
+                    "(config) unable to parse {}: {}",
+                    path.display(),
+                    err
+                ));
+                None
+            }
+        }
+    }
+
+    /// Parses the contents of a config file and checks its values
+    ///
+    /// Example
+    /// ```rust
+    /// let config = Config::parse("[routing]\nmetric = \"distance\"")?;
+    /// ```
+    pub fn parse(contents: &str) -> Result<Config, String> {
+        let config: Config = toml::from_str(contents).map_err(|err| err.to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks the values the parser cannot, e.g. lengths that have to be positive
+    ///
+    /// @internal
+    fn validate(&self) -> Result<(), String> {
+        // a hull edge of 0 would split every road into infinitely many points
+        if !(self.isochrone.hull_edge > 0.0 && self.isochrone.hull_edge.is_finite()) {
+            return Err(format!(
+                "`hull_edge` in `[isochrone]` must be above 0, not {}",
+                self.isochrone.hull_edge
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Watches the config file and reloads it when it changes on disk
+pub struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    last_check: Instant,
+}
+
+impl ConfigWatcher {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            modified: modified(path),
+            last_check: Instant::now(),
+        }
+    }
+
+    /// Checks the config file at most once per second. When it has changed and parses, the global config is
+    /// replaced and the new config is returned.
+    ///
+    /// Example
+    /// ```rust
+    /// if let Some(new_config) = watcher.poll() {
+    ///     config = new_config;
+    /// }
+    /// ```
+    pub fn poll(&mut self) -> Option<Config> {
+        if self.last_check.elapsed() < RELOAD_INTERVAL {
+            return None;
+        }
+        self.last_check = Instant::now();
+
+        let modified = modified(&self.path);
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+
+        let config = Config::load(&self.path)?;
+        config.logging.apply();
+        *crate::get_config().write().unwrap() = config.clone();
+        get_logger().info("(config) reloaded config");
+
+        Some(config)
+    }
+}
+
+/// Blends two colors, `t` = 0 gives `from` and `t` = 1 gives `to`
+///
+/// @internal
+fn blend(from: [f32; 4], to: [f32; 4], t: f32) -> [f32; 4] {
+    let mut color = [0.0; 4];
+    for (i, channel) in color.iter_mut().enumerate() {
+        *channel = from[i] + (to[i] - from[i]) * t;
+    }
+    color
+}
+
+/// @internal
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Deserializes colors written as `#rrggbb` or `#rrggbbaa` strings
+///
+/// @internal
+mod hex_color {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[f32; 4], D::Error> {
+        parse(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+
+    pub fn parse(value: &str) -> Result<[f32; 4], String> {
+        let hex = value.strip_prefix('#').unwrap_or(value);
+
+        if !hex.is_ascii() || !(hex.len() == 6 || hex.len() == 8) {
+            return Err(format!("invalid color `{}`", value));
+        }
+
+        let mut color = [1.0; 4];
+        for (i, channel) in color.iter_mut().enumerate().take(hex.len() / 2) {
+            let byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+                .map_err(|_| format!("invalid color `{}`", value))?;
+            *channel = byte as f32 / 255.0;
+        }
+
+        Ok(color)
+    }
+}
+
+/// A list of colors written like `hex_color`
+///
+/// @internal
+mod hex_colors {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<[f32; 4]>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|value| super::hex_color::parse(value).map_err(D::Error::custom))
+            .collect()
+    }
+}
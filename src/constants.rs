@@ -8,4 +8,38 @@ pub mod colors {
     pub const COLOR_SOLID_BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
     pub const COLOR_SOLID_WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
     pub const COLOR_BACKGROUND: [f32; 4] = [18.0 / 255.0, 18.0 / 255.0, 18.0 / 255.0, 1.0];
+    pub const COLOR_WARN: [f32; 4] = [229.0 / 255.0, 192.0 / 255.0, 123.0 / 255.0, 1.0];
+    pub const COLOR_ERROR: [f32; 4] = [224.0 / 255.0, 108.0 / 255.0, 117.0 / 255.0, 1.0];
+    pub const COLOR_CONSOLE_BACKGROUND: [f32; 4] = [0.0, 0.0, 0.0, 204.0 / 255.0];
+    pub const COLOR_ARROW: [f32; 4] = [176.0 / 255.0, 176.0 / 255.0, 176.0 / 255.0, 1.0];
+    pub const COLOR_ROUTE: [f32; 4] = [79.0 / 255.0, 195.0 / 255.0, 247.0 / 255.0, 1.0];
+    pub const COLOR_FORBIDDEN_TURN: [f32; 4] = [224.0 / 255.0, 108.0 / 255.0, 117.0 / 255.0, 1.0];
+    pub const COLOR_MANDATORY_TURN: [f32; 4] = [152.0 / 255.0, 195.0 / 255.0, 121.0 / 255.0, 1.0];
+    pub const COLOR_STOP_SIGN: [f32; 4] = [192.0 / 255.0, 57.0 / 255.0, 43.0 / 255.0, 1.0];
+    pub const COLOR_YIELD_SIGN: [f32; 4] = [245.0 / 255.0, 245.0 / 255.0, 245.0 / 255.0, 1.0];
+    pub const COLOR_SIGNAL_HOUSING: [f32; 4] = [43.0 / 255.0, 43.0 / 255.0, 43.0 / 255.0, 1.0];
+    pub const COLOR_SIGNAL_RED: [f32; 4] = [231.0 / 255.0, 76.0 / 255.0, 60.0 / 255.0, 1.0];
+    pub const COLOR_SIGNAL_AMBER: [f32; 4] = [241.0 / 255.0, 196.0 / 255.0, 15.0 / 255.0, 1.0];
+    pub const COLOR_SIGNAL_GREEN: [f32; 4] = [46.0 / 255.0, 204.0 / 255.0, 113.0 / 255.0, 1.0];
+    pub const COLOR_VEHICLE: [f32; 4] = [1.0, 209.0 / 255.0, 102.0 / 255.0, 1.0];
+    pub const COLOR_HEATMAP_LOW: [f32; 4] = [46.0 / 255.0, 204.0 / 255.0, 113.0 / 255.0, 1.0];
+    pub const COLOR_HEATMAP_MID: [f32; 4] = [241.0 / 255.0, 196.0 / 255.0, 15.0 / 255.0, 1.0];
+    pub const COLOR_HEATMAP_HIGH: [f32; 4] = [231.0 / 255.0, 76.0 / 255.0, 60.0 / 255.0, 1.0];
+    pub const COLOR_ISOCHRONE_NEAR: [f32; 4] = [124.0 / 255.0, 242.0 / 255.0, 156.0 / 255.0, 1.0];
+    pub const COLOR_ISOCHRONE_FAR: [f32; 4] = [44.0 / 255.0, 110.0 / 255.0, 145.0 / 255.0, 1.0];
+    pub const COLOR_ALTERNATIVE_ROUTES: [[f32; 4]; 3] = [
+        [1.0, 183.0 / 255.0, 77.0 / 255.0, 1.0],
+        [186.0 / 255.0, 104.0 / 255.0, 200.0 / 255.0, 1.0],
+        [129.0 / 255.0, 199.0 / 255.0, 132.0 / 255.0, 1.0],
+    ];
+    pub const COLOR_DELIVERY_TOURS: [[f32; 4]; 5] = [
+        [1.0, 138.0 / 255.0, 101.0 / 255.0, 1.0],
+        [77.0 / 255.0, 208.0 / 255.0, 225.0 / 255.0, 1.0],
+        [174.0 / 255.0, 213.0 / 255.0, 129.0 / 255.0, 1.0],
+        [240.0 / 255.0, 98.0 / 255.0, 146.0 / 255.0, 1.0],
+        [149.0 / 255.0, 117.0 / 255.0, 205.0 / 255.0, 1.0],
+    ];
+    pub const COLOR_UNSERVED_STOP: [f32; 4] = [224.0 / 255.0, 108.0 / 255.0, 117.0 / 255.0, 1.0];
+    pub const COLOR_TOAST_BACKGROUND: [f32; 4] =
+        [30.0 / 255.0, 30.0 / 255.0, 30.0 / 255.0, 230.0 / 255.0];
 }
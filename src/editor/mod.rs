@@ -0,0 +1,837 @@
+pub mod session;
+
+use piston::input::{Button, GenericEvent, MouseButton};
+
+use crate::config::Config;
+use crate::managers::history::HistoryManager;
+use crate::managers::intersection::{Intersection, RestrictionKind, TurnRestriction};
+use crate::managers::road::Road;
+use crate::routing::alternatives::alternatives;
+use crate::routing::assignment::{assign, load_demand, RoadFlow};
+use crate::routing::astar::astar_departing;
+use crate::routing::graph::Graph;
+use crate::routing::instructions::instructions;
+use crate::routing::isochrone::{isochrone, write_geojson, Band};
+use crate::routing::profile::Profile;
+use crate::routing::snap::{snap, Position};
+use crate::routing::vrp::{load_stops, load_vehicles, solve, Solution};
+use crate::routing::Route;
+use crate::simulation::Simulation;
+use crate::{get_intersection_manager, get_logger, get_paths, get_road_manager};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum States {
+    DrawRoad,
+    DrawIntersection,
+    Destroy,
+    Route,
+    Restrict,
+    Isochrone,
+}
+
+/// An input event the editor reacts to. These are what sessions record.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputEvent {
+    /// The cursor moved to a position in the window
+    Move([f64; 2]),
+    /// A mouse button or key was pressed
+    Press(Button),
+    /// A mouse button or key was released
+    Release(Button),
+}
+
+impl InputEvent {
+    /// Returns the input event of a piston event, or `None` for events the editor does not react to
+    ///
+    /// Example
+    /// ```rust
+    /// if let Some(input) = InputEvent::from_event(&e) {
+    ///     editor.handle(input, &config);
+    /// }
+    /// ```
+    pub fn from_event<E: GenericEvent>(e: &E) -> Option<InputEvent> {
+        if let Some(pos) = e.mouse_cursor_args() {
+            return Some(InputEvent::Move(pos));
+        }
+
+        if let Some(button) = e.press_args() {
+            return Some(InputEvent::Press(button));
+        }
+
+        e.release_args().map(InputEvent::Release)
+    }
+}
+
+/// The editing state of the window: the current tool, what is being drawn, the route shown and the simulation.
+/// It changes the road and intersection managers only in response to input events, so the same events always
+/// produce the same roads and intersections, with or without a window.
+#[derive(Debug)]
+pub struct Editor {
+    pub state: States,
+    pub state_counter: usize,
+    pub draw_road: bool,
+    pub draw_intersection: bool,
+    pub start_point: Option<[f64; 2]>,
+    pub road_to_draw: [f64; 4],
+    pub intersection_to_draw: [f64; 2],
+    pub latest_mouse_pos: [f64; 2],
+    pub route_graph: Graph,
+    /// The point on a road the route starts at, set by the first click of the routing state
+    pub route_start: Option<Position>,
+    /// The route picked from `alternatives`
+    pub route: Option<Route>,
+    /// The routes found between the clicked points, the best first
+    pub alternatives: Vec<Route>,
+    pub selected_route: usize,
+    /// The routing profile picked in the routing state, `None` for the one of the routing config
+    pub profile: Option<String>,
+    pub restrict_from: Option<i32>,
+    pub simulation: Option<Simulation>,
+    /// Bands of the isochrone shown, from the smallest budget to the largest
+    pub isochrone: Option<Vec<Band>>,
+    /// Flows of the traffic assignment shown as a heatmap
+    pub heatmap: Option<Vec<RoadFlow>>,
+    /// Delivery tours shown
+    pub deliveries: Option<Solution>,
+    /// Seconds the editor has been running, advanced by `update`
+    pub time: f64,
+}
+
+impl Default for Editor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Editor {
+    pub fn new() -> Editor {
+        Editor {
+            state: States::DrawRoad,
+            state_counter: 1,
+            draw_road: false,
+            draw_intersection: false,
+            start_point: None,
+            road_to_draw: [0.0, 0.0, 0.0, 0.0],
+            intersection_to_draw: [0.0, 0.0],
+            latest_mouse_pos: [0.0, 0.0],
+            route_graph: Graph::default(),
+            route_start: None,
+            route: None,
+            alternatives: Vec::new(),
+            selected_route: 0,
+            profile: None,
+            restrict_from: None,
+            simulation: None,
+            isochrone: None,
+            heatmap: None,
+            deliveries: None,
+            time: 0.0,
+        }
+    }
+
+    /// Returns the current time on the clock signals follow: the simulation clock while a simulation runs, and the
+    /// editor's own clock otherwise
+    pub fn now(&self) -> f64 {
+        self.simulation
+            .as_ref()
+            .map_or(self.time, |simulation| simulation.clock.time)
+    }
+
+    /// Returns the name of the routing profile routes are found for
+    pub fn profile_name<'a>(&'a self, config: &'a Config) -> &'a str {
+        self.profile.as_deref().unwrap_or(&config.routing.profile)
+    }
+
+    /// Advances the editor's clock and the simulation by `dt` seconds of real time
+    pub fn update(&mut self, dt: f64) {
+        self.time += dt;
+
+        if let Some(simulation) = &mut self.simulation {
+            simulation.update(dt);
+        }
+    }
+
+    /// Reacts to an input event
+    ///
+    /// Example
+    /// ```rust
+    /// editor.handle(InputEvent::Press(Button::Keyboard(Key::Space)), &config); // switches to drawing intersections
+    /// ```
+    pub fn handle(&mut self, event: InputEvent, config: &Config) {
+        match event {
+            InputEvent::Press(button) => {
+                if button == Button::Mouse(MouseButton::Left) {
+                    if self.state_counter >= usize::MAX - 10 {
+                        self.state_counter = 1;
+                    }
+
+                    self.state_counter += 1;
+                }
+
+                if let Button::Keyboard(key) = button {
+                    self.key(key, config);
+                }
+            }
+            InputEvent::Move(pos) => {
+                self.latest_mouse_pos = pos;
+
+                if self.draw_road {
+                    let start = *self.start_point.get_or_insert(pos);
+                    self.road_to_draw = [start[0], start[1], pos[0], pos[1]];
+                }
+
+                if self.draw_intersection {
+                    self.intersection_to_draw = pos;
+                }
+            }
+            InputEvent::Release(button) => {
+                if button == Button::Mouse(MouseButton::Left) {
+                    self.click(config);
+                }
+            }
+        }
+
+        if !self.draw_road {
+            self.road_to_draw = [0.0, 0.0, 0.0, 0.0];
+        }
+
+        if !self.draw_intersection {
+            self.intersection_to_draw = [0.0, 0.0];
+        }
+    }
+
+    /// Applies a click of the left mouse button to the current tool
+    ///
+    /// @internal
+    fn click(&mut self, config: &Config) {
+        let logger = get_logger();
+        let mut road_manager = get_road_manager().write().unwrap();
+        let mut intersection_manager = get_intersection_manager().lock().unwrap();
+
+        match self.state {
+            States::DrawRoad => {
+                if self.state_counter % 2 == 0 {
+                    self.draw_road = true;
+                    self.start_point = None;
+                } else {
+                    road_manager.create(Road {
+                        _id: None,
+                        name: config.editor.road_name.clone(),
+                        start_lat: self.road_to_draw[0],
+                        start_lon: self.road_to_draw[1],
+                        stop_lat: self.road_to_draw[2],
+                        stop_lon: self.road_to_draw[3],
+                        lane_count: config.editor.lane_count,
+                        speed_limit: config.editor.speed_limit,
+                        road_type: config.editor.road_type.clone(),
+                        direction: config.editor.direction,
+                        lanes_forward: None,
+                        lanes_backward: None,
+                    });
+
+                    self.draw_road = false;
+                    self.start_point = None;
+                }
+            }
+            States::DrawIntersection => {
+                if self.state_counter % 2 == 0 {
+                    self.draw_intersection = true;
+                } else {
+                    intersection_manager.create(Intersection {
+                        _id: None,
+                        lat: self.intersection_to_draw[0],
+                        lon: self.intersection_to_draw[1],
+                        control: config.editor.intersection_control,
+                        signal_offset: 0.0,
+                    });
+
+                    self.draw_intersection = false;
+                }
+            }
+            States::Destroy => {
+                let mut roads_to_destroy = vec![];
+
+                for road in road_manager.cache.as_ref().unwrap().iter() {
+                    let segments = road.segment(10.0);
+
+                    for segment in segments {
+                        let dx = segment.0 - self.latest_mouse_pos[0];
+                        let dy = segment.1 - self.latest_mouse_pos[1];
+                        let distance = (dx.powi(2) + dy.powi(2)).sqrt();
+
+                        if distance <= config.editor.road_hit_radius {
+                            roads_to_destroy.push(road._id.unwrap());
+                            break;
+                        }
+                    }
+                }
+
+                for road_id in roads_to_destroy {
+                    road_manager.destroy(road_id, &mut intersection_manager);
+                }
+
+                let mut intersections_to_destroy = vec![];
+
+                for intersection in intersection_manager.cache.as_ref().unwrap().iter() {
+                    let dx = intersection.lat - self.latest_mouse_pos[0];
+                    let dy = intersection.lon - self.latest_mouse_pos[1];
+                    let distance = (dx.powi(2) + dy.powi(2)).sqrt();
+
+                    if distance <= config.editor.intersection_hit_radius {
+                        intersections_to_destroy.push(intersection._id.unwrap());
+                        break;
+                    }
+                }
+
+                for intersection_id in intersections_to_destroy {
+                    intersection_manager.destroy(intersection_id);
+                }
+            }
+            States::Route => {
+                let name = self.profile_name(config);
+                let profile = config.profile(name).unwrap_or_else(|| {
+                    logger.warn(&format!(
+                        "(router) unknown profile {}, routing for cars",
+                        name
+                    ));
+                    Profile::car()
+                });
+
+                self.route_graph = Graph::from_managers_for_profile(
+                    &road_manager,
+                    &intersection_manager,
+                    config.routing.snap_radius,
+                    &profile,
+                );
+
+                // clicks anywhere along a road route from that point, clicks near an intersection from the
+                // intersection itself
+                let position = snap(
+                    road_manager.cache.as_deref().unwrap_or_default(),
+                    self.latest_mouse_pos[0],
+                    self.latest_mouse_pos[1],
+                    config
+                        .editor
+                        .road_hit_radius
+                        .max(config.editor.intersection_hit_radius),
+                );
+
+                if self.state_counter % 2 == 0 {
+                    self.route_start = position;
+                    self.route = None;
+                    self.alternatives.clear();
+                } else if let (Some(from), Some(to)) = (self.route_start, position) {
+                    let radius = config.editor.intersection_hit_radius;
+                    let start = self.route_graph.insert_position(&from, radius);
+                    let end = self.route_graph.insert_position(&to, radius);
+                    let (start, end) = match (start, end) {
+                        (Some(start), Some(end)) => (start, end),
+                        _ => {
+                            logger.warn("(router) the routing profile does not use these roads");
+                            return;
+                        }
+                    };
+                    let depart = self.now();
+
+                    // alternatives are compared on expected delays, then timed like the single route would be
+                    self.alternatives = if config.alternatives.count > 1 {
+                        alternatives(
+                            &self.route_graph,
+                            start,
+                            end,
+                            config.routing.metric,
+                            &config.alternatives,
+                        )
+                        .into_iter()
+                        .map(|route| {
+                            Route::from_edges_departing(
+                                &self.route_graph,
+                                start,
+                                route.edges,
+                                Some(depart),
+                            )
+                        })
+                        .collect()
+                    } else {
+                        astar_departing(
+                            &self.route_graph,
+                            start,
+                            end,
+                            config.routing.metric,
+                            depart,
+                        )
+                        .into_iter()
+                        .collect()
+                    };
+                    self.selected_route = 0;
+                    self.route = self.alternatives.first().cloned();
+
+                    if self.alternatives.is_empty() {
+                        logger.warn("(router) no route between these points");
+                    }
+
+                    for (i, found) in self.alternatives.iter().enumerate() {
+                        logger.info(&format!(
+                            "(router) route {} of {} over roads {:?}: {:.0} m, {:.0} s",
+                            i + 1,
+                            self.alternatives.len(),
+                            found.roads,
+                            found.distance,
+                            found.time
+                        ));
+                    }
+
+                    if let Some(route) = &self.route {
+                        let roads = road_manager.cache.as_deref().unwrap_or_default();
+
+                        for (i, step) in instructions(&self.route_graph, roads, route)
+                            .iter()
+                            .enumerate()
+                        {
+                            logger.info(&format!("(router) {}. {}", i + 1, step));
+                        }
+                    }
+                }
+            }
+            States::Restrict => {
+                let road = road_at(
+                    road_manager.cache.as_ref().unwrap(),
+                    self.latest_mouse_pos,
+                    config.editor.road_hit_radius,
+                );
+
+                if self.state_counter % 2 == 0 {
+                    self.restrict_from = road;
+                } else if let (Some(from_road), Some(to_road)) = (self.restrict_from, road) {
+                    let graph = Graph::from_managers(
+                        &road_manager,
+                        &intersection_manager,
+                        config.routing.snap_radius,
+                    );
+
+                    match graph.shared_intersection(from_road, to_road, self.latest_mouse_pos) {
+                        Some(intersection) => {
+                            let kind = intersection_manager
+                                .restrictions_at(intersection)
+                                .find(|r| r.from_road == from_road && r.to_road == to_road)
+                                .map(|r| r.kind);
+
+                            let next = match kind {
+                                None => Some(RestrictionKind::Forbidden),
+                                Some(RestrictionKind::Forbidden) => {
+                                    Some(RestrictionKind::Mandatory)
+                                }
+                                Some(RestrictionKind::Mandatory) => None,
+                            };
+
+                            match next {
+                                Some(kind) => {
+                                    intersection_manager.set_restriction(TurnRestriction {
+                                        intersection,
+                                        from_road,
+                                        to_road,
+                                        kind,
+                                    })
+                                }
+                                None => {
+                                    intersection_manager.remove_restriction(
+                                        intersection,
+                                        from_road,
+                                        to_road,
+                                    );
+                                }
+                            }
+
+                            logger.info(&format!(
+                                "(window) turn from road {} onto road {} at intersection {}: {}",
+                                from_road,
+                                to_road,
+                                intersection,
+                                match next {
+                                    Some(RestrictionKind::Forbidden) => "forbidden",
+                                    Some(RestrictionKind::Mandatory) => "mandatory",
+                                    None => "unrestricted",
+                                }
+                            ));
+                        }
+                        None => logger.warn(&format!(
+                            "(window) roads {} and {} do not meet at an intersection",
+                            from_road, to_road
+                        )),
+                    }
+
+                    self.restrict_from = None;
+                }
+            }
+            States::Isochrone => {
+                let graph = Graph::from_managers(
+                    &road_manager,
+                    &intersection_manager,
+                    config.routing.snap_radius,
+                );
+
+                let node = graph.nearest_node(
+                    self.latest_mouse_pos[0],
+                    self.latest_mouse_pos[1],
+                    config.editor.intersection_hit_radius,
+                );
+
+                if let Some(node) = node {
+                    let bands = isochrone(
+                        &graph,
+                        node,
+                        config.isochrone.metric,
+                        &config.isochrone.budgets,
+                    );
+
+                    for band in &bands {
+                        logger.info(&format!(
+                            "(router) {} roads reachable within {}",
+                            band.roads().len(),
+                            band.budget
+                        ));
+                    }
+
+                    self.isochrone = Some(bands);
+                }
+            }
+        }
+    }
+
+    /// Applies a key press to the editor
+    ///
+    /// @internal
+    fn key(&mut self, key: piston::input::Key, config: &Config) {
+        let logger = get_logger();
+
+        if key == config.keybindings.cycle_state {
+            self.state_counter = 1;
+            self.draw_road = false;
+            self.draw_intersection = false;
+            self.start_point = None;
+
+            match self.state {
+                States::DrawRoad => {
+                    self.state = States::DrawIntersection;
+                }
+                States::DrawIntersection => {
+                    self.state = States::Destroy;
+                }
+                States::Destroy => {
+                    self.state = States::Route;
+                }
+                States::Route => {
+                    self.state = States::Restrict;
+                    self.route_start = None;
+                    self.route = None;
+                    self.alternatives.clear();
+                }
+                States::Restrict => {
+                    self.state = States::Isochrone;
+                    self.restrict_from = None;
+                }
+                States::Isochrone => {
+                    self.state = States::DrawRoad;
+                    self.isochrone = None;
+                }
+            }
+        }
+
+        if key == config.keybindings.undo {
+            log::debug!("undo");
+            HistoryManager::undo();
+        }
+
+        if key == config.keybindings.save {
+            let roads = get_road_manager().read().unwrap().store();
+            let intersections = get_intersection_manager().lock().unwrap().store();
+
+            match roads.and(intersections) {
+                Ok(_) => logger.info(&format!(
+                    "(window) saved to {}",
+                    get_paths().data_dir.display()
+                )),
+                Err(err) => logger.error(&format!("(window) unable to save: {}", err)),
+            }
+        }
+
+        if key == config.keybindings.toggle_simulation {
+            match self.simulation.take() {
+                Some(stopped) => logger.info(&format!(
+                    "(simulation) stopped after {:.0} s, {} vehicles arrived",
+                    stopped.clock.time, stopped.arrived
+                )),
+                None => {
+                    let graph = Graph::from_managers(
+                        &get_road_manager().read().unwrap(),
+                        &get_intersection_manager().lock().unwrap(),
+                        config.routing.snap_radius,
+                    );
+
+                    self.simulation = Some(Simulation::new(graph, &config.simulation));
+                    logger.info("(simulation) started");
+                }
+            }
+        }
+
+        if key == config.keybindings.export_isochrone {
+            if let Some(bands) = &self.isochrone {
+                let path = get_paths().data_file("isochrone.geojson");
+                let written = std::fs::File::create(&path)
+                    .map_err(|err| err.to_string())
+                    .and_then(|file| {
+                        write_geojson(
+                            bands,
+                            config.isochrone.metric,
+                            config.isochrone.hull_edge,
+                            std::io::BufWriter::new(file),
+                        )
+                        .map_err(|err| err.to_string())
+                    });
+
+                match written {
+                    Ok(_) => logger.info(&format!("(router) wrote {}", path.display())),
+                    Err(err) => logger.error(&format!(
+                        "(router) unable to write {}: {}",
+                        path.display(),
+                        err
+                    )),
+                }
+            }
+        }
+
+        if key == config.keybindings.cycle_route && !self.alternatives.is_empty() {
+            self.selected_route = (self.selected_route + 1) % self.alternatives.len();
+            let picked = &self.alternatives[self.selected_route];
+
+            logger.info(&format!(
+                "(router) picked route {} of {}: {:.0} m, {:.0} s",
+                self.selected_route + 1,
+                self.alternatives.len(),
+                picked.distance,
+                picked.time
+            ));
+            self.route = Some(picked.clone());
+        }
+
+        if key == config.keybindings.cycle_profile && self.state == States::Route {
+            let names = config.profile_names();
+            let current = self.profile_name(config);
+            let next = names
+                .iter()
+                .position(|name| name == current)
+                .map_or(0, |i| (i + 1) % names.len());
+
+            self.profile = Some(names[next].clone());
+            self.state_counter = 1;
+            self.route_start = None;
+            self.route = None;
+            self.alternatives.clear();
+            logger.info(&format!("(router) routing for {}", names[next]));
+        }
+
+        if key == config.keybindings.toggle_heatmap {
+            self.heatmap = match self.heatmap.take() {
+                Some(_) => None,
+                None => assign_demand(config),
+            };
+        }
+
+        if key == config.keybindings.toggle_deliveries {
+            self.deliveries = match self.deliveries.take() {
+                Some(_) => None,
+                None => plan_deliveries(config),
+            };
+        }
+
+        if key == config.keybindings.cycle_control {
+            let mut intersection_manager = get_intersection_manager().lock().unwrap();
+
+            let hovered = intersection_manager
+                .cache
+                .as_ref()
+                .unwrap()
+                .iter()
+                .find(|intersection| {
+                    let dx = intersection.lat - self.latest_mouse_pos[0];
+                    let dy = intersection.lon - self.latest_mouse_pos[1];
+                    (dx.powi(2) + dy.powi(2)).sqrt() <= config.editor.intersection_hit_radius
+                })
+                .copied();
+
+            if let Some(intersection) = hovered {
+                let control = intersection.control.next();
+                intersection_manager.update(Intersection {
+                    control,
+                    ..intersection
+                });
+
+                logger.info(&format!(
+                    "(window) intersection {} is now {}",
+                    intersection._id.unwrap_or_default(),
+                    control
+                ));
+            }
+        }
+    }
+}
+
+/// Assigns the trips of `demand.csv` in the data directory, or of the sample demand if there is none, to the roads
+/// held by the managers. Logs and returns `None` if the demand cannot be read or assigned.
+///
+/// @internal
+fn assign_demand(config: &Config) -> Option<Vec<RoadFlow>> {
+    let logger = get_logger();
+    let paths = get_paths();
+
+    let path = Some(paths.data_file("demand.csv"))
+        .filter(|path| path.exists())
+        .or_else(|| paths.sample_file("demand.csv"))?;
+
+    let demand = match load_demand(&path) {
+        Ok(demand) => demand,
+        Err(err) => {
+            logger.error(&format!(
+                "(assignment) unable to load {}: {}",
+                path.display(),
+                err
+            ));
+            return None;
+        }
+    };
+
+    let graph = Graph::from_managers(
+        &get_road_manager().read().unwrap(),
+        &get_intersection_manager().lock().unwrap(),
+        config.routing.snap_radius,
+    );
+
+    match assign(&graph, &demand, &config.assignment) {
+        Ok(assignment) => {
+            logger.info(&format!(
+                "(assignment) assigned {} with a relative gap of {:.6} after {} iterations",
+                path.display(),
+                assignment.gap,
+                assignment.iterations
+            ));
+            Some(assignment.road_flows(&graph))
+        }
+        Err(err) => {
+            logger.error(&format!(
+                "(assignment) unable to assign {}: {}",
+                path.display(),
+                err
+            ));
+            None
+        }
+    }
+}
+
+/// Plans the tours of the fleet in `vehicles.csv` to the stops in `stops.csv` over the roads held by the managers,
+/// using the sample files for those missing from the data directory. Logs and returns `None` if they cannot be read
+/// or planned.
+///
+/// @internal
+fn plan_deliveries(config: &Config) -> Option<Solution> {
+    let logger = get_logger();
+    let paths = get_paths();
+    let file = |name: &str| {
+        Some(paths.data_file(name))
+            .filter(|path| path.exists())
+            .or_else(|| paths.sample_file(name))
+    };
+
+    let vehicles_path = file("vehicles.csv")?;
+    let stops_path = file("stops.csv")?;
+    let loaded = load_vehicles(&vehicles_path)
+        .and_then(|vehicles| load_stops(&stops_path).map(|stops| (vehicles, stops)));
+
+    let (vehicles, stops) = match loaded {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            logger.error(&format!("(router) unable to load deliveries: {}", err));
+            return None;
+        }
+    };
+
+    let graph = Graph::from_managers(
+        &get_road_manager().read().unwrap(),
+        &get_intersection_manager().lock().unwrap(),
+        config.routing.snap_radius,
+    );
+
+    match solve(&graph, config.vrp.depot, &vehicles, &stops, &config.vrp) {
+        Ok(solution) => {
+            for tour in &solution.tours {
+                logger.info(&format!(
+                    "(router) {} serves {} stops: {:.0} m, {:.0} s, {:.0} s late",
+                    tour.vehicle,
+                    tour.visits.len(),
+                    tour.distance,
+                    tour.time,
+                    tour.lateness
+                ));
+            }
+            Some(solution)
+        }
+        Err(err) => {
+            logger.error(&format!("(router) unable to plan deliveries: {}", err));
+            None
+        }
+    }
+}
+
+/// Loads the sample roads and intersections into the managers, replacing what they hold. The window starts from
+/// the sample data, and so do replayed sessions.
+///
+/// Example
+/// ```rust
+/// load_sample_data();
+/// ```
+pub fn load_sample_data() {
+    let logger = get_logger();
+    let paths = get_paths();
+
+    logger.trace("(roadmanager) load roads");
+    match paths.sample_file("roads.csv") {
+        Some(file) => {
+            if let Err(err) = get_road_manager().write().unwrap().load(Some(&file)) {
+                logger.error(&format!(
+                    "(roadmanager) unable to load {}: {}",
+                    file.display(),
+                    err
+                ));
+            }
+        }
+        None => logger.warn("(roadmanager) sample roads not found"),
+    }
+
+    logger.trace("(intersectionmanager) load intersections");
+    match paths.sample_file("intersections.csv") {
+        Some(file) => {
+            if let Err(err) = get_intersection_manager().lock().unwrap().load(Some(&file)) {
+                logger.error(&format!(
+                    "(intersectionmanager) unable to load {}: {}",
+                    file.display(),
+                    err
+                ));
+            }
+        }
+        None => logger.warn("(intersectionmanager) sample intersections not found"),
+    }
+}
+
+/// Returns the id of the first road within `radius` of a point
+///
+/// @internal
+fn road_at(roads: &[Road], pos: [f64; 2], radius: f64) -> Option<i32> {
+    roads
+        .iter()
+        .find(|road| {
+            road.segment(10.0).into_iter().any(|(lat, lon)| {
+                ((lat - pos[0]).powi(2) + (lon - pos[1]).powi(2)).sqrt() <= radius
+            })
+        })
+        .and_then(|road| road._id)
+}
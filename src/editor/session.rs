@@ -0,0 +1,297 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use crate::config::Config;
+use crate::editor::{load_sample_data, Editor, InputEvent};
+use crate::managers::history::HistoryManager;
+use crate::{
+    get_config, get_history_manager, get_intersection_manager, get_logger, get_paths,
+    get_road_manager,
+};
+
+/// An input event with the editor time it happened at, one line of a session file
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct RecordedEvent {
+    pub time: f64,
+    #[serde(flatten)]
+    pub event: InputEvent,
+}
+
+/// The first line of a session file: the contents of the config file the session was recorded with
+///
+/// @internal
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+struct Header {
+    config: String,
+}
+
+/// A recorded session
+#[derive(Debug, Clone)]
+pub struct Session {
+    /// The config the session was recorded with, `None` for sessions recorded before sessions held their config
+    pub config: Option<Config>,
+    pub events: Vec<RecordedEvent>,
+}
+
+impl Session {
+    /// Returns the config to replay the session with: the one it was recorded with, or the current one with a
+    /// warning for sessions without a config
+    pub fn config(&self) -> Config {
+        match &self.config {
+            Some(config) => config.clone(),
+            None => {
+                get_logger().warn(
+                    "(session) the session does not hold its config, replaying with the current config",
+                );
+                get_config().read().unwrap().clone()
+            }
+        }
+    }
+}
+
+/// Writes input events to a session file as they happen, one JSON object per line after a header holding the
+/// config. Every line is flushed right away, so a session recorded up to a crash or a hang can still be replayed.
+pub struct Recorder {
+    writer: BufWriter<File>,
+}
+
+impl Recorder {
+    /// Creates the session file, overwriting it if it exists. `config` is the contents of the config file the
+    /// editor runs with, so replays use the same keybindings, hit radii and defaults.
+    ///
+    /// Example
+    /// ```rust
+    /// let config = std::fs::read_to_string(&get_paths().config_file).unwrap_or_default();
+    /// let mut recorder = Recorder::create(Path::new("session.jsonl"), &config)?;
+    /// ```
+    pub fn create(path: &Path, config: &str) -> io::Result<Recorder> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(
+            &mut writer,
+            &Header {
+                config: config.to_string(),
+            },
+        )?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+
+        Ok(Recorder { writer })
+    }
+
+    /// Appends an event to the session file
+    pub fn record(&mut self, time: f64, event: InputEvent) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, &RecordedEvent { time, event })?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()
+    }
+}
+
+/// Reads a session file: the config it was recorded with and its events, in the order they were recorded. A config
+/// that no longer parses is replaced by the default config, like it was when the session was recorded.
+///
+/// Example
+/// ```rust
+/// let session = load(Path::new("session.jsonl"))?;
+/// ```
+pub fn load(path: &Path) -> io::Result<Session> {
+    let mut config = None;
+    let mut events = Vec::new();
+
+    for (number, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if let Ok(header) = serde_json::from_str::<Header>(&line) {
+            config = Some(Config::parse(&header.config).unwrap_or_default());
+            continue;
+        }
+
+        let event = serde_json::from_str(&line).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: {}", number + 1, err),
+            )
+        })?;
+        events.push(event);
+    }
+
+    Ok(Session { config, events })
+}
+
+/// Hands out the events of a session once the editor clock reaches their time
+pub struct Player {
+    events: Vec<RecordedEvent>,
+    next: usize,
+}
+
+impl Player {
+    pub fn new(events: Vec<RecordedEvent>) -> Player {
+        Player { events, next: 0 }
+    }
+
+    /// Returns the events that are due at `time` and have not been returned yet
+    ///
+    /// Example
+    /// ```rust
+    /// for event in player.due(editor.time) {
+    ///     editor.handle(event, &config);
+    /// }
+    /// ```
+    pub fn due(&mut self, time: f64) -> Vec<InputEvent> {
+        let start = self.next;
+
+        while self.next < self.events.len() && self.events[self.next].time <= time {
+            self.next += 1;
+        }
+
+        self.events[start..self.next]
+            .iter()
+            .map(|recorded| recorded.event)
+            .collect()
+    }
+
+    /// Returns whether every event has been handed out
+    pub fn finished(&self) -> bool {
+        self.next == self.events.len()
+    }
+}
+
+/// Replays a session without a window: starting from the sample data and with the config the session was recorded
+/// with, every event is handed to an editor after advancing its clock to the time the event was recorded at. The
+/// resulting roads, intersections and turn restrictions are stored in the data directory.
+///
+/// Example
+/// ```rust
+/// replay_headless(Path::new("session.jsonl"))?; // roads.csv and intersections.csv now hold the result
+/// ```
+pub fn replay_headless(path: &Path) -> io::Result<()> {
+    replay_headless_to(path, &get_paths().data_dir)
+}
+
+/// Like `replay_headless`, but stores the result in `dir`. The undo history is emptied first, so undoing in the
+/// session never reaches edits made before the replay.
+///
+/// Example
+/// ```rust
+/// replay_headless_to(Path::new("session.jsonl"), Path::new("replayed"))?;
+/// ```
+pub fn replay_headless_to(path: &Path, dir: &Path) -> io::Result<()> {
+    let logger = get_logger();
+    let session = load(path)?;
+    let config = session.config();
+    *get_history_manager().lock().unwrap() = HistoryManager::new(config.editor.history_depth);
+    let events = session.events;
+
+    load_sample_data();
+
+    let mut editor = Editor::new();
+    for recorded in &events {
+        editor.update((recorded.time - editor.time).max(0.0));
+        editor.handle(recorded.event, &config);
+    }
+
+    get_road_manager().read().unwrap().store_to(dir)?;
+    get_intersection_manager().lock().unwrap().store_to(dir)?;
+
+    logger.info(&format!(
+        "(session) replayed {} events from {}, stored the result in {}",
+        events.len(),
+        path.display(),
+        dir.display()
+    ));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use piston::input::{Button, Key, MouseButton};
+
+    const LEFT: Button = Button::Mouse(MouseButton::Left);
+
+    fn click(events: &mut Vec<InputEvent>, pos: [f64; 2]) {
+        events.extend([
+            InputEvent::Move(pos),
+            InputEvent::Press(LEFT),
+            InputEvent::Release(LEFT),
+        ]);
+    }
+
+    fn press(events: &mut Vec<InputEvent>, key: Key) {
+        events.extend([
+            InputEvent::Press(Button::Keyboard(key)),
+            InputEvent::Release(Button::Keyboard(key)),
+        ]);
+    }
+
+    /// Draws two roads, undoes the second and places an intersection at the start of the first
+    fn edits() -> Vec<InputEvent> {
+        let mut events = Vec::new();
+
+        for (from, to) in [
+            ([40.0, 40.0], [40.0, 300.0]),
+            ([60.0, 500.0], [320.0, 520.0]),
+        ] {
+            click(&mut events, from);
+            events.push(InputEvent::Move([
+                (from[0] + to[0]) / 2.0,
+                (from[1] + to[1]) / 2.0,
+            ]));
+            click(&mut events, to);
+        }
+        press(&mut events, Key::Z);
+
+        press(&mut events, Key::Space);
+        click(&mut events, [40.0, 40.0]);
+        click(&mut events, [40.0, 40.0]);
+
+        events
+    }
+
+    #[test]
+    fn replaying_headless_stores_the_same_files_every_time() {
+        let dir = std::env::temp_dir().join(format!("route_rs_replay_{}", std::process::id()));
+        let path = dir.join("session.jsonl");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut recorder = Recorder::create(&path, "").unwrap();
+        for (index, event) in edits().into_iter().enumerate() {
+            recorder.record(index as f64 * 0.25, event).unwrap();
+        }
+        drop(recorder);
+
+        let files = [
+            "roads.csv",
+            "intersections.csv",
+            "turn_restrictions.csv",
+            "signal_phases.csv",
+        ];
+        let replays: Vec<Vec<Vec<u8>>> = ["first", "second"]
+            .iter()
+            .map(|name| {
+                let out = dir.join(name);
+                replay_headless_to(&path, &out).unwrap();
+                files
+                    .iter()
+                    .map(|file| std::fs::read(out.join(file)).unwrap())
+                    .collect()
+            })
+            .collect();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        for (file, (first, second)) in files.iter().zip(replays[0].iter().zip(&replays[1])) {
+            assert!(first == second, "{} differs between the replays", file);
+        }
+
+        // one road and one intersection were added to the sample data
+        for (file, stored) in files.iter().zip(&replays[0]).take(2) {
+            let sample = std::fs::read(get_paths().sample_file(file).unwrap()).unwrap();
+            let lines = |contents: &[u8]| contents.split(|&byte| byte == b'\n').count();
+            assert_eq!(lines(stored), lines(&sample) + 1, "{}", file);
+        }
+    }
+}
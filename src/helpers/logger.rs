@@ -1,4 +1,15 @@
-#[derive(Clone, Copy)]
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicU8, AtomicUsize, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::SystemTime;
+
+use serde_json::{Map, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum LogLevel {
     Trace,
     Debug,
@@ -10,30 +21,126 @@ pub enum LogLevel {
 impl std::fmt::Display for LogLevel {
     /// Formats the log level to a string
     ///
-    /// @internal
+    /// Example
+    /// ```rust
+    /// let level = LogLevel::Info;
+    /// let level_string = level.to_string(); // "INFO"
+    /// ```
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            LogLevel::Debug => write!(f, "\x1b[34m"),
-            LogLevel::Info => write!(f, "\x1b[32m"),
-            LogLevel::Warn => write!(f, "\x1b[33m"),
-            LogLevel::Error => write!(f, "\x1b[31m"),
-            LogLevel::Trace => write!(f, "\x1b[35m"),
+            LogLevel::Debug => write!(f, "DEBUG"),
+            LogLevel::Info => write!(f, "INFO"),
+            LogLevel::Warn => write!(f, "WARN"),
+            LogLevel::Error => write!(f, "ERROR"),
+            LogLevel::Trace => write!(f, "TRACE"),
         }
     }
 }
 
-use std::time::SystemTime;
+impl std::str::FromStr for LogLevel {
+    type Err = String;
+
+    /// Parses a log level, ignoring case
+    ///
+    /// Example
+    /// ```rust
+    /// let level: LogLevel = "warn".parse().unwrap(); // LogLevel::Warn
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "trace" => Ok(LogLevel::Trace),
+            "debug" => Ok(LogLevel::Debug),
+            "info" => Ok(LogLevel::Info),
+            "warn" => Ok(LogLevel::Warn),
+            "error" => Ok(LogLevel::Error),
+            _ => Err(format!("Unknown log level `{}`", s)),
+        }
+    }
+}
+
+impl LogLevel {
+    /// Returns the ANSI escape code used to color the log level
+    ///
+    /// @internal
+    fn color(self) -> &'static str {
+        match self {
+            LogLevel::Debug => "\x1b[34m",
+            LogLevel::Info => "\x1b[32m",
+            LogLevel::Warn => "\x1b[33m",
+            LogLevel::Error => "\x1b[31m",
+            LogLevel::Trace => "\x1b[35m",
+        }
+    }
+
+    /// Reads the log level from the `ROUTE_RS_LOG` environment variable
+    ///
+    /// Example
+    /// ```rust
+    /// // ROUTE_RS_LOG=debug
+    /// let level = LogLevel::from_env(); // Some(LogLevel::Debug)
+    /// ```
+    pub fn from_env() -> Option<LogLevel> {
+        std::env::var("ROUTE_RS_LOG").ok()?.parse().ok()
+    }
+
+    /// @internal
+    fn to_filter(self) -> log::LevelFilter {
+        match self {
+            LogLevel::Trace => log::LevelFilter::Trace,
+            LogLevel::Debug => log::LevelFilter::Debug,
+            LogLevel::Info => log::LevelFilter::Info,
+            LogLevel::Warn => log::LevelFilter::Warn,
+            LogLevel::Error => log::LevelFilter::Error,
+        }
+    }
+
+    /// @internal
+    fn from_u8(value: u8) -> LogLevel {
+        match value {
+            0 => LogLevel::Trace,
+            1 => LogLevel::Debug,
+            2 => LogLevel::Info,
+            3 => LogLevel::Warn,
+            _ => LogLevel::Error,
+        }
+    }
+}
+
+/// The format log lines are written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// `[YYYY-MM-DD HH:MM:SS.MMM] [LEVEL] (module) message key=value`, colored when written to a terminal
+    Text,
+    /// One JSON object per line with `timestamp`, `level`, `module`, `message` and `fields`
+    Json,
+}
+
+/// A JSON log line
+///
+/// @internal
+#[derive(serde::Serialize)]
+struct JsonLine<'a> {
+    timestamp: String,
+    level: String,
+    module: &'a str,
+    message: &'a str,
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    fields: Map<String, Value>,
+}
 
 struct SystemTimeWrapper(SystemTime);
 
 impl std::fmt::Display for SystemTimeWrapper {
-    /// Formats the system time to a string in the format of `HH:MM:SS.MMM`
+    /// Formats the system time to a string in the format of `YYYY-MM-DD HH:MM:SS.MMM` (UTC).
+    /// The alternate flag (`{:#}`) formats it as RFC 3339 instead, `YYYY-MM-DDTHH:MM:SS.MMMZ`.
     ///
     /// @internal
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let time = self.0.duration_since(std::time::UNIX_EPOCH).unwrap();
         let secs = time.as_secs();
         let millis = time.subsec_millis() as u64;
+        let (year, month, day) = civil_from_days((secs / (24 * 3600)) as i64);
         let time = secs % (24 * 3600);
 
         let hours = time / 3600;
@@ -41,64 +148,499 @@ impl std::fmt::Display for SystemTimeWrapper {
         let minutes = time / 60;
         let seconds = time % 60;
 
+        let (separator, suffix) = if f.alternate() { ("T", "Z") } else { (" ", "") };
+
         write!(
             f,
-            "{:02}:{:02}:{:02}.{:03}",
-            hours, minutes, seconds, millis
+            "{:04}-{:02}-{:02}{}{:02}:{:02}:{:02}.{:03}{}",
+            year, month, day, separator, hours, minutes, seconds, millis, suffix
         )
     }
 }
 
-impl LogLevel {
-    /// Converts the log level to a string
+/// Converts days since the unix epoch to a (year, month, day) date in the proleptic Gregorian calendar
+///
+/// @internal
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+/// A log file that is rotated once it grows past `max_size` bytes.
+/// Rotated files are renamed to `<file>.1`, `<file>.2`, ... up to `max_files`.
+pub struct RotatingFile {
+    path: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    file: Option<File>,
+    size: u64,
+}
+
+impl RotatingFile {
+    /// Opens (or creates) the log file, appending to it
     ///
     /// Example
     /// ```rust
-    /// let level = LogLevel::Info; // 2
-    /// let level_string = level.to_string(); // "INFO"
+    /// let file = RotatingFile::open(Path::new("route-rs.log"), 10 * 1024 * 1024, 5)?;
     /// ```
-    fn to_string(&self) -> String {
+    pub fn open(path: &Path, max_size: u64, max_files: usize) -> std::io::Result<RotatingFile> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+
+        Ok(RotatingFile {
+            path: path.to_path_buf(),
+            max_size,
+            max_files,
+            file: Some(file),
+            size,
+        })
+    }
+
+    /// Writes a line to the file, rotating it first if the line would not fit
+    ///
+    /// @internal
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        let len = line.len() as u64 + 1;
+
+        if self.size > 0 && self.size + len > self.max_size {
+            self.rotate()?;
+        }
+
+        if let Some(file) = &mut self.file {
+            writeln!(file, "{}", line)?;
+            self.size += len;
+        }
+
+        Ok(())
+    }
+
+    /// @internal
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file = None;
+
+        let rotated = |index: usize| PathBuf::from(format!("{}.{}", self.path.display(), index));
+
+        if self.max_files == 0 {
+            std::fs::remove_file(&self.path)?;
+        } else {
+            for index in (1..self.max_files).rev() {
+                if rotated(index).exists() {
+                    std::fs::rename(rotated(index), rotated(index + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, rotated(1))?;
+        }
+
+        self.file = Some(
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?,
+        );
+        self.size = 0;
+
+        Ok(())
+    }
+}
+
+/// A destination log lines are written to
+pub enum Sink {
+    Stdout,
+    Stderr,
+    File(RotatingFile),
+}
+
+impl Sink {
+    /// Whether lines written to this sink should contain ANSI colors.
+    /// Colors are only used for terminals, and never when `NO_COLOR` is set.
+    ///
+    /// @internal
+    fn colored(&self) -> bool {
+        if std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+            return false;
+        }
+
+        match self {
+            Sink::Stdout => std::io::stdout().is_terminal(),
+            Sink::Stderr => std::io::stderr().is_terminal(),
+            Sink::File(_) => false,
+        }
+    }
+
+    /// @internal
+    fn flush(&mut self) -> std::io::Result<()> {
         match self {
-            LogLevel::Debug => "DEBUG".to_string(),
-            LogLevel::Info => "INFO".to_string(),
-            LogLevel::Warn => "WARN".to_string(),
-            LogLevel::Error => "ERROR".to_string(),
-            LogLevel::Trace => "TRACE".to_string(),
+            Sink::Stdout => std::io::stdout().flush(),
+            Sink::Stderr => std::io::stderr().flush(),
+            Sink::File(file) => file.file.as_mut().map_or(Ok(()), |file| file.flush()),
+        }
+    }
+
+    /// @internal
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        match self {
+            Sink::Stdout => writeln!(std::io::stdout().lock(), "{}", line),
+            Sink::Stderr => writeln!(std::io::stderr().lock(), "{}", line),
+            Sink::File(file) => file.write_line(line),
         }
     }
 }
 
+impl From<log::Level> for LogLevel {
+    fn from(level: log::Level) -> Self {
+        match level {
+            log::Level::Trace => LogLevel::Trace,
+            log::Level::Debug => LogLevel::Debug,
+            log::Level::Info => LogLevel::Info,
+            log::Level::Warn => LogLevel::Warn,
+            log::Level::Error => LogLevel::Error,
+        }
+    }
+}
+
+/// A message kept in the logger's ring buffer of recent messages
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    /// Increases by one for every message, so readers can tell which entries they have already seen
+    pub id: u64,
+    pub time: SystemTime,
+    pub level: LogLevel,
+    pub target: String,
+    /// The message, with its key/value fields appended as `key=value`
+    pub message: String,
+}
+
+impl std::fmt::Display for LogEntry {
+    /// Formats the entry like a line of the text format, without colors
+    ///
+    /// Example
+    /// ```rust
+    /// println!("{}", entry); // [2026-01-01 12:00:00.000] [WARN] (roadmanager) Unable to resolve road
+    /// ```
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "[{}] [{}] ", SystemTimeWrapper(self.time), self.level)?;
+        if !self.target.is_empty() {
+            write!(f, "({}) ", self.target)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Splits a message written as `(target) message` into its target, e.g. `roadmanager`, and the message
+///
+/// @internal
+fn split_target(message: &str) -> (&str, &str) {
+    message
+        .strip_prefix('(')
+        .and_then(|rest| rest.split_once(')'))
+        .map_or(("", message), |(target, rest)| (target, rest.trim_start()))
+}
+
 pub struct Logger {
-    level: LogLevel,
+    level: AtomicU8,
+    format: RwLock<LogFormat>,
+    filters: RwLock<Vec<(String, LogLevel)>>,
+    sinks: Mutex<Vec<Sink>>,
+    recent: Mutex<VecDeque<LogEntry>>,
+    recent_capacity: AtomicUsize,
+    next_id: AtomicU64,
 }
 
 impl Logger {
-    /// Creates a logger instance with the given log level.
+    /// Creates a logger instance with the given log level, writing to stdout.
     ///
     /// Example
     /// ```rust
     /// let logger = Logger::new(LogLevel::Info); // logger will only log info, warn and error messages
     /// ```
     pub fn new(level: LogLevel) -> Logger {
-        Logger { level }
+        Logger {
+            level: AtomicU8::new(level as u8),
+            format: RwLock::new(LogFormat::Text),
+            filters: RwLock::new(Vec::new()),
+            sinks: Mutex::new(vec![Sink::Stdout]),
+            recent: Mutex::new(VecDeque::new()),
+            recent_capacity: AtomicUsize::new(200),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Installs this logger as the logger of the `log` crate, so `log::info!` and friends end up in the same sinks
+    ///
+    /// Example
+    /// ```rust
+    /// get_logger().install(); // log::warn!("...") is now written by the logger
+    /// ```
+    pub fn install(&'static self) {
+        if log::set_logger(self).is_err() {
+            self.warn("(logger) another logger is already installed");
+        }
+
+        self.update_max_level();
+    }
+
+    /// Returns the current log level
+    pub fn level(&self) -> LogLevel {
+        LogLevel::from_u8(self.level.load(Ordering::Relaxed))
+    }
+
+    /// Changes the log level at runtime
+    ///
+    /// Example
+    /// ```rust
+    /// logger.set_level(LogLevel::Warn); // only warn and error messages are logged from now on
+    /// ```
+    pub fn set_level(&self, level: LogLevel) {
+        self.level.store(level as u8, Ordering::Relaxed);
+        self.update_max_level();
+    }
+
+    /// Changes the format of the log lines
+    ///
+    /// Example
+    /// ```rust
+    /// logger.set_format(LogFormat::Json); // {"timestamp":"...","level":"INFO","module":"main","message":"run window"}
+    /// ```
+    pub fn set_format(&self, format: LogFormat) {
+        *self.format.write().unwrap() = format;
+    }
+
+    /// Sets how many recent messages are kept in memory for `recent`
+    ///
+    /// Example
+    /// ```rust
+    /// logger.set_recent_capacity(50); // only the last 50 messages are kept
+    /// ```
+    pub fn set_recent_capacity(&self, capacity: usize) {
+        self.recent_capacity.store(capacity, Ordering::Relaxed);
+
+        let mut recent = self.recent.lock().unwrap();
+        while recent.len() > capacity {
+            recent.pop_front();
+        }
+    }
+
+    /// Returns the recent messages with an id greater than `after`, oldest first
+    ///
+    /// Example
+    /// ```rust
+    /// let all = logger.recent(0);
+    /// let new = logger.recent(all.last().map_or(0, |entry| entry.id));
+    /// ```
+    pub fn recent(&self, after: u64) -> Vec<LogEntry> {
+        self.recent
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|entry| entry.id > after)
+            .cloned()
+            .collect()
+    }
+
+    /// Sets per-target levels. A target is either the name written in front of a message, such as `roadmanager` in
+    /// `(roadmanager) load roads`, or a module path such as `route_rs::managers`, which also matches its submodules.
+    /// The most specific matching target wins, messages without a matching target use the global level.
+    ///
+    /// Example
+    /// ```rust
+    /// logger.set_filters(vec![("roadmanager".to_string(), LogLevel::Warn)]);
+    /// ```
+    pub fn set_filters(&self, filters: Vec<(String, LogLevel)>) {
+        *self.filters.write().unwrap() = filters;
+        self.update_max_level();
+    }
+
+    /// Returns whether a message of the given level and target would be logged
+    ///
+    /// Example
+    /// ```rust
+    /// if logger.enabled(LogLevel::Trace, "roadmanager") {
+    ///     logger.trace(&format!("(roadmanager) {:?}", road_manager.cache));
+    /// }
+    /// ```
+    pub fn enabled(&self, level: LogLevel, target: &str) -> bool {
+        let filters = self.filters.read().unwrap();
+
+        let filter = filters
+            .iter()
+            .filter(|(name, _)| {
+                target == name
+                    || target
+                        .strip_prefix(name.as_str())
+                        .is_some_and(|rest| rest.starts_with("::"))
+            })
+            .max_by_key(|(name, _)| name.len());
+
+        match filter {
+            Some((_, filter_level)) => level >= *filter_level,
+            None => level >= self.level(),
+        }
     }
 
-    /// Used internally to log a message to the console
+    /// Keeps the `log` crate from formatting records that no filter would let through
+    ///
+    /// @internal
+    fn update_max_level(&self) {
+        let most_verbose = self
+            .filters
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.level(), std::cmp::min);
+
+        log::set_max_level(most_verbose.to_filter());
+    }
+
+    /// Replaces the sinks log lines are written to
+    ///
+    /// Example
+    /// ```rust
+    /// logger.set_sinks(vec![Sink::Stderr, Sink::File(RotatingFile::open(path, 1024 * 1024, 3)?)]);
+    /// ```
+    pub fn set_sinks(&self, sinks: Vec<Sink>) {
+        *self.sinks.lock().unwrap() = sinks;
+    }
+
+    /// Used internally to log a message to every sink
     ///
     /// @internal
     fn log(&self, level: LogLevel, message: &str) {
-        if level as u8 >= self.level as u8 {
-            println!(
-                "{} [{}] [{}] {}",
-                level,
-                SystemTimeWrapper(SystemTime::now()),
-                level.to_string(),
-                message
-            );
+        self.event(level, message, &[]);
+    }
+
+    /// Logs a message with key/value fields. In the text format the fields are appended as `key=value`,
+    /// in the JSON format they are written to the `fields` object.
+    ///
+    /// Example
+    /// ```rust
+    /// logger.event(
+    ///     LogLevel::Info,
+    ///     "(router) found route",
+    ///     &[("road_id", json!(3)), ("length", json!(350.5))],
+    /// );
+    /// ```
+    pub fn event(&self, level: LogLevel, message: &str, fields: &[(&str, Value)]) {
+        let (target, message) = split_target(message);
+
+        if !self.enabled(level, target) {
+            return;
         }
+
+        self.write(level, target, message, fields);
     }
 
-    /// Logs a debug message to the console
+    /// Writes a message to every sink without checking the level, used by the `log` and `tracing` bridges
+    pub(crate) fn write(
+        &self,
+        level: LogLevel,
+        target: &str,
+        message: &str,
+        fields: &[(&str, Value)],
+    ) {
+        let now = SystemTimeWrapper(SystemTime::now());
+        let format = *self.format.read().unwrap();
+
+        self.remember(&now, level, target, message, fields);
+
+        let plain = match format {
+            LogFormat::Text => {
+                let mut line = format!("[{}] [{}] ", now, level);
+                if !target.is_empty() {
+                    line.push_str(&format!("({}) ", target));
+                }
+                line.push_str(message);
+                for (key, value) in fields {
+                    line.push_str(&format!(" {}={}", key, value));
+                }
+                line
+            }
+            LogFormat::Json => {
+                let line = JsonLine {
+                    timestamp: format!("{:#}", now),
+                    level: level.to_string(),
+                    module: target,
+                    message,
+                    fields: fields
+                        .iter()
+                        .map(|(key, value)| (key.to_string(), value.clone()))
+                        .collect(),
+                };
+                serde_json::to_string(&line).unwrap_or_default()
+            }
+        };
+
+        let mut sinks = match self.sinks.lock() {
+            Ok(sinks) => sinks,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        for sink in sinks.iter_mut() {
+            let result = if format == LogFormat::Text && sink.colored() {
+                sink.write_line(&format!("{}{}\x1b[0m", level.color(), plain))
+            } else {
+                sink.write_line(&plain)
+            };
+
+            if let Err(err) = result {
+                eprintln!("[logger] unable to write log line: {}", err);
+            }
+        }
+    }
+
+    /// Adds a message to the ring buffer of recent messages
+    ///
+    /// @internal
+    fn remember(
+        &self,
+        now: &SystemTimeWrapper,
+        level: LogLevel,
+        target: &str,
+        message: &str,
+        fields: &[(&str, Value)],
+    ) {
+        let capacity = self.recent_capacity.load(Ordering::Relaxed);
+        if capacity == 0 {
+            return;
+        }
+
+        let mut message = message.to_string();
+        for (key, value) in fields {
+            message.push_str(&format!(" {}={}", key, value));
+        }
+
+        let mut recent = match self.recent.lock() {
+            Ok(recent) => recent,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        while recent.len() >= capacity {
+            recent.pop_front();
+        }
+
+        recent.push_back(LogEntry {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            time: now.0,
+            level,
+            target: target.to_string(),
+            message,
+        });
+    }
+
+    /// Logs a debug message
     ///
     /// Example
     /// ```rust
@@ -108,7 +650,7 @@ impl Logger {
         self.log(LogLevel::Debug, message);
     }
 
-    /// Logs an info message to the console
+    /// Logs an info message
     ///
     /// Example
     /// ```rust
@@ -118,7 +660,7 @@ impl Logger {
         self.log(LogLevel::Info, message);
     }
 
-    /// Logs a warn message to the console
+    /// Logs a warn message
     ///
     /// Example
     /// ```rust
@@ -128,7 +670,7 @@ impl Logger {
         self.log(LogLevel::Warn, message);
     }
 
-    /// Logs an error message to the console. This will not panic.
+    /// Logs an error message. This will not panic.
     ///
     /// Example
     /// ```rust
@@ -138,7 +680,7 @@ impl Logger {
         self.log(LogLevel::Error, message);
     }
 
-    /// Logs a trace message to the console
+    /// Logs a trace message
     ///
     /// Example
     /// ```rust
@@ -148,3 +690,221 @@ impl Logger {
         self.log(LogLevel::Trace, message);
     }
 }
+
+impl log::Log for Logger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        Logger::enabled(self, metadata.level().into(), metadata.target())
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !log::Log::enabled(self, record.metadata()) {
+            return;
+        }
+
+        let mut visitor = FieldVisitor::default();
+        let _ = record.key_values().visit(&mut visitor);
+
+        let fields: Vec<(&str, Value)> = visitor
+            .fields
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.clone()))
+            .collect();
+
+        self.write(
+            record.level().into(),
+            record.target(),
+            &record.args().to_string(),
+            &fields,
+        );
+    }
+
+    fn flush(&self) {
+        let mut sinks = match self.sinks.lock() {
+            Ok(sinks) => sinks,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        for sink in sinks.iter_mut() {
+            let _ = sink.flush();
+        }
+    }
+}
+
+/// Collects the key/value pairs of a `log` record
+///
+/// @internal
+#[derive(Default)]
+struct FieldVisitor {
+    fields: Vec<(String, Value)>,
+}
+
+impl<'kvs> log::kv::VisitSource<'kvs> for FieldVisitor {
+    fn visit_pair(
+        &mut self,
+        key: log::kv::Key<'kvs>,
+        value: log::kv::Value<'kvs>,
+    ) -> Result<(), log::kv::Error> {
+        let value = if let Some(value) = value.to_bool() {
+            Value::from(value)
+        } else if let Some(value) = value.to_i64() {
+            Value::from(value)
+        } else if let Some(value) = value.to_u64() {
+            Value::from(value)
+        } else if let Some(value) = value.to_f64() {
+            Value::from(value)
+        } else {
+            Value::from(value.to_string())
+        };
+
+        self.fields.push((key.to_string(), value));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("route_rs_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn files_are_rotated_past_their_size() {
+        let dir = temp_dir("rotation");
+        let path = dir.join("route-rs.log");
+        let read = |suffix: &str| {
+            std::fs::read_to_string(format!("{}{}", path.display(), suffix)).unwrap_or_default()
+        };
+
+        // three lines of 10 bytes fit in a file
+        let mut file = RotatingFile::open(&path, 30, 2).unwrap();
+        for index in 0..10 {
+            file.write_line(&format!("line {:04}", index)).unwrap();
+        }
+        drop(file);
+
+        assert_eq!(read(""), "line 0009\n");
+        assert_eq!(read(".1"), "line 0006\nline 0007\nline 0008\n");
+        assert_eq!(read(".2"), "line 0003\nline 0004\nline 0005\n");
+        assert!(!dir.join("route-rs.log.3").exists());
+
+        // a reopened file counts what it already holds
+        let mut file = RotatingFile::open(&path, 30, 2).unwrap();
+        file.write_line("line 0010").unwrap();
+        file.write_line("line 0011").unwrap();
+        file.write_line("line 0012").unwrap();
+        drop(file);
+
+        assert_eq!(read(""), "line 0012\n");
+        assert_eq!(read(".1"), "line 0009\nline 0010\nline 0011\n");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn most_specific_filter_wins() {
+        let logger = Logger::new(LogLevel::Info);
+
+        for filters in [
+            vec![
+                ("editor", LogLevel::Warn),
+                ("editor::session", LogLevel::Trace),
+            ],
+            vec![
+                ("editor::session", LogLevel::Trace),
+                ("editor", LogLevel::Warn),
+            ],
+        ] {
+            logger.set_filters(
+                filters
+                    .into_iter()
+                    .map(|(target, level)| (target.to_string(), level))
+                    .collect(),
+            );
+
+            assert!(!logger.enabled(LogLevel::Info, "editor"));
+            assert!(logger.enabled(LogLevel::Warn, "editor"));
+            assert!(!logger.enabled(LogLevel::Info, "editor::history"));
+            assert!(logger.enabled(LogLevel::Trace, "editor::session"));
+            assert!(logger.enabled(LogLevel::Trace, "editor::session::player"));
+            // only whole path segments match
+            assert!(!logger.enabled(LogLevel::Info, "editor::sessions"));
+            assert!(logger.enabled(LogLevel::Info, "editorial"));
+            assert!(!logger.enabled(LogLevel::Debug, "editorial"));
+            assert!(logger.enabled(LogLevel::Info, ""));
+        }
+    }
+
+    #[test]
+    fn dates_are_civil() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(11016), (2000, 2, 29));
+        assert_eq!(civil_from_days(19782), (2024, 2, 29));
+        assert_eq!(civil_from_days(19783), (2024, 3, 1));
+        // 2100 is not a leap year
+        assert_eq!(civil_from_days(47540), (2100, 2, 28));
+        assert_eq!(civil_from_days(47541), (2100, 3, 1));
+        assert_eq!(civil_from_days(-135081), (1600, 2, 29));
+
+        let time =
+            SystemTimeWrapper(UNIX_EPOCH + Duration::from_millis(19782 * 86_400_000 + 45_296_789));
+        assert_eq!(time.to_string(), "2024-02-29 12:34:56.789");
+        assert_eq!(format!("{:#}", time), "2024-02-29T12:34:56.789Z");
+        assert_eq!(
+            SystemTimeWrapper(UNIX_EPOCH).to_string(),
+            "1970-01-01 00:00:00.000"
+        );
+    }
+
+    #[test]
+    fn json_lines_parse_back() {
+        let dir = temp_dir("json");
+        let path = dir.join("route-rs.log");
+        let logger = Logger::new(LogLevel::Trace);
+        logger.set_format(LogFormat::Json);
+        logger.set_sinks(vec![Sink::File(
+            RotatingFile::open(&path, 1024 * 1024, 1).unwrap(),
+        )]);
+
+        logger.event(
+            LogLevel::Info,
+            "(router) found \"route\" in 3 ms",
+            &[
+                ("road_id", serde_json::json!(3)),
+                ("length", serde_json::json!(350.5)),
+            ],
+        );
+        logger.warn("no target");
+        logger.set_sinks(Vec::new());
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let lines: Vec<Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["level"], "INFO");
+        assert_eq!(lines[0]["module"], "router");
+        assert_eq!(lines[0]["message"], "found \"route\" in 3 ms");
+        assert_eq!(
+            lines[0]["fields"],
+            serde_json::json!({"road_id": 3, "length": 350.5})
+        );
+
+        let timestamp = lines[0]["timestamp"].as_str().unwrap();
+        assert_eq!(timestamp.len(), "2026-10-19T08:15:00.000Z".len());
+        assert!(timestamp.ends_with('Z') && timestamp.as_bytes()[10] == b'T');
+
+        assert_eq!(lines[1]["level"], "WARN");
+        assert_eq!(lines[1]["module"], "");
+        assert_eq!(lines[1]["message"], "no target");
+        assert!(lines[1].get("fields").is_none());
+    }
+}
@@ -1 +1,5 @@
 pub mod logger;
+pub mod paths;
+pub(crate) mod rng;
+#[cfg(feature = "tracing")]
+pub mod tracing_layer;
@@ -0,0 +1,233 @@
+use std::path::{Path, PathBuf};
+
+use crate::cli::Args;
+use crate::get_logger;
+
+/// The font bundled into the binary, used when no assets directory can be found
+pub const EMBEDDED_FONT: &[u8] = include_bytes!("../assets/FiraSans-Regular.ttf");
+
+const FONT_FILE: &str = "FiraSans-Regular.ttf";
+const APP_DIR: &str = "route-rs";
+
+/// The `[paths]` table of the config file
+#[derive(Debug, Default, serde::Deserialize)]
+struct PathsSection {
+    data_dir: Option<PathBuf>,
+    assets_dir: Option<PathBuf>,
+    sample_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    paths: PathsSection,
+}
+
+/// The directories and files route-rs reads from and writes to
+#[derive(Debug)]
+pub struct Paths {
+    pub config_file: PathBuf,
+    pub data_dir: PathBuf,
+    pub assets_dir: Option<PathBuf>,
+    pub sample_dir: Option<PathBuf>,
+}
+
+impl Paths {
+    /// Resolves all paths. Every path is looked up in order from the command line arguments, the environment,
+    /// the config file and finally the directories next to the executable and the current directory.
+    ///
+    /// Example
+    /// ```rust
+    /// let paths = Paths::resolve(&args);
+    /// let roads = paths.data_file("roads.csv"); // e.g. ~/.local/share/route-rs/roads.csv
+    /// ```
+    pub fn resolve(args: &Args) -> Paths {
+        let config_file = args
+            .config
+            .clone()
+            .or_else(|| env_path("ROUTE_RS_CONFIG"))
+            .unwrap_or_else(|| config_dir().join("config.toml"));
+
+        let config = read_config(&config_file);
+        let roots = search_roots();
+        let local_roots = local_roots();
+
+        let data_dir = args
+            .data_dir
+            .clone()
+            .or_else(|| env_path("ROUTE_RS_DATA_DIR"))
+            .or(config.paths.data_dir)
+            // data is written to, so only a `data` folder next to the executable or in the current directory is
+            // used, never one in the installed files or the source tree the binary was built from
+            .or_else(|| find_dir(&local_roots, "data", |_| true))
+            .unwrap_or_else(data_dir);
+
+        let assets_dir = args
+            .assets_dir
+            .clone()
+            .or_else(|| env_path("ROUTE_RS_ASSETS_DIR"))
+            .or(config.paths.assets_dir)
+            .or_else(|| find_dir(&roots, "assets", |dir| dir.join(FONT_FILE).is_file()));
+
+        let sample_dir = args
+            .sample_dir
+            .clone()
+            .or_else(|| env_path("ROUTE_RS_SAMPLE_DIR"))
+            .or(config.paths.sample_dir)
+            .or_else(|| find_dir(&roots, "sample", |dir| dir.join("roads.csv").is_file()));
+
+        Paths {
+            config_file,
+            data_dir,
+            assets_dir,
+            sample_dir,
+        }
+    }
+
+    /// Returns the path of a file inside the data directory
+    ///
+    /// Example
+    /// ```rust
+    /// let path = paths.data_file("roads.csv");
+    /// ```
+    pub fn data_file(&self, name: &str) -> PathBuf {
+        self.data_dir.join(name)
+    }
+
+    /// Returns the path of a file inside the sample directory, if there is one
+    pub fn sample_file(&self, name: &str) -> Option<PathBuf> {
+        self.sample_dir.as_ref().map(|dir| dir.join(name))
+    }
+
+    /// Returns the path of the font file, or `None` if the embedded font should be used
+    pub fn font_file(&self) -> Option<PathBuf> {
+        self.assets_dir
+            .as_ref()
+            .map(|dir| dir.join(FONT_FILE))
+            .filter(|file| file.is_file())
+    }
+}
+
+/// Reads a path from an environment variable, ignoring empty values
+///
+/// @internal
+fn env_path(key: &str) -> Option<PathBuf> {
+    std::env::var_os(key)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Reads the `[paths]` table of the config file. Relative paths are resolved against the directory of the config file.
+///
+/// @internal
+fn read_config(file: &Path) -> ConfigFile {
+    let contents = match std::fs::read_to_string(file) {
+        Ok(contents) => contents,
+        Err(_) => return ConfigFile::default(),
+    };
+
+    let mut config: ConfigFile = match toml::from_str(&contents) {
+        Ok(config) => config,
+        Err(err) => {
+            get_logger().warn(&format!(
+                "(paths) unable to parse {}: {}",
+                file.display(),
+                err
+            ));
+            return ConfigFile::default();
+        }
+    };
+
+    let base = file.parent().unwrap_or(Path::new(""));
+    for path in [
+        &mut config.paths.data_dir,
+        &mut config.paths.assets_dir,
+        &mut config.paths.sample_dir,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        *path = base.join(&*path);
+    }
+
+    config
+}
+
+/// Directories that may contain the `assets` and `sample` folders, most specific first
+///
+/// @internal
+fn search_roots() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+
+    if let Some(exe_dir) = exe_dir() {
+        roots.push(exe_dir.join("..").join("share").join(APP_DIR));
+    }
+
+    roots.extend(local_roots());
+    roots.push(Path::new(env!("CARGO_MANIFEST_DIR")).join("src"));
+    roots
+}
+
+/// The directory of the executable and the current directory, which may contain the `data` folder, most specific
+/// first
+///
+/// @internal
+fn local_roots() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+
+    if let Some(exe_dir) = exe_dir() {
+        roots.push(exe_dir);
+    }
+
+    if let Ok(current_dir) = std::env::current_dir() {
+        roots.push(current_dir.join("src"));
+        roots.push(current_dir);
+    }
+
+    roots
+}
+
+/// @internal
+fn exe_dir() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+}
+
+/// Returns the first `<root>/<name>` directory that matches `accept`
+///
+/// @internal
+fn find_dir(roots: &[PathBuf], name: &str, accept: impl Fn(&Path) -> bool) -> Option<PathBuf> {
+    roots
+        .iter()
+        .map(|root| root.join(name))
+        .find(|dir| dir.is_dir() && accept(dir))
+}
+
+/// The per-user config directory, e.g. `~/.config/route-rs`
+///
+/// @internal
+fn config_dir() -> PathBuf {
+    if cfg!(windows) {
+        return env_path("APPDATA").unwrap_or_default().join(APP_DIR);
+    }
+
+    env_path("XDG_CONFIG_HOME")
+        .or_else(|| env_path("HOME").map(|home| home.join(".config")))
+        .unwrap_or_default()
+        .join(APP_DIR)
+}
+
+/// The per-user data directory, e.g. `~/.local/share/route-rs`
+///
+/// @internal
+fn data_dir() -> PathBuf {
+    if cfg!(windows) {
+        return env_path("APPDATA").unwrap_or_default().join(APP_DIR);
+    }
+
+    env_path("XDG_DATA_HOME")
+        .or_else(|| env_path("HOME").map(|home| home.join(".local").join("share")))
+        .unwrap_or_default()
+        .join(APP_DIR)
+}
@@ -0,0 +1,30 @@
+//! A small xorshift random number generator, for the simulation and the generated test networks
+
+/// A small xorshift random number generator, so runs with the same seed are reproducible
+///
+/// Example
+/// ```rust
+/// let mut rng = Rng::new(config.seed);
+/// let origin = origins[rng.below(origins.len())];
+/// ```
+#[derive(Debug, Clone)]
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    /// Seeds the generator, a seed of 0 is replaced by 1 because xorshift never leaves 0
+    pub(crate) fn new(seed: u64) -> Rng {
+        Rng(seed.max(1))
+    }
+
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns a number in `0..n`
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
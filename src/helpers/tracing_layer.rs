@@ -0,0 +1,92 @@
+use serde_json::Value;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::Layer;
+
+use crate::helpers::logger::{LogLevel, Logger};
+
+/// A `tracing` layer that writes events through the logger, using the event target for filtering
+///
+/// Example
+/// ```rust
+/// use tracing_subscriber::prelude::*;
+///
+/// tracing_subscriber::registry().with(LoggerLayer::new(get_logger())).init();
+/// ```
+pub struct LoggerLayer {
+    logger: &'static Logger,
+}
+
+impl LoggerLayer {
+    pub fn new(logger: &'static Logger) -> Self {
+        Self { logger }
+    }
+}
+
+/// Collects the `message` field and every other field as a key/value pair
+///
+/// @internal
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: Vec<(&'static str, Value)>,
+}
+
+impl MessageVisitor {
+    fn record(&mut self, field: &Field, value: Value) {
+        match (field.name(), value) {
+            ("message", Value::String(message)) => self.message = message,
+            (name, value) => self.fields.push((name, value)),
+        }
+    }
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.record(field, Value::from(format!("{:?}", value)));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record(field, Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record(field, Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.record(field, Value::from(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.record(field, Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.record(field, Value::from(value));
+    }
+}
+
+impl<S: Subscriber> Layer<S> for LoggerLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let level = match *metadata.level() {
+            tracing::Level::TRACE => LogLevel::Trace,
+            tracing::Level::DEBUG => LogLevel::Debug,
+            tracing::Level::INFO => LogLevel::Info,
+            tracing::Level::WARN => LogLevel::Warn,
+            tracing::Level::ERROR => LogLevel::Error,
+        };
+
+        if !self.logger.enabled(level, metadata.target()) {
+            return;
+        }
+
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        self.logger
+            .write(level, metadata.target(), &visitor.message, &visitor.fields);
+    }
+}
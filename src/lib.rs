@@ -0,0 +1,82 @@
+use lazy_static::lazy_static;
+use std::sync::{Mutex, OnceLock, RwLock};
+
+pub mod cli;
+pub mod commands;
+pub mod config;
+pub mod constants;
+pub mod editor;
+pub mod helpers;
+pub mod managers;
+pub mod routing;
+pub mod simulation;
+pub mod window;
+
+lazy_static! {
+    pub static ref LOGGER: helpers::logger::Logger = helpers::logger::Logger::new(
+        helpers::logger::LogLevel::from_env().unwrap_or(helpers::logger::LogLevel::Trace)
+    );
+    pub static ref HISTORY_MANAGER: Mutex<managers::history::HistoryManager> =
+        Mutex::new(managers::history::HistoryManager::default());
+    pub static ref ROAD_MANAGER: RwLock<managers::road::RoadManager> =
+        RwLock::new(managers::road::RoadManager::new());
+    pub static ref INTERSECTION_MANAGER: Mutex<managers::intersection::IntersectionManager> =
+        Mutex::new(managers::intersection::IntersectionManager::new());
+}
+
+static PATHS: OnceLock<helpers::paths::Paths> = OnceLock::new();
+static CONFIG: OnceLock<RwLock<config::Config>> = OnceLock::new();
+
+/// Returns the logger
+pub fn get_logger() -> &'static helpers::logger::Logger {
+    &LOGGER
+}
+
+/// Returns the resolved asset, sample and data paths. Unless the program installed its own with `set_paths`, these
+/// are resolved without command line arguments on first use.
+pub fn get_paths() -> &'static helpers::paths::Paths {
+    PATHS.get_or_init(|| helpers::paths::Paths::resolve(&cli::Args::default()))
+}
+
+/// Installs the paths `get_paths` returns. Fails with the paths given if paths were already installed or used.
+///
+/// Example
+/// ```rust
+/// set_paths(Paths::resolve(&args)).map_err(|_| "paths are already in use")?;
+/// ```
+pub fn set_paths(paths: helpers::paths::Paths) -> Result<(), helpers::paths::Paths> {
+    PATHS.set(paths)
+}
+
+/// Returns the config. Unless the program installed one with `set_config`, this is the default config, which is not
+/// written anywhere.
+pub fn get_config() -> &'static RwLock<config::Config> {
+    CONFIG.get_or_init(|| RwLock::new(config::Config::default()))
+}
+
+/// Installs the config `get_config` returns. Fails with the config given if a config was already installed or used.
+///
+/// Example
+/// ```rust
+/// set_config(Config::load_or_create(&get_paths().config_file)).map_err(|_| "the config is already in use")?;
+/// ```
+pub fn set_config(config: config::Config) -> Result<(), Box<config::Config>> {
+    CONFIG
+        .set(RwLock::new(config))
+        .map_err(|config| Box::new(config.into_inner().unwrap_or_else(|err| err.into_inner())))
+}
+
+/// Returns the history manager
+pub fn get_history_manager() -> &'static Mutex<managers::history::HistoryManager> {
+    &HISTORY_MANAGER
+}
+
+/// Returns the road manager
+pub fn get_road_manager() -> &'static RwLock<managers::road::RoadManager> {
+    &ROAD_MANAGER
+}
+
+/// Returns the intersection manager
+pub fn get_intersection_manager() -> &'static Mutex<managers::intersection::IntersectionManager> {
+    &INTERSECTION_MANAGER
+}
@@ -1,52 +1,62 @@
-use lazy_static::lazy_static;
-use std::env::current_dir;
 use std::error::Error;
-use std::sync::{Mutex, RwLock};
-
-mod constants;
-mod helpers;
-mod managers;
-mod window;
-
-lazy_static! {
-    pub static ref LOGGER: helpers::logger::Logger =
-        helpers::logger::Logger::new(helpers::logger::LogLevel::Trace);
-    pub static ref HISTORY_MANAGER: Mutex<managers::history::HistoryManager> =
-        Mutex::new(managers::history::HistoryManager::new());
-    pub static ref ROAD_MANAGER: RwLock<managers::road::RoadManager> =
-        RwLock::new(managers::road::RoadManager::new());
-    pub static ref INTERSECTION_MANAGER: Mutex<managers::intersection::IntersectionManager> =
-        Mutex::new(managers::intersection::IntersectionManager::new());
-}
 
-/// Returns the logger
-pub fn get_logger() -> &'static helpers::logger::Logger {
-    &LOGGER
-}
+use route_rs::cli::{Args, USAGE};
+use route_rs::commands;
+use route_rs::config::Config;
+use route_rs::editor::session;
+use route_rs::helpers::paths::Paths;
+use route_rs::{get_config, get_logger, get_paths, set_config, set_paths, window};
 
-/// Returns the history manager
-pub fn get_history_manager() -> &'static Mutex<managers::history::HistoryManager> {
-    &HISTORY_MANAGER
-}
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = match Args::parse() {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            std::process::exit(2);
+        }
+    };
 
-/// Returns the road manager
-pub fn get_road_manager() -> &'static RwLock<managers::road::RoadManager> {
-    &ROAD_MANAGER
-}
+    let logger = get_logger();
 
-/// Returns the intersection manager
-pub fn get_intersection_manager() -> &'static Mutex<managers::intersection::IntersectionManager> {
-    &INTERSECTION_MANAGER
-}
+    logger.install();
 
-fn main() -> Result<(), Box<dyn Error>> {
-    if !current_dir()?.to_str().unwrap().ends_with("src") {
-        panic!("[WRONG_DIR] Please run this program from the src directory");
+    #[cfg(feature = "tracing")]
+    {
+        use tracing_subscriber::prelude::*;
+
+        tracing_subscriber::registry()
+            .with(route_rs::helpers::tracing_layer::LoggerLayer::new(logger))
+            .init();
     }
 
-    let logger = get_logger();
+    set_paths(Paths::resolve(&args)).map_err(|_| "paths were used before they were resolved")?;
+    let paths = get_paths();
+    set_config(Config::load_or_create(&paths.config_file))
+        .map_err(|_| "the config was used before it was loaded")?;
+    get_config().read().unwrap().logging.apply();
+
+    logger.debug(&format!(
+        "(main) config file: {}",
+        paths.config_file.display()
+    ));
+    logger.debug(&format!("(main) data dir: {}", paths.data_dir.display()));
+
+    if let Some(command) = &args.command {
+        commands::run(command)?;
+        return Ok(());
+    }
+
+    if let Some(path) = args.replay.as_deref().filter(|_| args.headless) {
+        logger.info("(main) replay headless");
+        session::replay_headless(path)?;
+        return Ok(());
+    }
 
     logger.info("(main) run window");
-    window::init();
+    window::init(&args);
     Ok(())
 }
@@ -1,10 +1,16 @@
-use crate::managers::intersection::Intersection;
+use crate::config::EditorConfig;
+use crate::managers::intersection::{Intersection, TurnRestriction};
 use crate::managers::road::Road;
+use crate::managers::schedule::{Closure, SpeedFactor};
+use crate::managers::signal::SignalPhase;
 
 #[derive(Debug)]
 pub enum HistoryEntryData {
     Road(Road),
     Intersection(Intersection),
+    /// A turn restriction that was added, replaced by another kind of restriction for the same turn, or removed.
+    /// Updates hold the restriction before the change.
+    Restriction(TurnRestriction),
 }
 
 #[derive(Debug)]
@@ -20,113 +26,160 @@ pub enum Manager {
     Intersection,
 }
 
+/// What refers to a road or intersection by id and was removed along with it, so undoing the removal can bring it
+/// back
+#[derive(Debug, Clone, Default)]
+pub struct Attached {
+    pub restrictions: Vec<TurnRestriction>,
+    /// The phases of every intersection whose phases changed, as they were before
+    pub signal_phases: Vec<SignalPhase>,
+    pub speed_factors: Vec<SpeedFactor>,
+    pub closures: Vec<Closure>,
+}
+
 #[derive(Debug)]
 pub struct HistoryEntry {
     pub data: HistoryEntryData,
     pub entry_type: HistoryEntryType,
     pub manager: Manager,
+    pub attached: Attached,
 }
 
 #[derive(Debug)]
 pub struct HistoryManager {
     history: Vec<HistoryEntry>,
+    /// How many entries are kept, older ones are dropped
+    depth: usize,
+}
+
+impl Default for HistoryManager {
+    fn default() -> Self {
+        Self::new(EditorConfig::default().history_depth)
+    }
 }
 
 impl HistoryManager {
-    pub fn new() -> HistoryManager {
+    pub fn new(depth: usize) -> HistoryManager {
         HistoryManager {
             history: Vec::new(),
+            depth,
         }
     }
 
+    /// Changes how many entries are kept, dropping the oldest ones beyond the new depth
+    ///
+    /// Example
+    /// ```rust
+    /// get_history_manager().lock().unwrap().set_depth(config.editor.history_depth);
+    /// ```
+    pub fn set_depth(&mut self, depth: usize) {
+        self.depth = depth;
+
+        let excess = self.history.len().saturating_sub(depth);
+        self.history.drain(..excess);
+    }
+
     pub fn create(&mut self, entry: HistoryEntry) {
-        println!("Creating history entry: {:?}", entry);
+        log::trace!("creating history entry: {:?}", entry);
 
         self.history.push(entry);
 
-        println!("History: {:?}", self.history);
+        log::trace!("history: {:?}", self.history);
 
-        if self.history.len() > 15 {
+        if self.history.len() > self.depth {
             self.history.remove(0);
         }
     }
 
-    pub fn undo(&mut self) {
-        if self.history.len() == 0 {
-            return;
-        }
+    /// Takes the most recent entry off the history
+    pub fn pop(&mut self) -> Option<HistoryEntry> {
+        self.history.pop()
+    }
 
-        println!("Undoing: {:?}", self.history.last().unwrap());
-
-        let entry = self.history.pop().unwrap();
-
-        println!("Entry: {:?}", entry);
-
-        match entry.manager {
-            Manager::Road => match entry.entry_type {
-                HistoryEntryType::Create => {
-                    println!("CREATE TYPE");
-
-                    let road = match entry.data {
-                        HistoryEntryData::Road(road) => road,
-                        _ => panic!("[HISTORY_MANAGER] Wrong entry data"),
-                    };
-
-                    println!("Road: {:?}", road);
-
-                    crate::get_road_manager()
-                        .write()
-                        .unwrap()
-                        .destroy(road._id.unwrap());
-                }
-                HistoryEntryType::Update => {
-                    // TODO
-                }
-
-                HistoryEntryType::Destroy => {
-                    let road = match entry.data {
-                        HistoryEntryData::Road(road) => road,
-                        _ => panic!("[HISTORY_MANAGER] Wrong entry data"),
-                    };
-
-                    crate::get_road_manager().write().unwrap().create(road);
-                }
-                _ => {
-                    panic!("[HISTORY_MANAGER] Wrong entry type");
-                }
-            },
-
-            Manager::Intersection => match entry.entry_type {
-                HistoryEntryType::Create => {
-                    let intersection = match entry.data {
-                        HistoryEntryData::Intersection(intersection) => intersection,
-                        _ => panic!("[HISTORY_MANAGER] Wrong entry data"),
-                    };
-
-                    crate::get_intersection_manager()
-                        .lock()
-                        .unwrap()
-                        .destroy(intersection._id.unwrap());
-                }
-                HistoryEntryType::Update => {
-                    // TODO
-                }
-
-                HistoryEntryType::Destroy => {
-                    let intersection = match entry.data {
-                        HistoryEntryData::Intersection(intersection) => intersection,
-                        _ => panic!("[HISTORY_MANAGER] Wrong entry data"),
-                    };
-
-                    crate::get_intersection_manager()
-                        .lock()
-                        .unwrap()
-                        .create(intersection);
-                }
-                _ => {
-                    panic!("[HISTORY_MANAGER] Wrong entry type");
-                }
-            },
+    /// Undoes the most recent entry. The history manager is only locked while the entry is taken off, since undoing
+    /// goes through the road and intersection managers, which are locked in turn. The caller must not hold any of
+    /// the managers. Undoing is not recorded in the history itself.
+    ///
+    /// Example
+    /// ```rust
+    /// HistoryManager::undo(); // the last road or intersection drawn is gone again
+    /// ```
+    pub fn undo() {
+        let entry = match crate::get_history_manager().lock().unwrap().pop() {
+            Some(entry) => entry,
+            None => return,
+        };
+
+        log::debug!("undoing: {:?}", entry);
+
+        let attached = entry.attached;
+
+        match (entry.data, entry.entry_type) {
+            (HistoryEntryData::Road(road), HistoryEntryType::Create) => {
+                log::trace!("destroying created road: {:?}", road);
+
+                crate::get_road_manager()
+                    .write()
+                    .unwrap()
+                    ._remove(road._id.unwrap());
+            }
+            (HistoryEntryData::Road(road), HistoryEntryType::Destroy) => {
+                let mut road_manager = crate::get_road_manager().write().unwrap();
+                road_manager._add(road);
+                road_manager.speed_factors.extend(attached.speed_factors);
+                road_manager.closures.extend(attached.closures);
+
+                crate::get_intersection_manager()
+                    .lock()
+                    .unwrap()
+                    ._attach(Attached {
+                        restrictions: attached.restrictions,
+                        signal_phases: attached.signal_phases,
+                        ..Attached::default()
+                    });
+            }
+            (HistoryEntryData::Road(_), HistoryEntryType::Update) => {
+                // roads are never updated in place
+            }
+            (HistoryEntryData::Intersection(intersection), HistoryEntryType::Create) => {
+                crate::get_intersection_manager()
+                    .lock()
+                    .unwrap()
+                    ._remove(intersection._id.unwrap());
+            }
+            (HistoryEntryData::Intersection(intersection), HistoryEntryType::Update) => {
+                crate::get_intersection_manager()
+                    .lock()
+                    .unwrap()
+                    ._replace(intersection);
+            }
+            (HistoryEntryData::Restriction(restriction), HistoryEntryType::Create) => {
+                crate::get_intersection_manager()
+                    .lock()
+                    .unwrap()
+                    ._take_restriction(
+                        restriction.intersection,
+                        restriction.from_road,
+                        restriction.to_road,
+                    );
+            }
+            (
+                HistoryEntryData::Restriction(restriction),
+                HistoryEntryType::Update | HistoryEntryType::Destroy,
+            ) => {
+                let mut intersection_manager = crate::get_intersection_manager().lock().unwrap();
+                intersection_manager._take_restriction(
+                    restriction.intersection,
+                    restriction.from_road,
+                    restriction.to_road,
+                );
+                intersection_manager.restrictions.push(restriction);
+            }
+            (HistoryEntryData::Intersection(intersection), HistoryEntryType::Destroy) => {
+                let mut intersection_manager = crate::get_intersection_manager().lock().unwrap();
+                intersection_manager._add(intersection);
+                intersection_manager._attach(attached);
+            }
         }
     }
 }
@@ -1,45 +1,297 @@
+use std::path::Path;
+
 use crate::get_history_manager;
 use crate::get_logger;
+use crate::get_paths;
 use crate::managers::history::*;
+use crate::managers::signal::{SignalPhase, SignalPlan};
+
+/// How traffic through an intersection is controlled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Control {
+    /// No signs or signals, traffic passes freely
+    #[default]
+    Uncontrolled,
+    /// Every approach has to stop
+    AllWayStop,
+    /// Only the approaches from minor roads have to stop
+    TwoWayStop,
+    /// The approaches from minor roads have to give way
+    Yield,
+    TrafficSignals,
+    Roundabout,
+}
+
+impl Control {
+    /// Every control type, in the order the editor cycles through them
+    pub const ALL: [Control; 6] = [
+        Control::Uncontrolled,
+        Control::AllWayStop,
+        Control::TwoWayStop,
+        Control::Yield,
+        Control::TrafficSignals,
+        Control::Roundabout,
+    ];
+
+    /// Returns the control type that follows this one in the editor
+    ///
+    /// Example
+    /// ```rust
+    /// Control::Yield.next(); // Control::TrafficSignals
+    /// ```
+    pub fn next(self) -> Control {
+        let index = Control::ALL
+            .iter()
+            .position(|&control| control == self)
+            .unwrap();
+        Control::ALL[(index + 1) % Control::ALL.len()]
+    }
+
+    /// Returns the expected delay in seconds for a vehicle passing through the intersection.
+    /// `major_approach` is whether the vehicle arrives on one of the most important roads of the intersection,
+    /// which has priority at two-way stops and yield signs.
+    ///
+    /// Example
+    /// ```rust
+    /// Control::TwoWayStop.expected_delay(false); // 6.0
+    /// Control::TwoWayStop.expected_delay(true); // 0.0
+    /// ```
+    pub fn expected_delay(self, major_approach: bool) -> f64 {
+        match self {
+            Control::Uncontrolled => 0.0,
+            Control::AllWayStop => 8.0,
+            Control::TwoWayStop if major_approach => 0.0,
+            Control::TwoWayStop => 6.0,
+            Control::Yield if major_approach => 0.0,
+            Control::Yield => 3.0,
+            // a 60 s cycle with 30 s of red: a vehicle arrives on red half of the time and then waits 15 s on average
+            Control::TrafficSignals => 7.5,
+            Control::Roundabout => 4.0,
+        }
+    }
+}
+
+impl std::fmt::Display for Control {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Control::Uncontrolled => "uncontrolled",
+            Control::AllWayStop => "all-way stop",
+            Control::TwoWayStop => "two-way stop",
+            Control::Yield => "yield",
+            Control::TrafficSignals => "traffic signals",
+            Control::Roundabout => "roundabout",
+        };
+
+        write!(f, "{}", name)
+    }
+}
 
 #[derive(Debug, Copy, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(from = "IntersectionRecord")]
 pub struct Intersection {
     pub _id: Option<i32>,
     pub lat: f64,
     pub lon: f64,
-    pub traffic_lights: bool,
+    pub control: Control,
+    /// Seconds after the start of the clock at which the signal cycle starts
+    pub signal_offset: f64,
+}
+
+/// An intersection as stored in a csv file. Files written before intersections had a control type only have a
+/// `traffic_lights` column, which is migrated to `TrafficSignals` or `Uncontrolled`.
+///
+/// @internal
+#[derive(serde::Deserialize)]
+struct IntersectionRecord {
+    _id: Option<i32>,
+    lat: f64,
+    lon: f64,
+    #[serde(default)]
+    control: Option<Control>,
+    #[serde(default)]
+    traffic_lights: Option<bool>,
+    #[serde(default)]
+    signal_offset: Option<f64>,
+}
+
+impl From<IntersectionRecord> for Intersection {
+    fn from(record: IntersectionRecord) -> Self {
+        let control = record.control.unwrap_or(match record.traffic_lights {
+            Some(true) => Control::TrafficSignals,
+            _ => Control::Uncontrolled,
+        });
+
+        Intersection {
+            _id: record._id,
+            lat: record.lat,
+            lon: record.lon,
+            control,
+            signal_offset: record.signal_offset.unwrap_or(0.0),
+        }
+    }
+}
+
+/// Whether a turn restriction forbids a turn, or makes it the only turn allowed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RestrictionKind {
+    /// Turning from `from_road` onto `to_road` is not allowed, e.g. no left turn or no U-turn
+    Forbidden,
+    /// Coming from `from_road`, `to_road` is the only road that may be taken, e.g. only straight on
+    Mandatory,
+}
+
+/// A restricted turn from one road onto another at an intersection. A U-turn is a turn onto the same road.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct TurnRestriction {
+    pub intersection: i32,
+    pub from_road: i32,
+    pub to_road: i32,
+    pub kind: RestrictionKind,
+}
+
+/// Returns whether the restrictions at an intersection allow turning from `from_road` onto `to_road`
+///
+/// Example
+/// ```rust
+/// let allowed = turn_allowed(intersection_manager.restrictions_at(1), 3, 4);
+/// ```
+pub fn turn_allowed<'a>(
+    restrictions: impl IntoIterator<Item = &'a TurnRestriction>,
+    from_road: i32,
+    to_road: i32,
+) -> bool {
+    let mut has_mandatory = false;
+
+    for restriction in restrictions {
+        if restriction.from_road != from_road {
+            continue;
+        }
+
+        match restriction.kind {
+            RestrictionKind::Forbidden if restriction.to_road == to_road => return false,
+            RestrictionKind::Mandatory if restriction.to_road == to_road => return true,
+            RestrictionKind::Mandatory => has_mandatory = true,
+            RestrictionKind::Forbidden => {}
+        }
+    }
+
+    !has_mandatory
 }
 
 #[derive(Debug)]
 pub struct IntersectionManager {
     pub cache: Option<Vec<Intersection>>,
+    pub restrictions: Vec<TurnRestriction>,
+    pub signal_phases: Vec<SignalPhase>,
+}
+
+impl Default for IntersectionManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl IntersectionManager {
     /// internal
-    fn _add(&mut self, intersection: Intersection) {
+    pub(crate) fn _add(&mut self, intersection: Intersection) {
         if let Some(cache) = &mut self.cache {
             cache.push(intersection);
         }
     }
 
     /// internal
-    fn _remove(&mut self, id: i32) {
+    pub(crate) fn _remove(&mut self, id: i32) {
         if let Some(cache) = &mut self.cache {
-            let mut index = 0;
-            for intersection in &mut *cache {
-                if intersection._id == Some(id) {
-                    cache.remove(index);
-                    break;
-                }
-                index += 1;
+            if let Some(index) = cache
+                .iter()
+                .position(|intersection| intersection._id == Some(id))
+            {
+                cache.remove(index);
             }
         }
     }
 
+    /// internal, brings back the turn restrictions and signal phases removed with a road or intersection. The phases
+    /// replace the current phases of their intersections.
+    pub(crate) fn _attach(&mut self, attached: Attached) {
+        self.restrictions.extend(attached.restrictions);
+        self.signal_phases.retain(|phase| {
+            !attached
+                .signal_phases
+                .iter()
+                .any(|restored| restored.intersection == phase.intersection)
+        });
+        self.signal_phases.extend(attached.signal_phases);
+    }
+
+    /// internal, removes the turn restrictions onto or off a road and the road from the approaches of signal phases.
+    /// Returns the restrictions and the phases of the intersections that changed, as they were before.
+    pub(crate) fn _detach_road(&mut self, road: i32) -> Attached {
+        let (restrictions, kept) = self
+            .restrictions
+            .drain(..)
+            .partition(|restriction| restriction.from_road == road || restriction.to_road == road);
+        self.restrictions = kept;
+
+        let changed: Vec<i32> = self
+            .signal_phases
+            .iter()
+            .filter(|phase| phase.approaches.contains(&road))
+            .map(|phase| phase.intersection)
+            .collect();
+        let signal_phases = self
+            .signal_phases
+            .iter()
+            .filter(|phase| changed.contains(&phase.intersection))
+            .cloned()
+            .collect();
+
+        for phase in &mut self.signal_phases {
+            phase.approaches.retain(|&approach| approach != road);
+        }
+
+        Attached {
+            restrictions,
+            signal_phases,
+            ..Attached::default()
+        }
+    }
+
+    /// internal, removes the restriction for a turn without recording it in the history
+    pub(crate) fn _take_restriction(
+        &mut self,
+        intersection: i32,
+        from_road: i32,
+        to_road: i32,
+    ) -> Option<TurnRestriction> {
+        let index = self.restrictions.iter().position(|restriction| {
+            restriction.intersection == intersection
+                && restriction.from_road == from_road
+                && restriction.to_road == to_road
+        })?;
+
+        Some(self.restrictions.remove(index))
+    }
+
+    /// internal, returns the replaced intersection
+    pub(crate) fn _replace(&mut self, intersection: Intersection) -> Option<Intersection> {
+        let id = intersection._id?;
+        let cached = self
+            .cache
+            .as_mut()?
+            .iter_mut()
+            .find(|cached| cached._id == Some(id))?;
+
+        Some(std::mem::replace(cached, intersection))
+    }
+
     pub fn new() -> Self {
         Self {
             cache: Some(Vec::<Intersection>::new()),
+            restrictions: Vec::new(),
+            signal_phases: Vec::new(),
         }
     }
 
@@ -68,7 +320,8 @@ impl IntersectionManager {
     ///     id: None,
     ///     lat: 0.0,
     ///     lon: 0.0,
-    ///     traffic_lights: false,
+    ///     control: Control::Uncontrolled,
+    ///     signal_offset: 0.0,
     /// });
     /// ```
     pub fn create(&mut self, intersection: Intersection) {
@@ -95,13 +348,14 @@ impl IntersectionManager {
             .lock()
             .expect("Failed to lock history manager")
             .create(HistoryEntry {
-                data: HistoryEntryData::Intersection(intersection.clone()),
+                data: HistoryEntryData::Intersection(intersection),
                 entry_type: HistoryEntryType::Create,
                 manager: Manager::Intersection,
+                attached: Attached::default(),
             });
     }
 
-    /// Removes an intersection from the cache
+    /// Removes an intersection from the cache, along with its turn restrictions and signal phases
     ///
     /// Example
     /// ```rust
@@ -112,13 +366,23 @@ impl IntersectionManager {
             return;
         }
 
-        let intersection = self.resolve(id).unwrap().clone();
+        let intersection = *self.resolve(id).unwrap();
         if intersection._id.is_none() {
             get_logger().warn("Unable to resolve intersection");
             return;
         }
 
         self._remove(id);
+        let (restrictions, kept) = self
+            .restrictions
+            .drain(..)
+            .partition(|restriction| restriction.intersection == id);
+        self.restrictions = kept;
+        let (signal_phases, kept) = self
+            .signal_phases
+            .drain(..)
+            .partition(|phase| phase.intersection == id);
+        self.signal_phases = kept;
 
         get_history_manager()
             .lock()
@@ -127,40 +391,236 @@ impl IntersectionManager {
                 data: HistoryEntryData::Intersection(intersection),
                 entry_type: HistoryEntryType::Destroy,
                 manager: Manager::Intersection,
+                attached: Attached {
+                    restrictions,
+                    signal_phases,
+                    ..Attached::default()
+                },
             });
     }
 
-    /// Stores the cache to the intersections.csv file. This will overwrite the file.
+    /// Replaces an intersection in the cache with the one of the same id
     ///
     /// Example
     /// ```rust
-    /// intersection_manager.store(); // intersections.csv is now overwritten by the contents of the cache
+    /// intersection_manager.update(Intersection {
+    ///     control: Control::Roundabout,
+    ///     ..*intersection_manager.resolve(1).unwrap()
+    /// });
     /// ```
-    pub fn store(&self) {
-        let mut wtr = csv::Writer::from_path("data/intersections.csv").unwrap();
+    pub fn update(&mut self, intersection: Intersection) {
+        let previous = match self._replace(intersection) {
+            Some(previous) => previous,
+            None => {
+                get_logger().warn("Unable to resolve intersection");
+                return;
+            }
+        };
 
-        for intersection in self.cache.as_ref().unwrap() {
-            wtr.serialize(intersection).unwrap();
+        get_history_manager()
+            .lock()
+            .expect("Failed to lock history manager")
+            .create(HistoryEntry {
+                data: HistoryEntryData::Intersection(previous),
+                entry_type: HistoryEntryType::Update,
+                manager: Manager::Intersection,
+                attached: Attached::default(),
+            });
+    }
+
+    /// Returns the turn restrictions of an intersection
+    ///
+    /// Example
+    /// ```rust
+    /// for restriction in intersection_manager.restrictions_at(1) {
+    ///     println!("{:?}", restriction);
+    /// }
+    /// ```
+    pub fn restrictions_at(&self, intersection: i32) -> impl Iterator<Item = &TurnRestriction> {
+        self.restrictions
+            .iter()
+            .filter(move |restriction| restriction.intersection == intersection)
+    }
+
+    /// Adds a turn restriction, replacing any existing restriction for the same turn
+    ///
+    /// Example
+    /// ```rust
+    /// intersection_manager.set_restriction(TurnRestriction {
+    ///     intersection: 1,
+    ///     from_road: 3,
+    ///     to_road: 3,
+    ///     kind: RestrictionKind::Forbidden,
+    /// }); // no U-turn from road 3 at intersection 1
+    /// ```
+    pub fn set_restriction(&mut self, restriction: TurnRestriction) {
+        let previous = self._take_restriction(
+            restriction.intersection,
+            restriction.from_road,
+            restriction.to_road,
+        );
+        self.restrictions.push(restriction);
+
+        let (data, entry_type) = match previous {
+            Some(previous) => (previous, HistoryEntryType::Update),
+            None => (restriction, HistoryEntryType::Create),
+        };
+
+        get_history_manager()
+            .lock()
+            .expect("Failed to lock history manager")
+            .create(HistoryEntry {
+                data: HistoryEntryData::Restriction(data),
+                entry_type,
+                manager: Manager::Intersection,
+                attached: Attached::default(),
+            });
+    }
+
+    /// Removes the restriction for a turn, returning it if there was one
+    pub fn remove_restriction(
+        &mut self,
+        intersection: i32,
+        from_road: i32,
+        to_road: i32,
+    ) -> Option<TurnRestriction> {
+        let removed = self._take_restriction(intersection, from_road, to_road)?;
+
+        get_history_manager()
+            .lock()
+            .expect("Failed to lock history manager")
+            .create(HistoryEntry {
+                data: HistoryEntryData::Restriction(removed),
+                entry_type: HistoryEntryType::Destroy,
+                manager: Manager::Intersection,
+                attached: Attached::default(),
+            });
+
+        Some(removed)
+    }
+
+    /// Returns the timing plan of an intersection with traffic signals, or `None` if it has no signals or no phases
+    ///
+    /// Example
+    /// ```rust
+    /// let wait = intersection_manager.signal_plan(1).map_or(0.0, |plan| plan.wait(3, 42.0));
+    /// ```
+    pub fn signal_plan(&self, id: i32) -> Option<SignalPlan> {
+        let intersection = self
+            .cache
+            .as_ref()?
+            .iter()
+            .find(|intersection| intersection._id == Some(id))?;
+
+        if intersection.control != Control::TrafficSignals {
+            return None;
+        }
+
+        let phases: Vec<SignalPhase> = self
+            .signal_phases
+            .iter()
+            .filter(|phase| phase.intersection == id)
+            .cloned()
+            .collect();
+
+        if phases.is_empty() {
+            return None;
         }
 
-        wtr.flush().unwrap();
+        Some(SignalPlan {
+            offset: intersection.signal_offset,
+            phases,
+        })
     }
 
-    /// Loads intersections from a csv file
+    /// Stores the cache to the intersections.csv file, the turn restrictions to the turn_restrictions.csv file and
+    /// the signal phases to the signal_phases.csv file in the data directory. This will overwrite all three files.
     ///
     /// Example
     /// ```rust
-    /// intersection_manager.load(Some("sample/intersections.csv"));
+    /// intersection_manager.store()?; // intersections.csv is now overwritten by the contents of the cache
     /// ```
-    pub fn load(&mut self, _path: Option<&str>) {
-        let mut rdr = csv::Reader::from_path(_path.unwrap_or("data/intersections.csv")).unwrap();
+    pub fn store(&self) -> csv::Result<()> {
+        self.store_to(&get_paths().data_dir)
+    }
 
-        self.cache = Some(Vec::<Intersection>::new());
+    /// Like `store`, but writes the three files to `dir`, creating it if needed
+    ///
+    /// Example
+    /// ```rust
+    /// intersection_manager.store_to(Path::new("backup"))?;
+    /// ```
+    pub fn store_to(&self, dir: &Path) -> csv::Result<()> {
+        std::fs::create_dir_all(dir)?;
+
+        let mut wtr = csv::Writer::from_path(dir.join("intersections.csv"))?;
 
-        for result in rdr.deserialize() {
-            let intersection: Intersection = result.unwrap();
-            self._add(intersection);
+        for intersection in self.cache.as_ref().unwrap() {
+            wtr.serialize(intersection)?;
         }
+
+        wtr.flush()?;
+
+        let mut wtr = csv::Writer::from_path(dir.join("turn_restrictions.csv"))?;
+
+        for restriction in &self.restrictions {
+            wtr.serialize(restriction)?;
+        }
+
+        wtr.flush()?;
+
+        let mut wtr = csv::Writer::from_path(dir.join("signal_phases.csv"))?;
+
+        for phase in &self.signal_phases {
+            wtr.serialize(phase)?;
+        }
+
+        wtr.flush()?;
+        Ok(())
+    }
+
+    /// Loads intersections from a csv file, defaults to intersections.csv in the data directory.
+    /// Turn restrictions and signal phases are loaded from turn_restrictions.csv and signal_phases.csv next to it,
+    /// if they exist.
+    /// The cache is left untouched if the files cannot be read.
+    ///
+    /// Example
+    /// ```rust
+    /// intersection_manager.load(Some(Path::new("sample/intersections.csv")))?;
+    /// ```
+    pub fn load(&mut self, _path: Option<&Path>) -> csv::Result<()> {
+        let path = _path
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| get_paths().data_file("intersections.csv"));
+        let mut rdr = csv::Reader::from_path(&path)?;
+
+        let intersections = rdr
+            .deserialize()
+            .collect::<csv::Result<Vec<Intersection>>>()?;
+
+        let restrictions_path = path.with_file_name("turn_restrictions.csv");
+        let restrictions = if restrictions_path.exists() {
+            csv::Reader::from_path(restrictions_path)?
+                .deserialize()
+                .collect::<csv::Result<Vec<TurnRestriction>>>()?
+        } else {
+            Vec::new()
+        };
+
+        let phases_path = path.with_file_name("signal_phases.csv");
+        let signal_phases = if phases_path.exists() {
+            csv::Reader::from_path(phases_path)?
+                .deserialize()
+                .collect::<csv::Result<Vec<SignalPhase>>>()?
+        } else {
+            Vec::new()
+        };
+
+        self.cache = Some(intersections);
+        self.restrictions = restrictions;
+        self.signal_phases = signal_phases;
+
+        Ok(())
     }
 
     /// Resets the cache to an empty vector
@@ -171,5 +631,72 @@ impl IntersectionManager {
     /// ```
     pub fn reset(&mut self) {
         self.cache = Some(Vec::<Intersection>::new());
+        self.restrictions.clear();
+        self.signal_phases.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(contents: &str) -> Vec<Intersection> {
+        csv::Reader::from_reader(contents.as_bytes())
+            .deserialize()
+            .collect::<csv::Result<Vec<Intersection>>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn traffic_lights_are_migrated_to_signals() {
+        let intersections =
+            read("_id,lat,lon,traffic_lights\n1,50.65,70.23,true\n2,30.24,10.34,false\n");
+
+        assert_eq!(intersections.len(), 2);
+        assert_eq!(intersections[0]._id, Some(1));
+        assert_eq!(intersections[0].control, Control::TrafficSignals);
+        assert_eq!(intersections[1].control, Control::Uncontrolled);
+        assert!(intersections
+            .iter()
+            .all(|intersection| intersection.signal_offset == 0.0));
+    }
+
+    #[test]
+    fn control_and_offset_are_read() {
+        let intersections = read(
+            "_id,lat,lon,control,signal_offset\n1,50.65,70.23,traffic_signals,12.5\n2,30.24,10.34,all_way_stop,0\n3,10.93,40.75,roundabout,\n",
+        );
+
+        let read: Vec<(Control, f64)> = intersections
+            .iter()
+            .map(|intersection| (intersection.control, intersection.signal_offset))
+            .collect();
+        assert_eq!(
+            read,
+            vec![
+                (Control::TrafficSignals, 12.5),
+                (Control::AllWayStop, 0.0),
+                (Control::Roundabout, 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn written_intersections_read_back_the_same() {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer
+            .serialize(Intersection {
+                _id: Some(7),
+                lat: 1.5,
+                lon: -2.0,
+                control: Control::Yield,
+                signal_offset: 4.0,
+            })
+            .unwrap();
+        let written = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+
+        let intersection = read(&written)[0];
+        assert_eq!(intersection.control, Control::Yield);
+        assert_eq!(intersection.signal_offset, 4.0);
     }
 }
@@ -1,3 +1,5 @@
 pub mod history;
 pub mod intersection;
 pub mod road;
+pub mod schedule;
+pub mod signal;
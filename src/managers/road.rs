@@ -1,6 +1,37 @@
+use std::collections::HashMap;
+use std::path::Path;
+
 use crate::get_history_manager;
 use crate::get_logger;
+use crate::get_paths;
 use crate::managers::history::*;
+use crate::managers::intersection::IntersectionManager;
+use crate::managers::schedule::{schedules, Closure, RoadSchedule, SpeedFactor};
+
+/// The directions a road can be driven in, relative to its start and stop points
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// Two-way road
+    #[default]
+    Both,
+    /// One-way road from start to stop
+    Forward,
+    /// One-way road from stop to start
+    Backward,
+}
+
+impl Direction {
+    /// Whether the road can be driven from start to stop
+    pub fn allows_forward(self) -> bool {
+        self != Direction::Backward
+    }
+
+    /// Whether the road can be driven from stop to start
+    pub fn allows_backward(self) -> bool {
+        self != Direction::Forward
+    }
+}
 
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub struct Road {
@@ -13,6 +44,14 @@ pub struct Road {
     pub speed_limit: f64,
     pub lane_count: f64,
     pub road_type: String,
+    #[serde(default)]
+    pub direction: Direction,
+    /// Lanes from start to stop, derived from `lane_count` and `direction` when empty
+    #[serde(default)]
+    pub lanes_forward: Option<f64>,
+    /// Lanes from stop to start, derived from `lane_count` and `direction` when empty
+    #[serde(default)]
+    pub lanes_backward: Option<f64>,
 }
 
 impl Road {
@@ -21,6 +60,32 @@ impl Road {
         [self.start_lat, self.start_lon, self.stop_lat, self.stop_lon]
     }
 
+    /// Returns the number of lanes from start to stop
+    ///
+    /// Example
+    /// ```rust
+    /// // lane_count = 2, direction = both and no explicit lane counts
+    /// road.forward_lanes(); // 1.0
+    /// ```
+    pub fn forward_lanes(&self) -> f64 {
+        match (self.direction, self.lanes_forward) {
+            (Direction::Backward, _) => 0.0,
+            (_, Some(lanes)) => lanes,
+            (Direction::Forward, None) => self.lane_count,
+            (Direction::Both, None) => self.lane_count / 2.0,
+        }
+    }
+
+    /// Returns the number of lanes from stop to start
+    pub fn backward_lanes(&self) -> f64 {
+        match (self.direction, self.lanes_backward) {
+            (Direction::Forward, _) => 0.0,
+            (_, Some(lanes)) => lanes,
+            (Direction::Backward, None) => self.lane_count,
+            (Direction::Both, None) => self.lane_count / 2.0,
+        }
+    }
+
     /// Returns the length of the road
     pub fn length(&self) -> f64 {
         ((self.start_lat - self.stop_lat).powi(2) + (self.start_lon - self.stop_lon).powi(2)).sqrt()
@@ -55,26 +120,29 @@ impl Road {
 #[derive(Debug)]
 pub struct RoadManager {
     pub cache: Option<Vec<Road>>,
+    pub speed_factors: Vec<SpeedFactor>,
+    pub closures: Vec<Closure>,
+}
+
+impl Default for RoadManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl RoadManager {
     /// internal
-    fn _add(&mut self, road: Road) {
+    pub(crate) fn _add(&mut self, road: Road) {
         if let Some(cache) = &mut self.cache {
             cache.push(road);
         }
     }
 
     /// internal
-    fn _remove(&mut self, id: i32) {
+    pub(crate) fn _remove(&mut self, id: i32) {
         if let Some(cache) = &mut self.cache {
-            let mut index = 0;
-            for road in &mut *cache {
-                if road._id == Some(id) {
-                    cache.remove(index);
-                    break;
-                }
-                index += 1;
+            if let Some(index) = cache.iter().position(|road| road._id == Some(id)) {
+                cache.remove(index);
             }
         }
     }
@@ -82,6 +150,8 @@ impl RoadManager {
     pub fn new() -> Self {
         Self {
             cache: Some(Vec::<Road>::new()),
+            speed_factors: Vec::new(),
+            closures: Vec::new(),
         }
     }
 
@@ -116,11 +186,12 @@ impl RoadManager {
     ///   speed_limit: 0.0,
     ///   lane_count: 0.0,
     ///   road_type: "asphalt".to_string(),
+    ///   direction: Direction::Both,
+    ///   lanes_forward: None,
+    ///   lanes_backward: None,
     /// }); // a new road is added to the cache, with a unique id
     /// ```
     pub fn create(&mut self, road: Road) {
-        println!("creating road");
-
         let mut id = 1;
 
         while self
@@ -138,39 +209,53 @@ impl RoadManager {
             ..road
         };
 
-        println!("created road: {:?}", road);
+        log::debug!("created road: {:?}", road);
 
         let road_clone = road.clone();
         self._add(road);
 
-        println!("added road to cache");
-
         get_history_manager().lock().unwrap().create(HistoryEntry {
             data: HistoryEntryData::Road(road_clone),
             entry_type: HistoryEntryType::Create,
             manager: Manager::Road,
+            attached: Attached::default(),
         });
     }
 
-    /// Removes a road from the cache by id. This will not remove the road from the roads.csv file.
+    /// Removes a road from the cache by id, along with its speed factors, closures, the turn restrictions onto and
+    /// off it and its place in signal phases, so a road created later with the same id starts out without them. This
+    /// will not remove the road from the roads.csv file.
     ///
     /// Example
     /// ```rust
-    /// road_manager.destroy(1); // road with id 1 is now removed from the cache
+    /// road_manager.destroy(1, &mut intersection_manager); // road with id 1 is now removed from the cache
     /// ```
-    pub fn destroy(&mut self, id: i32) {
+    pub fn destroy(&mut self, id: i32, intersection_manager: &mut IntersectionManager) {
         if id < 0 {
             return;
         }
 
         let road = self.resolve(id).unwrap().clone();
 
-        if !road._id.is_some() {
+        if road._id.is_none() {
             get_logger().warn("Unable to resolve road");
             return;
         }
 
         self._remove(id);
+        let (speed_factors, kept) = self.speed_factors.drain(..).partition(|row| row._id == id);
+        self.speed_factors = kept;
+        let (closures, kept) = self
+            .closures
+            .drain(..)
+            .partition(|closure| closure._id == id);
+        self.closures = kept;
+
+        let attached = Attached {
+            speed_factors,
+            closures,
+            ..intersection_manager._detach_road(id)
+        };
 
         get_history_manager()
             .lock()
@@ -179,44 +264,109 @@ impl RoadManager {
                 data: HistoryEntryData::Road(road),
                 entry_type: HistoryEntryType::Destroy,
                 manager: Manager::Road,
+                attached,
             });
     }
 
-    /// Stores the cache to the roads.csv file. This will overwrite the file.
+    /// Returns the speed factors and closures of every road that has any, by road id
     ///
     /// Example
     /// ```rust
-    /// road_manager.store(); // roads.csv is now overwritten by the contents of the cache
+    /// let closed = road_manager.schedules().get(&4).is_some_and(|schedule| schedule.is_closed(now));
     /// ```
-    pub fn store(&self) {
-        let mut wtr = csv::Writer::from_path("data/roads.csv").unwrap();
+    pub fn schedules(&self) -> HashMap<i32, RoadSchedule> {
+        schedules(&self.speed_factors, &self.closures)
+    }
+
+    /// Stores the cache to the roads.csv file in the data directory, and the speed factors and closures to
+    /// speed_profiles.csv and closures.csv next to it. This will overwrite the files.
+    ///
+    /// Example
+    /// ```rust
+    /// road_manager.store()?; // roads.csv is now overwritten by the contents of the cache
+    /// ```
+    pub fn store(&self) -> csv::Result<()> {
+        self.store_to(&get_paths().data_dir)
+    }
+
+    /// Like `store`, but to the files in another directory, which is created if it does not exist
+    ///
+    /// Example
+    /// ```rust
+    /// road_manager.store_to(Path::new("backup"))?; // backup/roads.csv now holds the cache
+    /// ```
+    pub fn store_to(&self, dir: &Path) -> csv::Result<()> {
+        std::fs::create_dir_all(dir)?;
+
+        let mut wtr = csv::Writer::from_path(dir.join("roads.csv"))?;
 
         for road in self.cache.as_ref().unwrap() {
-            wtr.serialize(road).unwrap();
+            wtr.serialize(road)?;
         }
 
-        wtr.flush().unwrap();
+        wtr.flush()?;
+
+        let mut wtr = csv::Writer::from_path(dir.join("speed_profiles.csv"))?;
+
+        for factor in &self.speed_factors {
+            wtr.serialize(factor)?;
+        }
+
+        wtr.flush()?;
+
+        let mut wtr = csv::Writer::from_path(dir.join("closures.csv"))?;
+
+        for closure in &self.closures {
+            wtr.serialize(closure)?;
+        }
+
+        wtr.flush()?;
+        Ok(())
     }
 
-    /// Specify a path to load in, defaults to "data/roads.csv". This will overwrite the cache.
+    /// Specify a path to load in, defaults to roads.csv in the data directory. This will overwrite the cache.
+    /// Speed factors and closures are loaded from speed_profiles.csv and closures.csv next to it, if they exist.
+    /// The cache is left untouched if the files cannot be read.
     ///
     /// Example
     /// ```rust
     /// // loads in roads.csv from the data folder
-    /// road_manager.load(); // cache is now overwritten by the contents of roads.csv
+    /// road_manager.load(None)?; // cache is now overwritten by the contents of roads.csv
     ///
     /// // loads in a custom file
-    /// road_manager.load(Some("data/custom_roads.csv")); // cache is now overwritten by the contents of custom_roads.csv
+    /// road_manager.load(Some(Path::new("data/custom_roads.csv")))?; // cache is now overwritten by the contents of custom_roads.csv
     /// ```
-    pub fn load(&mut self, _path: Option<&str>) {
-        let mut rdr = csv::Reader::from_path(_path.unwrap_or("data/roads.csv")).unwrap();
+    pub fn load(&mut self, _path: Option<&Path>) -> csv::Result<()> {
+        let path = _path
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| get_paths().data_file("roads.csv"));
+        let mut rdr = csv::Reader::from_path(&path)?;
 
-        self.cache = Some(Vec::<Road>::new());
+        let roads = rdr.deserialize().collect::<csv::Result<Vec<Road>>>()?;
 
-        for result in rdr.deserialize() {
-            let road: Road = result.unwrap();
-            self._add(road);
-        }
+        let factors_path = path.with_file_name("speed_profiles.csv");
+        let speed_factors = if factors_path.exists() {
+            csv::Reader::from_path(factors_path)?
+                .deserialize()
+                .collect::<csv::Result<Vec<SpeedFactor>>>()?
+        } else {
+            Vec::new()
+        };
+
+        let closures_path = path.with_file_name("closures.csv");
+        let closures = if closures_path.exists() {
+            csv::Reader::from_path(closures_path)?
+                .deserialize()
+                .collect::<csv::Result<Vec<Closure>>>()?
+        } else {
+            Vec::new()
+        };
+
+        self.cache = Some(roads);
+        self.speed_factors = speed_factors;
+        self.closures = closures;
+
+        Ok(())
     }
 
     /// Resets the cache to an empty vector
@@ -227,5 +377,7 @@ impl RoadManager {
     /// ```
     pub fn reset(&mut self) {
         self.cache = Some(Vec::<Road>::new());
+        self.speed_factors.clear();
+        self.closures.clear();
     }
 }
@@ -0,0 +1,463 @@
+use std::collections::HashMap;
+
+const HOUR: f64 = 3600.0;
+const DAY: f64 = 24.0 * HOUR;
+const HOURS_PER_WEEK: usize = 7 * 24;
+
+/// Short names of the days of the week, starting on Monday like the days of a `SpeedFactor`
+pub const WEEKDAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+/// A factor on the speed of a road during an hour of some days of the week, one row of speed_profiles.csv
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct SpeedFactor {
+    pub _id: i32,
+    /// Days of the week the factor applies on, 0 for Monday to 6 for Sunday. Written as short day names separated
+    /// by spaces, `weekdays`, `weekend` or `all`.
+    #[serde(with = "day_list")]
+    pub days: Vec<usize>,
+    /// Hour of the day the factor applies in, 0 to 23, or every hour when empty
+    pub hour: Option<u32>,
+    /// What the speed of the road is multiplied by, e.g. 0.5 for half the speed in rush hour
+    pub factor: f64,
+}
+
+/// A period a road cannot be used, e.g. for road works, one row of closures.csv. Times are seconds since
+/// 1970-01-01 00:00, written as `YYYY-MM-DD HH:MM`.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Closure {
+    pub _id: i32,
+    #[serde(with = "datetime")]
+    pub start: f64,
+    #[serde(with = "datetime")]
+    pub end: f64,
+    #[serde(default)]
+    pub reason: String,
+}
+
+/// The speed factors and closures of a road
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoadSchedule {
+    /// Speed factor of every hour of the week, starting on Monday at 00:00
+    pub factors: Vec<f64>,
+    pub closures: Vec<Closure>,
+}
+
+impl RoadSchedule {
+    /// Collects the speed factors and closures of a road, `None` if it has neither. Factors listed later override
+    /// earlier ones for the same hour.
+    ///
+    /// Example
+    /// ```rust
+    /// let schedule = RoadSchedule::build(4, &road_manager.speed_factors, &road_manager.closures);
+    /// ```
+    pub fn build(road: i32, factors: &[SpeedFactor], closures: &[Closure]) -> Option<RoadSchedule> {
+        let factors: Vec<&SpeedFactor> = factors.iter().filter(|row| row._id == road).collect();
+        let closures: Vec<Closure> = closures
+            .iter()
+            .filter(|closure| closure._id == road)
+            .cloned()
+            .collect();
+
+        if factors.is_empty() && closures.is_empty() {
+            return None;
+        }
+
+        let mut schedule = RoadSchedule {
+            factors: vec![1.0; HOURS_PER_WEEK],
+            closures,
+        };
+
+        for row in factors {
+            let hours = match row.hour {
+                Some(hour) => hour as usize..hour as usize + 1,
+                None => 0..24,
+            };
+
+            for &day in &row.days {
+                for hour in hours.clone().filter(|&hour| hour < 24) {
+                    schedule.factors[day * 24 + hour] = row.factor;
+                }
+            }
+        }
+
+        Some(schedule)
+    }
+
+    /// Returns the factor on the speed of the road at `time`
+    pub fn speed_factor(&self, time: f64) -> f64 {
+        let hour = ((time - monday(time)) / HOUR).floor() as usize;
+        self.factors[hour.min(HOURS_PER_WEEK - 1)]
+    }
+
+    /// Returns whether the road is closed at `time`
+    pub fn is_closed(&self, time: f64) -> bool {
+        self.closures
+            .iter()
+            .any(|closure| closure.start <= time && time < closure.end)
+    }
+
+    /// Returns the seconds it takes to drive `length` meters along the road at `speed` km/h, entering it at `time`.
+    /// The speed changes with the speed factors on the way, so a drive that starts just before rush hour is slowed
+    /// down for the part driven in it. `None` if the road is closed or has a factor of 0 when it is entered.
+    ///
+    /// Example
+    /// ```rust
+    /// let seconds = schedule.travel_time(edge.length, edge.speed, parse_datetime("2026-10-19 08:15")?);
+    /// ```
+    pub fn travel_time(&self, length: f64, speed: f64, time: f64) -> Option<f64> {
+        if self.is_closed(time) || self.speed_factor(time) <= 0.0 {
+            return None;
+        }
+
+        let mut remaining = length;
+        let mut now = time;
+
+        // a week of hours at most, after that the factors repeat
+        for _ in 0..=HOURS_PER_WEEK {
+            let meters_per_second = speed * self.speed_factor(now) / 3.6;
+            let hour_end = ((now / HOUR).floor() + 1.0) * HOUR;
+
+            if meters_per_second > 0.0 {
+                let needed = remaining / meters_per_second;
+                if now + needed <= hour_end {
+                    return Some(now + needed - time);
+                }
+                remaining -= meters_per_second * (hour_end - now);
+            }
+
+            now = hour_end;
+        }
+
+        Some(f64::INFINITY)
+    }
+}
+
+/// Returns the day of the week of a time, 0 for Monday to 6 for Sunday
+///
+/// Example
+/// ```rust
+/// let day = weekday(parse_datetime("2026-10-19 08:15")?); // 0, a Monday
+/// ```
+pub fn weekday(time: f64) -> usize {
+    // 1970-01-01 was a Thursday
+    ((time / DAY).floor() as i64 + 3).rem_euclid(7) as usize
+}
+
+/// Parses `YYYY-MM-DD HH:MM`, optionally with seconds or a `T` between the date and the time, into seconds since
+/// 1970-01-01 00:00. There are no time zones: all times of the network are on the same clock.
+///
+/// Example
+/// ```rust
+/// let depart = parse_datetime("2026-10-19 08:15")?;
+/// ```
+pub fn parse_datetime(text: &str) -> Result<f64, String> {
+    let invalid = || {
+        format!(
+            "Invalid date and time `{}`, expected YYYY-MM-DD HH:MM",
+            text
+        )
+    };
+    let (date, time) = text.trim().split_once([' ', 'T']).ok_or_else(invalid)?;
+
+    let date: Vec<i64> = date
+        .split('-')
+        .map(|part| part.parse().map_err(|_| invalid()))
+        .collect::<Result<_, _>>()?;
+    let time = parse_time_of_day(time).map_err(|_| invalid())?;
+
+    match date[..] {
+        [year, month, day] if (1..=12).contains(&month) && (1..=31).contains(&day) => {
+            Ok(days_from_civil(year, month, day) as f64 * DAY + time)
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// Parses `HH:MM` or `HH:MM:SS` into seconds since midnight
+///
+/// Example
+/// ```rust
+/// let seconds = parse_time_of_day("08:15")?; // 29700.0
+/// ```
+pub fn parse_time_of_day(text: &str) -> Result<f64, String> {
+    let parts: Vec<f64> = text
+        .trim()
+        .split(':')
+        .map(|part| part.parse::<u32>().map(f64::from))
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("Invalid time `{}`, expected HH:MM", text))?;
+
+    match parts[..] {
+        [hours, minutes] if hours < 24.0 && minutes < 60.0 => Ok(hours * HOUR + minutes * 60.0),
+        [hours, minutes, seconds] if hours < 24.0 && minutes < 60.0 && seconds < 60.0 => {
+            Ok(hours * HOUR + minutes * 60.0 + seconds)
+        }
+        _ => Err(format!("Invalid time `{}`, expected HH:MM", text)),
+    }
+}
+
+/// Writes seconds since 1970-01-01 00:00 as `YYYY-MM-DD HH:MM`, or with seconds if it does not fall on a whole
+/// minute
+///
+/// Example
+/// ```rust
+/// let text = format_datetime(1792397700.0); // "2026-10-19 08:15"
+/// ```
+pub fn format_datetime(time: f64) -> String {
+    let time = time.round();
+    let days = (time / DAY).floor();
+    let (year, month, day) = civil_from_days(days as i64);
+    let seconds = (time - days * DAY) as i64;
+
+    if seconds % 60 == 0 {
+        format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}",
+            year,
+            month,
+            day,
+            seconds / 3600,
+            seconds % 3600 / 60
+        )
+    } else {
+        format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            year,
+            month,
+            day,
+            seconds / 3600,
+            seconds % 3600 / 60,
+            seconds % 60
+        )
+    }
+}
+
+/// Returns the start of the Monday of the week a time falls in
+///
+/// @internal
+fn monday(time: f64) -> f64 {
+    ((time / DAY).floor() - weekday(time) as f64) * DAY
+}
+
+/// Days since 1970-01-01 of a date in the proleptic Gregorian calendar
+///
+/// @internal
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The date of a number of days since 1970-01-01, the inverse of `days_from_civil`
+///
+/// @internal
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+/// Builds the schedules of all roads that have speed factors or closures, by road id
+///
+/// Example
+/// ```rust
+/// let schedules = schedules(&road_manager.speed_factors, &road_manager.closures);
+/// ```
+pub fn schedules(factors: &[SpeedFactor], closures: &[Closure]) -> HashMap<i32, RoadSchedule> {
+    let mut roads: Vec<i32> = factors
+        .iter()
+        .map(|row| row._id)
+        .chain(closures.iter().map(|closure| closure._id))
+        .collect();
+    roads.sort_unstable();
+    roads.dedup();
+
+    roads
+        .into_iter()
+        .filter_map(|road| RoadSchedule::build(road, factors, closures).map(|plan| (road, plan)))
+        .collect()
+}
+
+/// Serializes the days of a speed factor as short day names separated by spaces
+///
+/// @internal
+mod day_list {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::WEEKDAYS;
+
+    pub fn serialize<S: Serializer>(days: &[usize], serializer: S) -> Result<S::Ok, S::Error> {
+        let days: Vec<&str> = days.iter().map(|&day| WEEKDAYS[day % 7]).collect();
+        serializer.serialize_str(&days.join(" "))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<usize>, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        let mut days = Vec::new();
+
+        for name in text.split_whitespace() {
+            match name.to_ascii_lowercase().as_str() {
+                "all" => days.extend(0..7),
+                "weekdays" => days.extend(0..5),
+                "weekend" => days.extend(5..7),
+                name => days.push(
+                    WEEKDAYS
+                        .iter()
+                        .position(|day| *day == name)
+                        .ok_or_else(|| {
+                            serde::de::Error::custom(format!("unknown day `{}`", name))
+                        })?,
+                ),
+            }
+        }
+
+        Ok(days)
+    }
+}
+
+/// Serializes seconds since 1970-01-01 00:00 as `YYYY-MM-DD HH:MM`
+///
+/// @internal
+mod datetime {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(time: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::format_datetime(*time))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        super::parse_datetime(&String::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read<T: serde::de::DeserializeOwned>(contents: &str) -> csv::Result<Vec<T>> {
+        csv::Reader::from_reader(contents.as_bytes())
+            .deserialize()
+            .collect()
+    }
+
+    #[test]
+    fn datetimes_are_parsed_and_formatted() {
+        assert_eq!(parse_datetime("1970-01-01 00:00"), Ok(0.0));
+        assert_eq!(parse_datetime("2026-10-19 08:15"), Ok(1_792_397_700.0));
+        assert_eq!(parse_datetime(" 2026-10-19T08:15:30 "), Ok(1_792_397_730.0));
+        assert_eq!(parse_datetime("1969-12-31 23:00"), Ok(-3600.0));
+
+        for text in [
+            "2026-10-19",
+            "2026-13-01 08:00",
+            "2026-10-19 24:00",
+            "19-10 08:00",
+            "today 08:00",
+        ] {
+            assert!(parse_datetime(text).is_err(), "{}", text);
+        }
+
+        for text in [
+            "1970-01-01 00:00",
+            "2024-02-29 12:00",
+            "2000-02-29 23:59",
+            "2100-03-01 00:00:05",
+        ] {
+            assert_eq!(format_datetime(parse_datetime(text).unwrap()), text);
+        }
+    }
+
+    #[test]
+    fn times_of_day_are_parsed() {
+        assert_eq!(parse_time_of_day("08:15"), Ok(29_700.0));
+        assert_eq!(parse_time_of_day("23:59:59"), Ok(86_399.0));
+
+        for text in ["24:00", "08:60", "8", "08:15:60", "-1:00", ""] {
+            assert!(parse_time_of_day(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn weekdays_start_on_monday() {
+        assert_eq!(weekday(0.0), 3); // a Thursday
+        assert_eq!(weekday(-1.0), 2);
+        assert_eq!(weekday(parse_datetime("2026-10-19 08:15").unwrap()), 0);
+        assert_eq!(weekday(parse_datetime("2026-10-25 23:59").unwrap()), 6);
+    }
+
+    #[test]
+    fn speed_factors_apply_to_their_days_and_hours() {
+        let factors: Vec<SpeedFactor> = read(
+            "_id,days,hour,factor\n4,weekdays,8,0.5\n4,sat Sun,,1.25\n4,fri,8,0.75\n7,all,,0.1\n",
+        )
+        .unwrap();
+        assert_eq!(factors[0].days, vec![0, 1, 2, 3, 4]);
+        assert_eq!(factors[1].days, vec![5, 6]);
+
+        let schedule = RoadSchedule::build(4, &factors, &[]).unwrap();
+        let at = |text: &str| schedule.speed_factor(parse_datetime(text).unwrap());
+
+        assert_eq!(at("2026-10-19 08:30"), 0.5);
+        assert_eq!(at("2026-10-19 09:00"), 1.0);
+        assert_eq!(at("2026-10-19 07:59"), 1.0);
+        // later rows override earlier ones
+        assert_eq!(at("2026-10-23 08:00"), 0.75);
+        assert_eq!(at("2026-10-24 03:00"), 1.25);
+        assert_eq!(at("2026-10-25 23:59"), 1.25);
+
+        assert_eq!(RoadSchedule::build(5, &factors, &[]), None);
+        assert!(read::<SpeedFactor>("_id,days,hour,factor\n4,someday,8,0.5\n").is_err());
+    }
+
+    #[test]
+    fn travel_time_follows_the_factors_on_the_way() {
+        let factors: Vec<SpeedFactor> = read("_id,days,hour,factor\n4,mon,8,0.5\n").unwrap();
+        let closures: Vec<Closure> =
+            read("_id,start,end,reason\n4,2026-10-19 12:00,2026-10-19 13:00,road works\n").unwrap();
+        let schedule = RoadSchedule::build(4, &factors, &closures).unwrap();
+        let at = |text: &str| parse_datetime(text).unwrap();
+
+        // 1 km at 36 km/h, 10 m/s
+        assert_eq!(
+            schedule.travel_time(1000.0, 36.0, at("2026-10-19 06:00")),
+            Some(100.0)
+        );
+        assert_eq!(
+            schedule.travel_time(1000.0, 36.0, at("2026-10-19 08:10")),
+            Some(200.0)
+        );
+        // 600 m in the minute before rush hour, the other 400 m at half the speed
+        assert_eq!(
+            schedule.travel_time(1000.0, 36.0, at("2026-10-19 07:59")),
+            Some(140.0)
+        );
+
+        assert!(schedule.is_closed(at("2026-10-19 12:00")));
+        assert!(!schedule.is_closed(at("2026-10-19 13:00")));
+        assert_eq!(
+            schedule.travel_time(1000.0, 36.0, at("2026-10-19 12:30")),
+            None
+        );
+        // a road entered before it closes can still be driven to its end
+        assert_eq!(
+            schedule.travel_time(1000.0, 36.0, at("2026-10-19 11:59")),
+            Some(100.0)
+        );
+    }
+}
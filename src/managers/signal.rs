@@ -0,0 +1,161 @@
+/// The light an approach of a signalized intersection shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalState {
+    Green,
+    Amber,
+    Red,
+}
+
+/// One phase of a signal timing plan: the approaches in `approaches` get `green` seconds of green followed by
+/// `amber` seconds of amber, while every other approach is red. Phases run in the order they are stored.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct SignalPhase {
+    pub intersection: i32,
+    pub green: f64,
+    pub amber: f64,
+    /// Ids of the roads whose traffic arriving at the intersection has green during this phase
+    #[serde(with = "road_list")]
+    pub approaches: Vec<i32>,
+}
+
+impl SignalPhase {
+    /// Returns the length of the phase in seconds
+    pub fn duration(&self) -> f64 {
+        self.green + self.amber
+    }
+}
+
+/// The timing plan of a signalized intersection. The cycle starts `offset` seconds after the start of the clock,
+/// which lets neighbouring signals be coordinated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignalPlan {
+    pub offset: f64,
+    pub phases: Vec<SignalPhase>,
+}
+
+impl SignalPlan {
+    /// Returns the cycle length in seconds, the sum of the phase durations
+    pub fn cycle(&self) -> f64 {
+        self.phases.iter().map(SignalPhase::duration).sum()
+    }
+
+    /// Returns whether a road is given green by any phase. Approaches that are not are treated as unsignalized.
+    pub fn controls(&self, road: i32) -> bool {
+        self.phases
+            .iter()
+            .any(|phase| phase.approaches.contains(&road))
+    }
+
+    /// Returns the light shown to traffic arriving on `road` at `time` seconds, or `None` if the approach is not
+    /// signalized
+    ///
+    /// Example
+    /// ```rust
+    /// if plan.state_at(3, clock.now()) == Some(SignalState::Red) {
+    ///     // stop at the line
+    /// }
+    /// ```
+    pub fn state_at(&self, road: i32, time: f64) -> Option<SignalState> {
+        let cycle = self.cycle();
+        if cycle <= 0.0 || !self.controls(road) {
+            return None;
+        }
+
+        let mut local = (time - self.offset).rem_euclid(cycle);
+        for phase in &self.phases {
+            if local < phase.duration() {
+                if !phase.approaches.contains(&road) {
+                    return Some(SignalState::Red);
+                }
+
+                return Some(if local < phase.green {
+                    SignalState::Green
+                } else {
+                    SignalState::Amber
+                });
+            }
+
+            local -= phase.duration();
+        }
+
+        Some(SignalState::Red)
+    }
+
+    /// Returns how many seconds traffic arriving on `road` at `time` waits for green. Arriving on amber counts as
+    /// arriving on red.
+    ///
+    /// Example
+    /// ```rust
+    /// let wait = plan.wait(3, 42.0); // 0.0 if road 3 has green 42 seconds into the clock
+    /// ```
+    pub fn wait(&self, road: i32, time: f64) -> f64 {
+        let cycle = self.cycle();
+        if cycle <= 0.0 || !self.controls(road) {
+            return 0.0;
+        }
+
+        let local = (time - self.offset).rem_euclid(cycle);
+        let mut start = 0.0;
+        let mut wait = f64::INFINITY;
+
+        for phase in &self.phases {
+            if phase.approaches.contains(&road) {
+                if local >= start && local < start + phase.green {
+                    return 0.0;
+                }
+
+                let next = if start >= local { start } else { start + cycle };
+                wait = wait.min(next - local);
+            }
+
+            start += phase.duration();
+        }
+
+        wait
+    }
+
+    /// Returns the average wait in seconds for traffic arriving on `road` at a random moment of the cycle
+    pub fn expected_wait(&self, road: i32) -> f64 {
+        let cycle = self.cycle();
+        if cycle <= 0.0 || !self.controls(road) {
+            return 0.0;
+        }
+
+        // every stretch without green makes the traffic arriving during it wait half its length on average
+        let mut red = Vec::new();
+        let mut current = 0.0;
+
+        for phase in &self.phases {
+            if phase.approaches.contains(&road) {
+                red.push(current);
+                current = phase.amber;
+            } else {
+                current += phase.duration();
+            }
+        }
+
+        // the red at the end of the cycle continues into the red at its start
+        red[0] += current;
+
+        red.iter().map(|red| red * red / 2.0).sum::<f64>() / cycle
+    }
+}
+
+/// Serializes a list of road ids as a single space separated csv field
+///
+/// @internal
+mod road_list {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(roads: &[i32], serializer: S) -> Result<S::Ok, S::Error> {
+        let roads: Vec<String> = roads.iter().map(i32::to_string).collect();
+        serializer.serialize_str(&roads.join(" "))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<i32>, D::Error> {
+        String::deserialize(deserializer)?
+            .split_whitespace()
+            .map(|road| road.parse().map_err(serde::de::Error::custom))
+            .collect()
+    }
+}
@@ -0,0 +1,100 @@
+use crate::config::AlternativesConfig;
+use crate::routing::astar::astar;
+use crate::routing::dijkstra::shortest_tree;
+use crate::routing::graph::Graph;
+use crate::routing::{Metric, Route};
+
+/// Penalized searches tried per route asked for before giving up on finding more
+const ATTEMPTS_PER_ROUTE: usize = 4;
+
+/// Finds up to `config.count` meaningfully different routes between two nodes, the best route first. The others
+/// are found by repeatedly making the edges of the routes found so far more expensive and searching again, keeping
+/// a route only if at most `config.max_overlap` of its length is shared with any route kept before it and it costs at
+/// most `config.max_stretch` times the best route. Returns no routes if `to` cannot be reached.
+///
+/// Example
+/// ```rust
+/// let routes = alternatives(&graph, start, end, Metric::Time, &config.alternatives);
+/// for route in &routes {
+///     println!("{:.0} m in {:.0} s", route.distance, route.time);
+/// }
+/// ```
+pub fn alternatives(
+    graph: &Graph,
+    from: usize,
+    to: usize,
+    metric: Metric,
+    config: &AlternativesConfig,
+) -> Vec<Route> {
+    let best = match astar(graph, from, to, metric) {
+        Some(best) => best,
+        None => return Vec::new(),
+    };
+
+    let best_cost = cost(&best, metric);
+    let mut penalized: Vec<f64> = graph.edges.iter().map(|edge| edge.cost(metric)).collect();
+    let mut last = best.edges.clone();
+    let mut routes = vec![best];
+
+    for _ in 0..config.count.saturating_sub(1) * ATTEMPTS_PER_ROUTE {
+        if routes.len() >= config.count {
+            break;
+        }
+
+        for &edge_id in &last {
+            penalized[edge_id] *= config.penalty;
+        }
+
+        let tree = shortest_tree(graph, from, &penalized, metric);
+        let edges = match tree.path_to(graph, to) {
+            Some(edges) => edges,
+            None => break,
+        };
+        let candidate = Route::from_edges(graph, from, edges);
+
+        // every search avoids more of the routes found so far, so later candidates are rarely any shorter
+        if cost(&candidate, metric) > best_cost * config.max_stretch {
+            break;
+        }
+
+        last = candidate.edges.clone();
+
+        if routes
+            .iter()
+            .all(|route| overlap(graph, &candidate, route) <= config.max_overlap)
+        {
+            routes.push(candidate);
+        }
+    }
+
+    routes
+}
+
+/// The share of the length of `route` that is driven on edges `other` also drives, 0 for an empty route
+///
+/// Example
+/// ```rust
+/// let shared = overlap(&graph, &routes[1], &routes[0]); // 0.25 if a quarter of the second route is shared
+/// ```
+pub fn overlap(graph: &Graph, route: &Route, other: &Route) -> f64 {
+    if route.distance <= 0.0 {
+        return 0.0;
+    }
+
+    let shared: f64 = route
+        .edges
+        .iter()
+        .filter(|edge_id| other.edges.contains(edge_id))
+        .map(|&edge_id| graph.edges[edge_id].length)
+        .sum();
+
+    shared / route.distance
+}
+
+/// @internal
+fn cost(route: &Route, metric: Metric) -> f64 {
+    match metric {
+        Metric::Distance => route.distance,
+        Metric::Time => route.time,
+    }
+}
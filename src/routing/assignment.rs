@@ -0,0 +1,305 @@
+use std::io::Write;
+use std::path::Path;
+
+use crate::config::AssignmentConfig;
+use crate::get_logger;
+use crate::routing::dijkstra::shortest_tree;
+use crate::routing::graph::Graph;
+use crate::routing::{Metric, RoutingError};
+
+/// Steps of the bisection that finds how far to move towards the all-or-nothing flows
+const LINE_SEARCH_STEPS: usize = 30;
+
+/// Trips between two intersections, one row of `demand.csv`
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Demand {
+    pub from: i32,
+    pub to: i32,
+    /// Vehicles per hour
+    pub trips: f64,
+}
+
+/// Loads the trips of a demand file
+///
+/// Example
+/// ```rust
+/// let demand = load_demand(&get_paths().data_file("demand.csv"))?;
+/// ```
+pub fn load_demand(path: &Path) -> csv::Result<Vec<Demand>> {
+    csv::Reader::from_path(path)?.deserialize().collect()
+}
+
+/// The flows and congested travel times of a road, in both directions. Directions a road cannot be driven in have
+/// no flow and no time.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct RoadFlow {
+    pub road: i32,
+    /// Vehicles per hour from start to stop
+    pub forward_flow: f64,
+    /// Vehicles per hour from stop to start
+    pub backward_flow: f64,
+    /// Congested travel time in seconds from start to stop
+    pub forward_time: Option<f64>,
+    /// Congested travel time in seconds from stop to start
+    pub backward_time: Option<f64>,
+    /// The highest volume/capacity ratio of both directions
+    pub volume_capacity: f64,
+}
+
+/// The result of a traffic assignment, indexed by edge
+#[derive(Debug, Clone)]
+pub struct Assignment {
+    /// Vehicles per hour on each edge
+    pub flows: Vec<f64>,
+    /// Vehicles per hour each edge can carry
+    pub capacities: Vec<f64>,
+    /// Congested travel time of each edge in seconds
+    pub times: Vec<f64>,
+    pub iterations: usize,
+    /// Relative gap between the travel time of the assigned flows and of all trips taking the current fastest
+    /// routes, 0 at equilibrium
+    pub gap: f64,
+    /// Vehicles per hour of trips between intersections without a route
+    pub unassigned: f64,
+}
+
+impl Assignment {
+    /// Returns the flows and congested times per road, ordered by road id
+    ///
+    /// Example
+    /// ```rust
+    /// for road in assignment.road_flows(&graph) {
+    ///     println!("road {} is at {:.0}% of capacity", road.road, road.volume_capacity * 100.0);
+    /// }
+    /// ```
+    pub fn road_flows(&self, graph: &Graph) -> Vec<RoadFlow> {
+        let mut roads: Vec<RoadFlow> = Vec::new();
+
+        for (edge_id, edge) in graph.edges.iter().enumerate() {
+            let index = match roads.binary_search_by_key(&edge.road, |road| road.road) {
+                Ok(index) => index,
+                Err(index) => {
+                    roads.insert(
+                        index,
+                        RoadFlow {
+                            road: edge.road,
+                            forward_flow: 0.0,
+                            backward_flow: 0.0,
+                            forward_time: None,
+                            backward_time: None,
+                            volume_capacity: 0.0,
+                        },
+                    );
+                    index
+                }
+            };
+
+            let road = &mut roads[index];
+            if edge.forward {
+                road.forward_flow = self.flows[edge_id];
+                road.forward_time = Some(self.times[edge_id]);
+            } else {
+                road.backward_flow = self.flows[edge_id];
+                road.backward_time = Some(self.times[edge_id]);
+            }
+            road.volume_capacity = road
+                .volume_capacity
+                .max(self.flows[edge_id] / self.capacities[edge_id]);
+        }
+
+        roads
+    }
+
+    /// Writes the flows per road as csv, one row per road with the columns of `RoadFlow`
+    ///
+    /// Example
+    /// ```rust
+    /// assignment.write_csv(&graph, File::create("flows.csv")?)?;
+    /// ```
+    pub fn write_csv<W: Write>(&self, graph: &Graph, writer: W) -> csv::Result<()> {
+        let mut wtr = csv::Writer::from_writer(writer);
+
+        for road in self.road_flows(graph) {
+            wtr.serialize(road)?;
+        }
+
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+/// Assigns the trips to the network so that no trip can get faster by switching routes (user equilibrium), using
+/// the Frank-Wolfe algorithm. Edge travel times grow with their flow following the BPR volume-delay function, with
+/// capacities derived from the road type and lane count. Intersection delays do not depend on the flow.
+///
+/// Example
+/// ```rust
+/// let assignment = assign(&graph, &demand, &config.assignment)?;
+/// println!("converged to a gap of {} in {} iterations", assignment.gap, assignment.iterations);
+/// ```
+pub fn assign(
+    graph: &Graph,
+    demand: &[Demand],
+    config: &AssignmentConfig,
+) -> Result<Assignment, RoutingError> {
+    let trips = demand
+        .iter()
+        .map(|trip| {
+            let from = graph
+                .intersection_node(trip.from)
+                .ok_or(RoutingError::UnknownIntersection(trip.from))?;
+            let to = graph
+                .intersection_node(trip.to)
+                .ok_or(RoutingError::UnknownIntersection(trip.to))?;
+            Ok((from, to, trip.trips))
+        })
+        .collect::<Result<Vec<(usize, usize, f64)>, RoutingError>>()?;
+
+    let free_flow: Vec<f64> = graph
+        .edges
+        .iter()
+        .map(|edge| edge.cost(Metric::Time))
+        .collect();
+    let capacities: Vec<f64> = graph
+        .edges
+        .iter()
+        .map(|edge| config.capacity(&edge.road_type, edge.lanes))
+        .collect();
+    let bpr = |edge_id: usize, flow: f64| {
+        free_flow[edge_id] * (1.0 + config.alpha * (flow / capacities[edge_id]).powf(config.beta))
+    };
+
+    // the first all-or-nothing assignment at free flow times is the starting point
+    let (mut flows, mut delays, unassigned) = all_or_nothing(graph, &trips, &free_flow);
+    let mut times: Vec<f64> = (0..flows.len())
+        .map(|edge_id| bpr(edge_id, flows[edge_id]))
+        .collect();
+    let mut gap = f64::INFINITY;
+    let mut iterations = 0;
+
+    if unassigned > 0.0 {
+        get_logger().warn(&format!(
+            "(assignment) {} vehicles per hour have no route",
+            unassigned
+        ));
+    }
+
+    while iterations < config.max_iterations {
+        iterations += 1;
+
+        let (target, target_delays, _) = all_or_nothing(graph, &trips, &times);
+
+        let current: f64 = flows
+            .iter()
+            .zip(&times)
+            .map(|(flow, time)| flow * time)
+            .sum::<f64>()
+            + delays;
+        let shortest: f64 = target
+            .iter()
+            .zip(&times)
+            .map(|(flow, time)| flow * time)
+            .sum::<f64>()
+            + target_delays;
+
+        gap = if current > 0.0 {
+            (current - shortest) / current
+        } else {
+            0.0
+        };
+        if gap < config.gap {
+            break;
+        }
+
+        // bisect for the step where moving further towards the target stops reducing the total travel time
+        let slope = |step: f64| {
+            (0..flows.len())
+                .map(|edge_id| {
+                    let direction = target[edge_id] - flows[edge_id];
+                    direction * bpr(edge_id, flows[edge_id] + step * direction)
+                })
+                .sum::<f64>()
+                + target_delays
+                - delays
+        };
+
+        let (mut low, mut high) = (0.0, 1.0);
+        for _ in 0..LINE_SEARCH_STEPS {
+            let step = (low + high) / 2.0;
+            if slope(step) > 0.0 {
+                high = step;
+            } else {
+                low = step;
+            }
+        }
+        let step = (low + high) / 2.0;
+
+        for (flow, target) in flows.iter_mut().zip(&target) {
+            *flow += step * (target - *flow);
+        }
+        delays += step * (target_delays - delays);
+
+        for (edge_id, time) in times.iter_mut().enumerate() {
+            *time = bpr(edge_id, flows[edge_id]);
+        }
+    }
+
+    get_logger().debug(&format!(
+        "(assignment) relative gap {:.6} after {} iterations",
+        gap, iterations
+    ));
+
+    Ok(Assignment {
+        flows,
+        capacities,
+        times,
+        iterations,
+        gap,
+        unassigned,
+    })
+}
+
+/// Sends every trip along its fastest route for the given edge times. Returns the flow on every edge, the total
+/// intersection delay of all trips in vehicles per hour times seconds, and the trips that have no route.
+///
+/// @internal
+fn all_or_nothing(
+    graph: &Graph,
+    trips: &[(usize, usize, f64)],
+    times: &[f64],
+) -> (Vec<f64>, f64, f64) {
+    let mut flows = vec![0.0; graph.edges.len()];
+    let mut delays = 0.0;
+    let mut unassigned = 0.0;
+
+    let mut origins: Vec<usize> = trips.iter().map(|&(from, _, _)| from).collect();
+    origins.sort_unstable();
+    origins.dedup();
+
+    for origin in origins {
+        let tree = shortest_tree(graph, origin, times, Metric::Time);
+
+        for &(_, to, vehicles) in trips.iter().filter(|&&(from, _, _)| from == origin) {
+            let edges = match tree.path_to(graph, to) {
+                Some(edges) => edges,
+                None => {
+                    unassigned += vehicles;
+                    continue;
+                }
+            };
+
+            for (i, &edge_id) in edges.iter().enumerate() {
+                flows[edge_id] += vehicles;
+
+                if i > 0 {
+                    let turn = graph
+                        .turn_cost(edges[i - 1], edge_id, Metric::Time)
+                        .unwrap_or(0.0);
+                    delays += vehicles * turn;
+                }
+            }
+        }
+    }
+
+    (flows, delays, unassigned)
+}
@@ -0,0 +1,105 @@
+use std::collections::BinaryHeap;
+
+use crate::routing::dijkstra::{trace_back, QueueEntry};
+use crate::routing::graph::Graph;
+use crate::routing::{Metric, Route};
+
+/// Finds the cheapest path between two nodes like `shortest_path`, but explores towards the end first. Edges are
+/// taken from the queue by their cost plus a lower bound of the cost left from their end, so far fewer edges are
+/// visited on large networks while the route found is just as cheap.
+///
+/// Example
+/// ```rust
+/// let route = astar(&graph, start, end, Metric::Time); // None if end cannot be reached
+/// ```
+pub fn astar(graph: &Graph, from: usize, to: usize, metric: Metric) -> Option<Route> {
+    search(graph, from, to, metric, None)
+}
+
+/// Like `astar`, departing at `depart` seconds on the clock so traffic signals cost the actual wait at the moment
+/// they are reached, as with `shortest_path_departing`
+///
+/// Example
+/// ```rust
+/// let route = astar_departing(&graph, start, end, Metric::Time, 30.0);
+/// ```
+pub fn astar_departing(
+    graph: &Graph,
+    from: usize,
+    to: usize,
+    metric: Metric,
+    depart: f64,
+) -> Option<Route> {
+    search(graph, from, to, metric, Some(depart))
+}
+
+/// @internal
+fn search(
+    graph: &Graph,
+    from: usize,
+    to: usize,
+    metric: Metric,
+    depart: Option<f64>,
+) -> Option<Route> {
+    if from == to {
+        return Some(Route::from_edges_departing(graph, from, Vec::new(), depart));
+    }
+
+    let remaining = |edge_id: usize| graph.lower_bound(graph.edges[edge_id].to, to, metric);
+
+    let mut cost = vec![f64::INFINITY; graph.edges.len()];
+    let mut via: Vec<Option<usize>> = vec![None; graph.edges.len()];
+    let mut queue = BinaryHeap::new();
+
+    for &edge_id in graph.outgoing(from) {
+        let edge_cost = graph.edges[edge_id].cost(metric);
+
+        if edge_cost < cost[edge_id] {
+            cost[edge_id] = edge_cost;
+            queue.push(QueueEntry {
+                cost: edge_cost + remaining(edge_id),
+                id: edge_id,
+            });
+        }
+    }
+
+    while let Some(QueueEntry {
+        cost: estimate,
+        id: edge_id,
+    }) = queue.pop()
+    {
+        let edge_cost = cost[edge_id];
+        if estimate > edge_cost + remaining(edge_id) {
+            continue;
+        }
+
+        let node = graph.edges[edge_id].to;
+        if node == to {
+            let edges = trace_back(&via, edge_id);
+            return Some(Route::from_edges_departing(graph, from, edges, depart));
+        }
+
+        for &next_id in graph.outgoing(node) {
+            let turn = match depart {
+                Some(depart) => graph.turn_cost_at(edge_id, next_id, metric, depart + edge_cost),
+                None => graph.turn_cost(edge_id, next_id, metric),
+            };
+            let turn = match turn {
+                Some(turn) => turn,
+                None => continue,
+            };
+            let next_cost = edge_cost + turn + graph.edges[next_id].cost(metric);
+
+            if next_cost < cost[next_id] {
+                cost[next_id] = next_cost;
+                via[next_id] = Some(edge_id);
+                queue.push(QueueEntry {
+                    cost: next_cost + remaining(next_id),
+                    id: next_id,
+                });
+            }
+        }
+    }
+
+    None
+}
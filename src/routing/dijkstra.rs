@@ -0,0 +1,764 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::routing::graph::Graph;
+use crate::routing::{Metric, Route};
+
+/// An entry of the priority queue, ordered so that the `BinaryHeap` pops the lowest cost first.
+/// `id` is a node or an edge, depending on what the search runs over.
+#[derive(Debug, Clone, Copy)]
+pub struct QueueEntry {
+    pub cost: f64,
+    pub id: usize,
+}
+
+impl PartialEq for QueueEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for QueueEntry {}
+
+impl PartialOrd for QueueEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueueEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.id.cmp(&self.id))
+    }
+}
+
+/// Finds the cheapest path between two nodes. The search runs over edges rather than nodes, so turn restrictions
+/// and turn costs between consecutive edges are respected.
+///
+/// Example
+/// ```rust
+/// let route = shortest_path(&graph, start, end, Metric::Time); // None if end cannot be reached
+/// ```
+pub fn shortest_path(graph: &Graph, from: usize, to: usize, metric: Metric) -> Option<Route> {
+    search(graph, from, to, metric, None)
+}
+
+/// Finds the cheapest path between two nodes when departing at `depart` seconds on the clock. Traffic signals cost
+/// the actual wait at the moment they are reached rather than their average wait.
+///
+/// Example
+/// ```rust
+/// let route = shortest_path_departing(&graph, start, end, Metric::Time, 30.0);
+/// ```
+pub fn shortest_path_departing(
+    graph: &Graph,
+    from: usize,
+    to: usize,
+    metric: Metric,
+    depart: f64,
+) -> Option<Route> {
+    search(graph, from, to, metric, Some(depart))
+}
+
+/// Finds the fastest path between two nodes when departing at `depart`, in seconds since 1970-01-01 00:00 (see
+/// `managers::schedule::parse_datetime`). Roads are driven at the speed their speed factors give for the hours they
+/// are driven in, traffic signals cost the actual wait when they are reached, and roads closed at the moment they are
+/// reached are not used, even if they open again shortly after.
+///
+/// Example
+/// ```rust
+/// let route = fastest_path_at(&graph, start, end, parse_datetime("2026-10-19 08:15")?);
+/// ```
+pub fn fastest_path_at(graph: &Graph, from: usize, to: usize, depart: f64) -> Option<Route> {
+    if from == to {
+        return Some(Route::from_edges_at(graph, from, Vec::new(), depart));
+    }
+
+    // labels are the times the end of each edge is reached at
+    let mut arrival = vec![f64::INFINITY; graph.edges.len()];
+    let mut via: Vec<Option<usize>> = vec![None; graph.edges.len()];
+    let mut queue = BinaryHeap::new();
+
+    for &edge_id in graph.outgoing(from) {
+        if let Some(time) = graph.travel_time_at(edge_id, depart) {
+            if depart + time < arrival[edge_id] {
+                arrival[edge_id] = depart + time;
+                queue.push(QueueEntry {
+                    cost: depart + time,
+                    id: edge_id,
+                });
+            }
+        }
+    }
+
+    while let Some(QueueEntry {
+        cost: time,
+        id: edge_id,
+    }) = queue.pop()
+    {
+        if time > arrival[edge_id] {
+            continue;
+        }
+
+        let node = graph.edges[edge_id].to;
+        if node == to {
+            let edges = trace_back(&via, edge_id);
+            return Some(Route::from_edges_at(graph, from, edges, depart));
+        }
+
+        for &next_id in graph.outgoing(node) {
+            let enter = match graph.turn_cost_at(edge_id, next_id, Metric::Time, time) {
+                Some(turn) => time + turn,
+                None => continue,
+            };
+            let next_arrival = match graph.travel_time_at(next_id, enter) {
+                Some(travel) => enter + travel,
+                None => continue,
+            };
+
+            if next_arrival < arrival[next_id] {
+                arrival[next_id] = next_arrival;
+                via[next_id] = Some(edge_id);
+                queue.push(QueueEntry {
+                    cost: next_arrival,
+                    id: next_id,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+/// Runs the search over edges, with time-dependent turn costs if a departure time is given
+///
+/// @internal
+fn search(
+    graph: &Graph,
+    from: usize,
+    to: usize,
+    metric: Metric,
+    depart: Option<f64>,
+) -> Option<Route> {
+    if from == to {
+        return Some(Route::from_edges_departing(graph, from, Vec::new(), depart));
+    }
+
+    let mut cost = vec![f64::INFINITY; graph.edges.len()];
+    let mut via: Vec<Option<usize>> = vec![None; graph.edges.len()];
+    let mut queue = BinaryHeap::new();
+
+    for &edge_id in graph.outgoing(from) {
+        let edge_cost = graph.edges[edge_id].cost(metric);
+
+        if edge_cost < cost[edge_id] {
+            cost[edge_id] = edge_cost;
+            queue.push(QueueEntry {
+                cost: edge_cost,
+                id: edge_id,
+            });
+        }
+    }
+
+    while let Some(QueueEntry {
+        cost: edge_cost,
+        id: edge_id,
+    }) = queue.pop()
+    {
+        if edge_cost > cost[edge_id] {
+            continue;
+        }
+
+        let node = graph.edges[edge_id].to;
+        if node == to {
+            let edges = trace_back(&via, edge_id);
+            return Some(Route::from_edges_departing(graph, from, edges, depart));
+        }
+
+        for &next_id in graph.outgoing(node) {
+            let turn = match depart {
+                Some(depart) => graph.turn_cost_at(edge_id, next_id, metric, depart + edge_cost),
+                None => graph.turn_cost(edge_id, next_id, metric),
+            };
+            let turn = match turn {
+                Some(turn) => turn,
+                None => continue,
+            };
+            let next_cost = edge_cost + turn + graph.edges[next_id].cost(metric);
+
+            if next_cost < cost[next_id] {
+                cost[next_id] = next_cost;
+                via[next_id] = Some(edge_id);
+                queue.push(QueueEntry {
+                    cost: next_cost,
+                    id: next_id,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+/// Finds the cheapest path from one node to every other node at once, returning the distance and time of that
+/// path for every node, or `None` for nodes that cannot be reached. Times include the expected intersection delays.
+///
+/// Example
+/// ```rust
+/// let reached = costs_from(&graph, start, Metric::Time);
+/// if let Some((distance, time)) = reached[end] {
+///     println!("{} m in {} s", distance, time);
+/// }
+/// ```
+pub fn costs_from(graph: &Graph, from: usize, metric: Metric) -> Vec<Option<(f64, f64)>> {
+    let mut reached: Vec<Option<(f64, f64)>> = vec![None; graph.nodes.len()];
+    let mut cost = vec![f64::INFINITY; graph.edges.len()];
+    // distance and time of the path each edge label was reached by
+    let mut totals = vec![(0.0, 0.0); graph.edges.len()];
+    let mut queue = BinaryHeap::new();
+
+    reached[from] = Some((0.0, 0.0));
+
+    for &edge_id in graph.outgoing(from) {
+        let edge = &graph.edges[edge_id];
+        let edge_cost = edge.cost(metric);
+
+        if edge_cost < cost[edge_id] {
+            cost[edge_id] = edge_cost;
+            totals[edge_id] = (edge.length, edge.travel_time());
+            queue.push(QueueEntry {
+                cost: edge_cost,
+                id: edge_id,
+            });
+        }
+    }
+
+    while let Some(QueueEntry {
+        cost: edge_cost,
+        id: edge_id,
+    }) = queue.pop()
+    {
+        if edge_cost > cost[edge_id] {
+            continue;
+        }
+
+        let node = graph.edges[edge_id].to;
+        let (distance, time) = totals[edge_id];
+
+        // edges are settled cheapest first, so the first one to arrive at a node holds its best path
+        if reached[node].is_none() {
+            reached[node] = Some((distance, time));
+        }
+
+        for &next_id in graph.outgoing(node) {
+            let turn = match graph.turn_cost(edge_id, next_id, metric) {
+                Some(turn) => turn,
+                None => continue,
+            };
+            let next = &graph.edges[next_id];
+            let next_cost = edge_cost + turn + next.cost(metric);
+
+            if next_cost < cost[next_id] {
+                let delay = match metric {
+                    Metric::Time => turn,
+                    Metric::Distance => graph
+                        .turn_cost(edge_id, next_id, Metric::Time)
+                        .unwrap_or(0.0),
+                };
+
+                cost[next_id] = next_cost;
+                totals[next_id] = (distance + next.length, time + delay + next.travel_time());
+                queue.push(QueueEntry {
+                    cost: next_cost,
+                    id: next_id,
+                });
+            }
+        }
+    }
+
+    reached
+}
+
+/// The cheapest paths from one node to every other node, found by `shortest_tree`
+#[derive(Debug, Clone)]
+pub struct ShortestPathTree {
+    pub from: usize,
+    /// Cost of the cheapest path ending with each edge, infinite for edges that cannot be reached
+    pub cost: Vec<f64>,
+    /// The edge each edge was reached through
+    pub via: Vec<Option<usize>>,
+}
+
+impl ShortestPathTree {
+    /// Returns the edge the cheapest path to a node arrives by, `None` for the start and for nodes that cannot be
+    /// reached
+    pub fn arriving_edge(&self, graph: &Graph, node: usize) -> Option<usize> {
+        graph
+            .incoming(node)
+            .iter()
+            .copied()
+            .filter(|&edge_id| self.cost[edge_id].is_finite())
+            .min_by(|&a, &b| self.cost[a].total_cmp(&self.cost[b]))
+    }
+
+    /// Returns the cost of the cheapest path to a node, `None` if it cannot be reached
+    pub fn cost_to(&self, graph: &Graph, node: usize) -> Option<f64> {
+        if node == self.from {
+            return Some(0.0);
+        }
+
+        self.arriving_edge(graph, node)
+            .map(|edge_id| self.cost[edge_id])
+    }
+
+    /// Returns the edges of the cheapest path to a node in driving order, `None` if it cannot be reached
+    ///
+    /// Example
+    /// ```rust
+    /// let tree = shortest_tree(&graph, start, &costs, Metric::Time);
+    /// let edges = tree.path_to(&graph, end).unwrap_or_default();
+    /// ```
+    pub fn path_to(&self, graph: &Graph, node: usize) -> Option<Vec<usize>> {
+        if node == self.from {
+            return Some(Vec::new());
+        }
+
+        self.arriving_edge(graph, node)
+            .map(|edge_id| trace_back(&self.via, edge_id))
+    }
+}
+
+/// Finds the cheapest paths from one node to every other node, where traversing edge `i` costs `edge_costs[i]` and
+/// turns cost what they cost for `metric`. Used where edge costs change between searches, such as with congestion.
+///
+/// Example
+/// ```rust
+/// let free_flow: Vec<f64> = graph.edges.iter().map(Edge::travel_time).collect();
+/// let tree = shortest_tree(&graph, start, &free_flow, Metric::Time);
+/// ```
+pub fn shortest_tree(
+    graph: &Graph,
+    from: usize,
+    edge_costs: &[f64],
+    metric: Metric,
+) -> ShortestPathTree {
+    let mut cost = vec![f64::INFINITY; graph.edges.len()];
+    let mut via: Vec<Option<usize>> = vec![None; graph.edges.len()];
+    let mut queue = BinaryHeap::new();
+
+    for &edge_id in graph.outgoing(from) {
+        if edge_costs[edge_id] < cost[edge_id] {
+            cost[edge_id] = edge_costs[edge_id];
+            queue.push(QueueEntry {
+                cost: edge_costs[edge_id],
+                id: edge_id,
+            });
+        }
+    }
+
+    while let Some(QueueEntry {
+        cost: edge_cost,
+        id: edge_id,
+    }) = queue.pop()
+    {
+        if edge_cost > cost[edge_id] {
+            continue;
+        }
+
+        for &next_id in graph.outgoing(graph.edges[edge_id].to) {
+            let turn = match graph.turn_cost(edge_id, next_id, metric) {
+                Some(turn) => turn,
+                None => continue,
+            };
+            let next_cost = edge_cost + turn + edge_costs[next_id];
+
+            if next_cost < cost[next_id] {
+                cost[next_id] = next_cost;
+                via[next_id] = Some(edge_id);
+                queue.push(QueueEntry {
+                    cost: next_cost,
+                    id: next_id,
+                });
+            }
+        }
+    }
+
+    ShortestPathTree { from, cost, via }
+}
+
+/// Follows the edges the last edge was reached through back to the start, returning them in driving order
+///
+/// @internal
+pub fn trace_back(via: &[Option<usize>], last: usize) -> Vec<usize> {
+    let mut edges = vec![last];
+    let mut edge_id = last;
+
+    while let Some(previous) = via[edge_id] {
+        edges.push(previous);
+        edge_id = previous;
+    }
+
+    edges.reverse();
+    edges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::rng::Rng;
+    use crate::managers::intersection::{Control, Intersection, RestrictionKind, TurnRestriction};
+    use crate::managers::road::Direction;
+    use crate::managers::schedule::{parse_datetime, schedules, Closure, SpeedFactor};
+    use crate::routing::astar::astar;
+    use crate::routing::bidirectional::bidirectional;
+    use crate::routing::testing::{assert_same_cost, network, options, queries, road};
+
+    const WEST: i32 = 1;
+    const EAST: i32 = 2;
+    const NORTH: i32 = 3;
+    const SOUTH: i32 = 4;
+
+    /// A crossing with an all-way stop at intersection 1, whose four roads end at intersections 2 to 5. The road to
+    /// the south is longer, so turning back on the north road is the shortest way around a turn that is not allowed.
+    fn crossing(restrictions: &[(i32, i32, i32, RestrictionKind)]) -> Graph {
+        let points = [
+            [0.0, 0.0],
+            [0.0, -100.0],
+            [0.0, 100.0],
+            [-100.0, 0.0],
+            [150.0, 0.0],
+        ];
+        let intersections: Vec<Intersection> = points
+            .iter()
+            .enumerate()
+            .map(|(index, point)| Intersection {
+                _id: Some(index as i32 + 1),
+                lat: point[0],
+                lon: point[1],
+                control: if index == 0 {
+                    Control::AllWayStop
+                } else {
+                    Control::Uncontrolled
+                },
+                signal_offset: 0.0,
+            })
+            .collect();
+        let roads = vec![
+            road(WEST, points[1], points[0], 30.0, Direction::Both),
+            road(EAST, points[0], points[2], 30.0, Direction::Both),
+            road(NORTH, points[0], points[3], 30.0, Direction::Both),
+            road(SOUTH, points[0], points[4], 30.0, Direction::Both),
+        ];
+
+        let mut graph = Graph::build(&roads, &intersections, 6.0);
+        let restrictions: Vec<TurnRestriction> = restrictions
+            .iter()
+            .map(
+                |&(intersection, from_road, to_road, kind)| TurnRestriction {
+                    intersection,
+                    from_road,
+                    to_road,
+                    kind,
+                },
+            )
+            .collect();
+        graph.set_turn_restrictions(&restrictions);
+
+        graph
+    }
+
+    fn node(graph: &Graph, intersection: i32) -> usize {
+        graph.intersection_node(intersection).unwrap()
+    }
+
+    /// The edge of a road that arrives at an intersection
+    fn edge(graph: &Graph, road: i32, to: i32) -> usize {
+        graph
+            .incoming(node(graph, to))
+            .iter()
+            .copied()
+            .find(|&edge_id| graph.edges[edge_id].road == road)
+            .unwrap()
+    }
+
+    /// The edge of a road that leaves an intersection
+    fn leaving(graph: &Graph, road: i32, from: i32) -> usize {
+        graph
+            .outgoing(node(graph, from))
+            .iter()
+            .copied()
+            .find(|&edge_id| graph.edges[edge_id].road == road)
+            .unwrap()
+    }
+
+    fn roads(graph: &Graph, from: i32, to: i32) -> Option<Vec<i32>> {
+        shortest_path(graph, node(graph, from), node(graph, to), Metric::Distance)
+            .map(|route| route.roads)
+    }
+
+    #[test]
+    fn turns_cost_the_delay_of_the_intersection() {
+        let graph = crossing(&[]);
+        let arrived = edge(&graph, WEST, 1);
+
+        for road in [EAST, NORTH, SOUTH, WEST] {
+            let leave = leaving(&graph, road, 1);
+            assert_eq!(graph.turn_cost(arrived, leave, Metric::Time), Some(8.0));
+            assert_eq!(graph.turn_cost(arrived, leave, Metric::Distance), Some(0.0));
+        }
+
+        assert_eq!(roads(&graph, 2, 3), Some(vec![WEST, EAST]));
+    }
+
+    #[test]
+    fn forbidden_turn_is_not_taken() {
+        let graph = crossing(&[(1, WEST, EAST, RestrictionKind::Forbidden)]);
+        let arrived = edge(&graph, WEST, 1);
+
+        assert_eq!(
+            graph.turn_cost(arrived, leaving(&graph, EAST, 1), Metric::Time),
+            None
+        );
+        assert_eq!(
+            graph.turn_cost(arrived, leaving(&graph, NORTH, 1), Metric::Time),
+            Some(8.0)
+        );
+
+        let route =
+            shortest_path(&graph, node(&graph, 2), node(&graph, 3), Metric::Distance).unwrap();
+        assert_eq!(route.roads, vec![WEST, NORTH, NORTH, EAST]);
+        assert_eq!(route.distance, 400.0);
+        // coming from the other side the turn is allowed
+        assert_eq!(roads(&graph, 3, 2), Some(vec![EAST, WEST]));
+    }
+
+    #[test]
+    fn mandatory_turn_is_the_only_one_taken() {
+        let graph = crossing(&[(1, WEST, SOUTH, RestrictionKind::Mandatory)]);
+        let arrived = edge(&graph, WEST, 1);
+
+        for road in [EAST, NORTH, WEST] {
+            let leave = leaving(&graph, road, 1);
+            assert_eq!(graph.turn_cost(arrived, leave, Metric::Distance), None);
+        }
+        assert_eq!(
+            graph.turn_cost(arrived, leaving(&graph, SOUTH, 1), Metric::Distance),
+            Some(0.0)
+        );
+
+        assert_eq!(roads(&graph, 2, 3), Some(vec![WEST, SOUTH, SOUTH, EAST]));
+        assert_eq!(roads(&graph, 2, 5), Some(vec![WEST, SOUTH]));
+    }
+
+    #[test]
+    fn u_turn_is_a_turn_onto_the_same_road() {
+        let graph = crossing(&[(4, NORTH, NORTH, RestrictionKind::Forbidden)]);
+        let arrived = edge(&graph, NORTH, 4);
+
+        assert_eq!(
+            graph.turn_cost(arrived, leaving(&graph, NORTH, 4), Metric::Distance),
+            None
+        );
+        // turning back at the crossing is still allowed
+        let arrived = edge(&graph, WEST, 1);
+        assert_eq!(
+            graph.turn_cost(arrived, leaving(&graph, WEST, 1), Metric::Distance),
+            Some(0.0)
+        );
+
+        // without turning back at the end of the north road, the way around goes south
+        let graph = crossing(&[
+            (1, WEST, EAST, RestrictionKind::Forbidden),
+            (4, NORTH, NORTH, RestrictionKind::Forbidden),
+        ]);
+        assert_eq!(roads(&graph, 2, 3), Some(vec![WEST, SOUTH, SOUTH, EAST]));
+
+        // and with no way to turn back anywhere, there is none
+        let graph = crossing(&[
+            (1, WEST, EAST, RestrictionKind::Forbidden),
+            (4, NORTH, NORTH, RestrictionKind::Forbidden),
+            (5, SOUTH, SOUTH, RestrictionKind::Forbidden),
+            (1, WEST, WEST, RestrictionKind::Forbidden),
+        ]);
+        assert_eq!(roads(&graph, 2, 3), None);
+    }
+
+    const DIRECT: i32 = 1;
+
+    /// A road from intersection 1 to 2 at 10 m/s, 100 s when it is free, and a longer detour over intersection 3 that
+    /// takes 127 s. The direct road is slower at 08:00 and in a jam at 17:00 on Mondays, and closed
+    /// from 12:00 to 13:00 on 2026-10-19.
+    fn detour(closures: &str) -> Graph {
+        let points = [[0.0, 0.0], [0.0, 1000.0], [500.0, 500.0]];
+        let intersections: Vec<Intersection> = points
+            .iter()
+            .enumerate()
+            .map(|(index, point)| Intersection {
+                _id: Some(index as i32 + 1),
+                lat: point[0],
+                lon: point[1],
+                control: Control::Uncontrolled,
+                signal_offset: 0.0,
+            })
+            .collect();
+        let roads = vec![
+            road(DIRECT, points[0], points[1], 36.0, Direction::Both),
+            road(2, points[0], points[2], 40.0, Direction::Both),
+            road(3, points[2], points[1], 40.0, Direction::Both),
+        ];
+
+        let factors: Vec<SpeedFactor> = csv::Reader::from_reader(
+            "_id,days,hour,factor\n1,mon,8,0.8\n1,mon,17,0.25\n".as_bytes(),
+        )
+        .deserialize()
+        .collect::<csv::Result<_>>()
+        .unwrap();
+        let closures: Vec<Closure> = csv::Reader::from_reader(closures.as_bytes())
+            .deserialize()
+            .collect::<csv::Result<_>>()
+            .unwrap();
+
+        let mut graph = Graph::build(&roads, &intersections, 6.0);
+        graph.set_schedules(schedules(&factors, &closures));
+        graph
+    }
+
+    fn depart(graph: &Graph, at: &str) -> Option<Route> {
+        fastest_path_at(
+            graph,
+            node(graph, 1),
+            node(graph, 2),
+            parse_datetime(at).unwrap(),
+        )
+    }
+
+    const ROAD_WORKS: &str = "_id,start,end\n1,2026-10-19 12:00,2026-10-19 13:00\n";
+
+    #[test]
+    fn slow_hours_slow_the_route_down_or_make_it_detour() {
+        let graph = detour(ROAD_WORKS);
+
+        let free = depart(&graph, "2026-10-19 06:00").unwrap();
+        assert_eq!(free.roads, vec![DIRECT]);
+        assert_eq!(free.time, 100.0);
+
+        let slow = depart(&graph, "2026-10-19 08:00").unwrap();
+        assert_eq!(slow.roads, vec![DIRECT]);
+        assert_eq!(slow.time, 125.0);
+
+        let jam = depart(&graph, "2026-10-19 17:00").unwrap();
+        assert_eq!(jam.roads, vec![2, 3]);
+        assert!((jam.time - 1000.0 * 2.0_f64.sqrt() / (40.0 / 3.6)).abs() < 1e-9);
+
+        // factors repeat every week
+        assert_eq!(
+            depart(&graph, "2026-10-26 17:00").unwrap().roads,
+            vec![2, 3]
+        );
+        assert_eq!(
+            depart(&graph, "2026-10-20 17:00").unwrap().roads,
+            vec![DIRECT]
+        );
+    }
+
+    #[test]
+    fn closed_roads_are_not_used() {
+        let graph = detour(ROAD_WORKS);
+
+        assert_eq!(
+            depart(&graph, "2026-10-19 12:30").unwrap().roads,
+            vec![2, 3]
+        );
+        // entered just before it closes
+        assert_eq!(
+            depart(&graph, "2026-10-19 11:59").unwrap().roads,
+            vec![DIRECT]
+        );
+        assert_eq!(
+            depart(&graph, "2026-10-19 13:00").unwrap().roads,
+            vec![DIRECT]
+        );
+
+        let graph = detour(&format!(
+            "{}2,2026-10-19 12:00,2026-10-19 13:00\n",
+            ROAD_WORKS
+        ));
+        assert!(depart(&graph, "2026-10-19 12:30").is_none());
+        assert_eq!(
+            fastest_path_at(&graph, node(&graph, 1), node(&graph, 1), 0.0).map(|route| route.time),
+            Some(0.0)
+        );
+    }
+
+    #[test]
+    fn route_times_follow_the_clock() {
+        let graph = detour(ROAD_WORKS);
+        let direct = leaving(&graph, DIRECT, 1);
+        let at = |text: &str| parse_datetime(text).unwrap();
+
+        let route = Route::from_edges_at(
+            &graph,
+            node(&graph, 1),
+            vec![direct],
+            at("2026-10-19 07:59"),
+        );
+        // 600 m in the minute before 08:00, the other 400 m at 8 m/s
+        assert_eq!(route.time, 110.0);
+        assert_eq!(route.distance, 1000.0);
+
+        let route = Route::from_edges_at(
+            &graph,
+            node(&graph, 1),
+            vec![direct],
+            at("2026-10-19 12:00"),
+        );
+        assert_eq!(route.time, f64::INFINITY);
+
+        let detour = vec![leaving(&graph, 2, 1), leaving(&graph, 3, 3)];
+        let route = Route::from_edges_at(
+            &graph,
+            node(&graph, 1),
+            detour.clone(),
+            at("2026-10-19 12:00"),
+        );
+        let expected = Route::from_edges(&graph, node(&graph, 1), detour);
+        assert_eq!(route.time, expected.time);
+        assert_eq!(
+            route.nodes,
+            vec![node(&graph, 1), node(&graph, 3), node(&graph, 2)]
+        );
+    }
+
+    /// A search between two nodes, like [`shortest_path`]
+    type Search = fn(&Graph, usize, usize, Metric) -> Option<Route>;
+
+    #[test]
+    fn other_searches_cost_the_same() {
+        let searches: [(&str, Search); 2] = [("astar", astar), ("bidirectional", bidirectional)];
+
+        for (name, search) in searches {
+            let mut rng = Rng::new(0x2545_f491_4f6c_dd1d);
+            let (roads, mut graph, unreachable) = network(&mut rng);
+
+            for options in options() {
+                graph.apply_options(&roads, &options);
+
+                for metric in [Metric::Distance, Metric::Time] {
+                    for (from, to) in queries(&mut rng, &graph, unreachable, 200) {
+                        assert_same_cost(
+                            name,
+                            &graph,
+                            shortest_path(&graph, from, to, metric),
+                            search(&graph, from, to, metric),
+                            metric,
+                            (from, to),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,699 @@
+use std::collections::HashMap;
+
+use crate::managers::intersection::{
+    turn_allowed, Control, Intersection, IntersectionManager, TurnRestriction,
+};
+use crate::managers::road::{Road, RoadManager};
+use crate::managers::schedule::RoadSchedule;
+use crate::managers::signal::SignalPlan;
+use crate::routing::options::RouteOptions;
+use crate::routing::profile::Profile;
+use crate::routing::snap::{project, Position};
+use crate::routing::Metric;
+
+/// A point where roads meet. Road endpoints within the snap radius of each other, or of an intersection, share a node.
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub lat: f64,
+    pub lon: f64,
+    pub intersection: Option<i32>,
+    pub control: Control,
+}
+
+/// A road traversed in one direction
+#[derive(Debug, Clone)]
+pub struct Edge {
+    pub road: i32,
+    pub from: usize,
+    pub to: usize,
+    /// Whether the road is traversed from its start to its stop point
+    pub forward: bool,
+    pub length: f64,
+    pub speed_limit: f64,
+    /// The speed the edge is traversed at in km/h: the speed limit, or the speed of the routing profile the graph
+    /// was built for
+    pub speed: f64,
+    pub lanes: f64,
+    pub road_type: String,
+    /// Factor the cost of the edge is multiplied by, set by `Graph::apply_options`. Infinite for roads routes may not
+    /// use.
+    pub weight: f64,
+}
+
+impl Edge {
+    /// Returns the time in seconds it takes to traverse the edge at its speed.
+    /// Lengths are in meters and speeds in km/h.
+    pub fn travel_time(&self) -> f64 {
+        if self.speed <= 0.0 {
+            return f64::INFINITY;
+        }
+
+        self.length / (self.speed / 3.6)
+    }
+
+    /// Returns the cost of traversing the edge for the given metric, weighted by the route options applied to the
+    /// graph
+    pub fn cost(&self, metric: Metric) -> f64 {
+        let cost = match metric {
+            Metric::Distance => self.length,
+            Metric::Time => self.travel_time(),
+        };
+
+        // an avoided edge stays unusable even if it has no length
+        if self.weight.is_infinite() {
+            f64::INFINITY
+        } else {
+            cost * self.weight
+        }
+    }
+}
+
+/// A directed graph of the road network
+#[derive(Debug, Clone, Default)]
+pub struct Graph {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+    outgoing: Vec<Vec<usize>>,
+    incoming: Vec<Vec<usize>>,
+    restrictions: Vec<Vec<TurnRestriction>>,
+    signals: Vec<Option<SignalPlan>>,
+    /// Speed factors and closures by road id
+    schedules: HashMap<i32, RoadSchedule>,
+    /// The profile the graph was built for, `None` for driving at the speed limit
+    profile: Option<Profile>,
+    /// The highest speed of any edge, in km/h
+    max_speed: f64,
+    /// The most the straight line between the nodes of an edge exceeds its length, which happens when road
+    /// endpoints are snapped to nodes further apart
+    stretch: f64,
+    /// The most route options lower the cost of any edge, as the factor its cost is divided by
+    discount: f64,
+}
+
+impl Graph {
+    /// Builds the graph from roads and intersections. Every intersection becomes a node, road endpoints are snapped
+    /// to the closest node within `snap_radius` or become a node of their own. One-way roads only get an edge in
+    /// their direction of travel.
+    ///
+    /// Example
+    /// ```rust
+    /// let graph = Graph::build(road_manager.cache.as_ref().unwrap(), intersection_manager.cache.as_ref().unwrap(), 6.0);
+    /// ```
+    pub fn build(roads: &[Road], intersections: &[Intersection], snap_radius: f64) -> Graph {
+        Graph::build_with(roads, intersections, snap_radius, None)
+    }
+
+    /// Like `build`, but for a routing profile: roads of types the profile does not use are left out, edges are
+    /// traversed at the speed of the profile, one-way roads get edges in both directions if the profile ignores them,
+    /// and `turn_cost` scales the delays at intersections the way the profile says.
+    ///
+    /// Example
+    /// ```rust
+    /// let graph = Graph::build_for_profile(&roads, &intersections, 6.0, &Profile::bicycle());
+    /// ```
+    pub fn build_for_profile(
+        roads: &[Road],
+        intersections: &[Intersection],
+        snap_radius: f64,
+        profile: &Profile,
+    ) -> Graph {
+        Graph::build_with(roads, intersections, snap_radius, Some(profile))
+    }
+
+    /// Builds the graph from the roads, intersections, turn restrictions and signal plans held by the managers
+    ///
+    /// Example
+    /// ```rust
+    /// let graph = Graph::from_managers(&road_manager, &intersection_manager, config.routing.snap_radius);
+    /// ```
+    pub fn from_managers(
+        road_manager: &RoadManager,
+        intersection_manager: &IntersectionManager,
+        snap_radius: f64,
+    ) -> Graph {
+        Graph::from_managers_with(road_manager, intersection_manager, snap_radius, None)
+    }
+
+    /// Like `from_managers`, but for a routing profile, see `build_for_profile`
+    ///
+    /// Example
+    /// ```rust
+    /// let profile = config.profile("pedestrian").unwrap();
+    /// let graph = Graph::from_managers_for_profile(&road_manager, &intersection_manager, 6.0, &profile);
+    /// ```
+    pub fn from_managers_for_profile(
+        road_manager: &RoadManager,
+        intersection_manager: &IntersectionManager,
+        snap_radius: f64,
+        profile: &Profile,
+    ) -> Graph {
+        Graph::from_managers_with(
+            road_manager,
+            intersection_manager,
+            snap_radius,
+            Some(profile),
+        )
+    }
+
+    /// Weights the edges by the route options, replacing any options applied before. `roads` are the roads the graph
+    /// was built from, looked up by id. Routes found in the graph afterwards avoid or prefer roads as the options say,
+    /// while their distance and time stay the real ones.
+    ///
+    /// Example
+    /// ```rust
+    /// let options = RouteOptions::default().avoid(RoadFilter::RoadType("dirt".to_string()));
+    /// graph.apply_options(road_manager.cache.as_deref().unwrap_or_default(), &options);
+    /// ```
+    pub fn apply_options(&mut self, roads: &[Road], options: &RouteOptions) {
+        let factors: HashMap<i32, f64> = roads
+            .iter()
+            .filter_map(|road| road._id.map(|id| (id, options.factor(road))))
+            .collect();
+
+        self.discount = 1.0;
+
+        for edge in &mut self.edges {
+            edge.weight = factors.get(&edge.road).copied().unwrap_or(1.0);
+            self.discount = self.discount.max(1.0 / edge.weight);
+        }
+    }
+
+    /// Returns the routing profile the graph was built for, `None` if it was built for driving at the speed limit
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    /// @internal
+    fn from_managers_with(
+        road_manager: &RoadManager,
+        intersection_manager: &IntersectionManager,
+        snap_radius: f64,
+        profile: Option<&Profile>,
+    ) -> Graph {
+        let mut graph = Graph::build_with(
+            road_manager.cache.as_deref().unwrap_or_default(),
+            intersection_manager.cache.as_deref().unwrap_or_default(),
+            snap_radius,
+            profile,
+        );
+        graph.set_turn_restrictions(&intersection_manager.restrictions);
+        graph.schedules = road_manager.schedules();
+
+        for node in 0..graph.nodes.len() {
+            graph.signals[node] = graph.nodes[node]
+                .intersection
+                .and_then(|id| intersection_manager.signal_plan(id));
+        }
+
+        graph
+    }
+
+    /// Returns the speed factors and closures of a road, if it has any
+    pub fn road_schedule(&self, road: i32) -> Option<&RoadSchedule> {
+        self.schedules.get(&road)
+    }
+
+    /// Replaces the speed factors and closures of the roads, by road id
+    pub fn set_schedules(&mut self, schedules: HashMap<i32, RoadSchedule>) {
+        self.schedules = schedules;
+    }
+
+    /// Returns the seconds it takes to traverse an edge entered at `time`, seconds since 1970-01-01 00:00, following
+    /// the speed factors of its road. `None` if the road is closed at that time.
+    ///
+    /// Example
+    /// ```rust
+    /// let seconds = graph.travel_time_at(edge_id, parse_datetime("2026-10-19 08:15")?);
+    /// ```
+    pub fn travel_time_at(&self, edge_id: usize, time: f64) -> Option<f64> {
+        let edge = &self.edges[edge_id];
+
+        match self.schedules.get(&edge.road) {
+            Some(schedule) => schedule.travel_time(edge.length, edge.speed, time),
+            None => Some(edge.travel_time()),
+        }
+    }
+
+    /// Returns the signal timing plan of a node, if it is a signalized intersection with phases
+    pub fn signal_plan(&self, node: usize) -> Option<&SignalPlan> {
+        self.signals[node].as_ref()
+    }
+
+    /// Attaches turn restrictions to the nodes of their intersections, replacing any previous restrictions
+    pub fn set_turn_restrictions(&mut self, restrictions: &[TurnRestriction]) {
+        for node_restrictions in &mut self.restrictions {
+            node_restrictions.clear();
+        }
+
+        for restriction in restrictions {
+            if let Some(node) = self.intersection_node(restriction.intersection) {
+                self.restrictions[node].push(*restriction);
+            }
+        }
+    }
+
+    /// Returns the extra cost of turning from one edge onto the next, or `None` if the turn is not allowed.
+    /// When optimizing for time this is the expected delay of the intersection's control, or the average wait of
+    /// the approach if the intersection has a signal plan.
+    ///
+    /// Example
+    /// ```rust
+    /// if let Some(turn) = graph.turn_cost(arrived_by, leave_by, Metric::Time) {
+    ///     cost += turn + graph.edges[leave_by].cost(Metric::Time);
+    /// }
+    /// ```
+    pub fn turn_cost(&self, from_edge: usize, to_edge: usize, metric: Metric) -> Option<f64> {
+        let from = &self.edges[from_edge];
+        let to = &self.edges[to_edge];
+
+        if !turn_allowed(&self.restrictions[from.to], from.road, to.road) {
+            return None;
+        }
+
+        match metric {
+            Metric::Distance => Some(0.0),
+            Metric::Time => {
+                let delay = match self.signal_plan(from.to) {
+                    Some(plan) if plan.controls(from.road) => plan.expected_wait(from.road),
+                    _ => self.nodes[from.to]
+                        .control
+                        .expected_delay(self.is_major_approach(from_edge)),
+                };
+
+                Some(self.profile_delay(from.to, delay))
+            }
+        }
+    }
+
+    /// Like `turn_cost`, but for arriving at the end of `from_edge` at `time` seconds on the clock. Signal plans give
+    /// the actual wait until the approach turns green instead of the average wait.
+    ///
+    /// Example
+    /// ```rust
+    /// let wait = graph.turn_cost_at(arrived_by, leave_by, Metric::Time, 42.0);
+    /// ```
+    pub fn turn_cost_at(
+        &self,
+        from_edge: usize,
+        to_edge: usize,
+        metric: Metric,
+        time: f64,
+    ) -> Option<f64> {
+        let cost = self.turn_cost(from_edge, to_edge, metric)?;
+        let from = &self.edges[from_edge];
+
+        match (metric, self.signal_plan(from.to)) {
+            (Metric::Time, Some(plan)) if plan.controls(from.road) => {
+                Some(self.profile_delay(from.to, plan.wait(from.road, time)))
+            }
+            _ => Some(cost),
+        }
+    }
+
+    /// Scales a wait at a node and adds the intersection penalty of the profile the graph was built for
+    ///
+    /// @internal
+    fn profile_delay(&self, node: usize, delay: f64) -> f64 {
+        match &self.profile {
+            Some(profile) if self.nodes[node].intersection.is_some() => {
+                delay * profile.delay_factor + profile.intersection_penalty
+            }
+            Some(profile) => delay * profile.delay_factor,
+            None => delay,
+        }
+    }
+
+    /// Returns whether an edge arrives at its node on one of the node's most important roads, ranked by speed limit
+    /// and then by lane count. Vehicles on these roads have priority at two-way stops and yield signs.
+    pub fn is_major_approach(&self, edge_id: usize) -> bool {
+        let rank = |edge: &Edge| (edge.speed_limit, edge.lanes);
+        let edge = &self.edges[edge_id];
+
+        self.incoming(edge.to)
+            .iter()
+            .chain(self.outgoing(edge.to))
+            .all(|&other| rank(&self.edges[other]) <= rank(edge))
+    }
+
+    /// Returns the id of the intersection where two roads meet, picking the one closest to `near` if they meet at
+    /// several, e.g. both ends of the same road for a U-turn
+    ///
+    /// Example
+    /// ```rust
+    /// let intersection = graph.shared_intersection(3, 4, [120.0, 80.0]);
+    /// ```
+    pub fn shared_intersection(&self, road_a: i32, road_b: i32, near: [f64; 2]) -> Option<i32> {
+        let ends = |road: i32| {
+            self.edges
+                .iter()
+                .filter(move |edge| edge.road == road)
+                .flat_map(|edge| [edge.from, edge.to])
+        };
+
+        ends(road_a)
+            .filter(|&node| ends(road_b).any(|other| other == node))
+            .filter_map(|node| {
+                let node = &self.nodes[node];
+                node.intersection
+                    .map(|id| (id, distance(node.lat, node.lon, near[0], near[1])))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(id, _)| id)
+    }
+
+    /// Returns a cost no path between two nodes can undercut: the straight-line distance between them, or the time
+    /// it takes to cover that distance at the highest speed of the network, both lowered by the most any route option
+    /// prefers a road. A* uses this as its heuristic.
+    ///
+    /// Example
+    /// ```rust
+    /// let remaining = graph.lower_bound(node, end, Metric::Time);
+    /// ```
+    pub fn lower_bound(&self, from: usize, to: usize, metric: Metric) -> f64 {
+        let (a, b) = (&self.nodes[from], &self.nodes[to]);
+        let straight =
+            distance(a.lat, a.lon, b.lat, b.lon) / self.stretch.max(1.0) / self.discount.max(1.0);
+
+        match metric {
+            Metric::Distance => straight,
+            Metric::Time if self.max_speed > 0.0 => straight / (self.max_speed / 3.6),
+            Metric::Time => 0.0,
+        }
+    }
+
+    /// Returns the ids of the edges leaving a node
+    pub fn outgoing(&self, node: usize) -> &[usize] {
+        &self.outgoing[node]
+    }
+
+    /// Returns the ids of the edges arriving at a node
+    pub fn incoming(&self, node: usize) -> &[usize] {
+        &self.incoming[node]
+    }
+
+    /// Returns the node of an intersection
+    pub fn intersection_node(&self, intersection: i32) -> Option<usize> {
+        self.nodes
+            .iter()
+            .position(|node| node.intersection == Some(intersection))
+    }
+
+    /// Returns the node closest to a point, if it is within `radius`
+    ///
+    /// Example
+    /// ```rust
+    /// let node = graph.nearest_node(mouse_pos[0], mouse_pos[1], 6.0);
+    /// ```
+    pub fn nearest_node(&self, lat: f64, lon: f64, radius: f64) -> Option<usize> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (i, distance(node.lat, node.lon, lat, lon)))
+            .filter(|(_, d)| *d <= radius)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+
+    /// Adds a node at a position partway along a road and splits the edges of the road there, so routes can start and
+    /// end at it. The cost of the split edges is shared in proportion to their lengths. Returns the node at either end
+    /// of the split edge instead if it is within `snap_radius` of the position, and `None` if the graph has no edges
+    /// for the road, e.g. because the routing profile does not use it.
+    ///
+    /// Example
+    /// ```rust
+    /// let position = snap(roads, mouse_pos[0], mouse_pos[1], 5.0).unwrap();
+    /// let start = graph.insert_position(&position, 6.0).unwrap();
+    /// let route = astar(&graph, start, end, Metric::Time);
+    /// ```
+    pub fn insert_position(&mut self, position: &Position, snap_radius: f64) -> Option<usize> {
+        let point = [position.lat, position.lon];
+        let ends = |edge: &Edge| {
+            let (from, to) = (&self.nodes[edge.from], &self.nodes[edge.to]);
+            ([from.lat, from.lon], [to.lat, to.lon])
+        };
+
+        // a road inserted into before is split into several edges per direction, the point lies on the closest one
+        let (nearest, fraction) = self
+            .edges
+            .iter()
+            .enumerate()
+            .filter(|(_, edge)| edge.road == position.road)
+            .map(|(id, edge)| {
+                let (a, b) = ends(edge);
+                let fraction = project(a, b, point);
+                let on_edge = [
+                    a[0] + (b[0] - a[0]) * fraction,
+                    a[1] + (b[1] - a[1]) * fraction,
+                ];
+                (
+                    id,
+                    fraction,
+                    distance(on_edge[0], on_edge[1], point[0], point[1]),
+                )
+            })
+            .min_by(|a, b| a.2.total_cmp(&b.2).then(a.0.cmp(&b.0)))
+            .map(|(id, fraction, _)| (id, fraction))?;
+
+        let (from, to) = (self.edges[nearest].from, self.edges[nearest].to);
+        let (a, b) = ends(&self.edges[nearest]);
+        let lat = a[0] + (b[0] - a[0]) * fraction;
+        let lon = a[1] + (b[1] - a[1]) * fraction;
+
+        let near_from = distance(a[0], a[1], lat, lon);
+        let near_to = distance(b[0], b[1], lat, lon);
+        if near_from <= snap_radius && near_from <= near_to {
+            return Some(from);
+        }
+        if near_to <= snap_radius {
+            return Some(to);
+        }
+
+        let node = self.add_node(Node {
+            lat,
+            lon,
+            intersection: None,
+            control: Control::Uncontrolled,
+        });
+
+        for edge_id in 0..self.edges.len() {
+            let edge = &self.edges[edge_id];
+            if edge.road != position.road {
+                continue;
+            }
+
+            if (edge.from, edge.to) == (from, to) {
+                self.split_edge(edge_id, node, fraction);
+            } else if (edge.from, edge.to) == (to, from) {
+                self.split_edge(edge_id, node, 1.0 - fraction);
+            }
+        }
+
+        Some(node)
+    }
+
+    /// @internal
+    fn build_with(
+        roads: &[Road],
+        intersections: &[Intersection],
+        snap_radius: f64,
+        profile: Option<&Profile>,
+    ) -> Graph {
+        let mut graph = Graph {
+            profile: profile.cloned(),
+            ..Graph::default()
+        };
+        let mut index = SpatialIndex::new(snap_radius);
+
+        for intersection in intersections {
+            let node = graph.add_node(Node {
+                lat: intersection.lat,
+                lon: intersection.lon,
+                intersection: intersection._id,
+                control: intersection.control,
+            });
+            index.insert(node, intersection.lat, intersection.lon);
+        }
+
+        for road in roads {
+            let id = match road._id {
+                Some(id) => id,
+                None => continue,
+            };
+
+            let speed = match profile {
+                Some(profile) => match profile.speed(&road.road_type, road.speed_limit) {
+                    Some(speed) => speed,
+                    None => continue,
+                },
+                None => road.speed_limit,
+            };
+            let oneway = profile.map_or(true, |profile| profile.oneway);
+
+            let start = graph.snap(&mut index, road.start_lat, road.start_lon, snap_radius);
+            let stop = graph.snap(&mut index, road.stop_lat, road.stop_lon, snap_radius);
+
+            if road.direction.allows_forward() || !oneway {
+                graph.add_edge(Edge {
+                    road: id,
+                    from: start,
+                    to: stop,
+                    forward: true,
+                    length: road.length(),
+                    speed_limit: road.speed_limit,
+                    speed,
+                    lanes: road.forward_lanes(),
+                    road_type: road.road_type.clone(),
+                    weight: 1.0,
+                });
+            }
+
+            if road.direction.allows_backward() || !oneway {
+                graph.add_edge(Edge {
+                    road: id,
+                    from: stop,
+                    to: start,
+                    forward: false,
+                    length: road.length(),
+                    speed_limit: road.speed_limit,
+                    speed,
+                    lanes: road.backward_lanes(),
+                    road_type: road.road_type.clone(),
+                    weight: 1.0,
+                });
+            }
+        }
+
+        graph
+    }
+
+    /// Returns the node closest to a point within `radius`, adding a new node there if there is none
+    ///
+    /// @internal
+    fn snap(&mut self, index: &mut SpatialIndex, lat: f64, lon: f64, radius: f64) -> usize {
+        let nearest = index
+            .around(lat, lon)
+            .map(|node| {
+                (
+                    node,
+                    distance(self.nodes[node].lat, self.nodes[node].lon, lat, lon),
+                )
+            })
+            .filter(|(_, d)| *d <= radius)
+            .min_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)))
+            .map(|(node, _)| node);
+
+        match nearest {
+            Some(node) => node,
+            None => {
+                let node = self.add_node(Node {
+                    lat,
+                    lon,
+                    intersection: None,
+                    control: Control::Uncontrolled,
+                });
+                index.insert(node, lat, lon);
+                node
+            }
+        }
+    }
+
+    /// @internal
+    fn add_node(&mut self, node: Node) -> usize {
+        self.nodes.push(node);
+        self.outgoing.push(Vec::new());
+        self.incoming.push(Vec::new());
+        self.restrictions.push(Vec::new());
+        self.signals.push(None);
+        self.nodes.len() - 1
+    }
+
+    /// @internal
+    fn add_edge(&mut self, edge: Edge) -> usize {
+        self.add_stretch(edge.from, edge.to, edge.length);
+        self.max_speed = self.max_speed.max(edge.speed);
+
+        let id = self.edges.len();
+        self.outgoing[edge.from].push(id);
+        self.incoming[edge.to].push(id);
+        self.edges.push(edge);
+        id
+    }
+
+    /// Ends an edge at `node`, `fraction` of the way along it, and adds an edge from there to where it ended
+    ///
+    /// @internal
+    fn split_edge(&mut self, edge_id: usize, node: usize, fraction: f64) {
+        let mut rest = self.edges[edge_id].clone();
+        rest.from = node;
+        rest.length *= 1.0 - fraction;
+
+        let edge = &mut self.edges[edge_id];
+        let (start, end) = (edge.from, edge.to);
+        edge.to = node;
+        edge.length *= fraction;
+        let length = edge.length;
+
+        self.incoming[end].retain(|&id| id != edge_id);
+        self.incoming[node].push(edge_id);
+        self.add_stretch(start, node, length);
+        self.add_edge(rest);
+    }
+
+    /// Raises `stretch` to cover an edge between two nodes
+    ///
+    /// @internal
+    fn add_stretch(&mut self, from: usize, to: usize, length: f64) {
+        let (from, to) = (&self.nodes[from], &self.nodes[to]);
+        let straight = distance(from.lat, from.lon, to.lat, to.lon);
+        if straight > 0.0 {
+            self.stretch = self.stretch.max(straight / length);
+        }
+    }
+}
+
+/// Nodes bucketed into square cells the size of the snap radius, so finding the nodes near a point does not have to
+/// look at every node of a large network
+///
+/// @internal
+struct SpatialIndex {
+    cell: f64,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+}
+
+impl SpatialIndex {
+    fn new(radius: f64) -> SpatialIndex {
+        SpatialIndex {
+            cell: if radius > 0.0 { radius } else { 1.0 },
+            cells: HashMap::new(),
+        }
+    }
+
+    fn key(&self, lat: f64, lon: f64) -> (i64, i64) {
+        (
+            (lat / self.cell).floor() as i64,
+            (lon / self.cell).floor() as i64,
+        )
+    }
+
+    fn insert(&mut self, node: usize, lat: f64, lon: f64) {
+        let key = self.key(lat, lon);
+        self.cells.entry(key).or_default().push(node);
+    }
+
+    /// Returns the nodes in the cell of a point and the cells around it, which holds every node within one cell
+    /// size of the point
+    fn around(&self, lat: f64, lon: f64) -> impl Iterator<Item = usize> + '_ {
+        let (x, y) = self.key(lat, lon);
+
+        (x - 1..=x + 1)
+            .flat_map(move |x| (y - 1..=y + 1).map(move |y| (x, y)))
+            .filter_map(|key| self.cells.get(&key))
+            .flatten()
+            .copied()
+    }
+}
+
+/// Straight-line distance between two points
+pub fn distance(lat_a: f64, lon_a: f64, lat_b: f64, lon_b: f64) -> f64 {
+    ((lat_a - lat_b).powi(2) + (lon_a - lon_b).powi(2)).sqrt()
+}
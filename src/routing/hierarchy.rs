@@ -0,0 +1,828 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fs::File;
+use std::hash::{BuildHasherDefault, Hasher};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::get_logger;
+use crate::routing::dijkstra::QueueEntry;
+use crate::routing::graph::Graph;
+use crate::routing::{Metric, Route};
+
+const MAGIC: &[u8; 4] = b"RRCH";
+const VERSION: u32 = 1;
+/// Stands in for a missing arc index on disk
+const NO_ARC: u32 = u32::MAX;
+/// Nodes a witness search may settle before giving up, in which case the shortcut is added just in case
+const WITNESS_LIMIT: usize = 500;
+
+/// A connection between two edges of the road graph: driving the second edge right after the first. Shortcuts
+/// stand for two arcs through a contracted edge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Arc {
+    from: u32,
+    to: u32,
+    cost: f64,
+    /// The two arcs a shortcut replaces
+    children: Option<(u32, u32)>,
+}
+
+/// A contraction hierarchy over the edges of a road graph, which answers point-to-point queries with the same
+/// costs as `shortest_path` while only looking at a few hundred edges.
+///
+/// Since turns are what routes pay for and restrictions forbid, the hierarchy is built over the edges of the graph
+/// connected by the turns between them. Edges are contracted from the least to the most important, adding shortcuts
+/// wherever a cheapest path ran through the contracted edge. Queries then only search towards more important edges
+/// from both ends.
+#[derive(Debug, Clone)]
+pub struct ContractionHierarchy {
+    pub metric: Metric,
+    /// Fingerprint of the graph the hierarchy was built for, see `ContractionHierarchy::fingerprint`
+    pub fingerprint: u64,
+    rank: Vec<u32>,
+    arcs: Vec<Arc>,
+    /// Arcs leaving each edge towards more important edges
+    up: Vec<Vec<u32>>,
+    /// Arcs arriving at each edge from more important edges
+    down: Vec<Vec<u32>>,
+}
+
+impl ContractionHierarchy {
+    /// Preprocesses a graph for queries optimized for `metric`. This takes a while on large networks, so the result
+    /// is meant to be stored and reused until the network changes.
+    ///
+    /// Example
+    /// ```rust
+    /// let hierarchy = ContractionHierarchy::build(&graph, Metric::Time);
+    /// ```
+    pub fn build(graph: &Graph, metric: Metric) -> ContractionHierarchy {
+        let nodes = graph.edges.len();
+        let mut arcs: Vec<Arc> = turns(graph, metric)
+            .map(|(from, to, cost)| Arc {
+                from: from as u32,
+                to: to as u32,
+                cost,
+                children: None,
+            })
+            .collect();
+
+        let mut contraction = Contraction {
+            outgoing: vec![Vec::new(); nodes],
+            incoming: vec![Vec::new(); nodes],
+            contracted: vec![false; nodes],
+            neighbours: vec![0; nodes],
+            levels: vec![0; nodes],
+            witness: vec![f64::INFINITY; nodes],
+            touched: Vec::new(),
+        };
+        for (index, arc) in arcs.iter().enumerate() {
+            contraction.outgoing[arc.from as usize].push(index as u32);
+            contraction.incoming[arc.to as usize].push(index as u32);
+        }
+
+        let mut queue: BinaryHeap<Reverse<(i64, usize)>> = (0..nodes)
+            .map(|node| {
+                let shortcuts = contraction.shortcuts(&arcs, node).len();
+                Reverse((contraction.priority(node, shortcuts), node))
+            })
+            .collect();
+        let mut rank = vec![0; nodes];
+        let mut next_rank = 0;
+
+        while let Some(Reverse((_, node))) = queue.pop() {
+            if contraction.contracted[node] {
+                continue;
+            }
+
+            // priorities go stale as neighbours are contracted, so only contract once it is still the lowest
+            let shortcuts = contraction.shortcuts(&arcs, node);
+            let priority = contraction.priority(node, shortcuts.len());
+            if let Some(Reverse((next, _))) = queue.peek() {
+                if priority > *next {
+                    queue.push(Reverse((priority, node)));
+                    continue;
+                }
+            }
+
+            for shortcut in shortcuts {
+                let index = arcs.len() as u32;
+                contraction.outgoing[shortcut.from as usize].push(index);
+                contraction.incoming[shortcut.to as usize].push(index);
+                arcs.push(shortcut);
+            }
+
+            contraction.remove(&arcs, node);
+            rank[node] = next_rank;
+            next_rank += 1;
+        }
+
+        let mut hierarchy = ContractionHierarchy {
+            metric,
+            fingerprint: ContractionHierarchy::fingerprint(graph, metric),
+            rank,
+            arcs,
+            up: Vec::new(),
+            down: Vec::new(),
+        };
+        hierarchy.index_arcs();
+
+        get_logger().debug(&format!(
+            "(hierarchy) contracted {} edges, {} arcs of which {} shortcuts",
+            nodes,
+            hierarchy.arcs.len(),
+            hierarchy
+                .arcs
+                .iter()
+                .filter(|arc| arc.children.is_some())
+                .count()
+        ));
+
+        hierarchy
+    }
+
+    /// Returns a fingerprint of everything in the graph that affects routes for `metric`: the edges, their costs and
+    /// the allowed turns with their costs. Any edit to the roads, intersections or turn restrictions that changes a
+    /// route changes the fingerprint.
+    pub fn fingerprint(graph: &Graph, metric: Metric) -> u64 {
+        let mut hash = Fnv::new();
+        hash.write(&[metric as u8]);
+        hash.write(&(graph.nodes.len() as u64).to_le_bytes());
+
+        for edge in &graph.edges {
+            hash.write(&(edge.from as u64).to_le_bytes());
+            hash.write(&(edge.to as u64).to_le_bytes());
+            hash.write(&edge.cost(metric).to_bits().to_le_bytes());
+        }
+
+        for (from, to, cost) in turns(graph, metric) {
+            hash.write(&(from as u64).to_le_bytes());
+            hash.write(&(to as u64).to_le_bytes());
+            hash.write(&cost.to_bits().to_le_bytes());
+        }
+
+        hash.finish()
+    }
+
+    /// Whether the hierarchy was built for this graph and metric, and still gives the same routes
+    pub fn is_valid_for(&self, graph: &Graph, metric: Metric) -> bool {
+        self.metric == metric
+            && self.fingerprint == ContractionHierarchy::fingerprint(graph, metric)
+    }
+
+    /// Finds the cheapest path between two nodes of the graph the hierarchy was built for
+    ///
+    /// Example
+    /// ```rust
+    /// let route = hierarchy.query(&graph, start, end); // None if end cannot be reached
+    /// ```
+    pub fn query(&self, graph: &Graph, from: usize, to: usize) -> Option<Route> {
+        if from == to {
+            return Some(Route::from_edges(graph, from, Vec::new()));
+        }
+
+        // labels are sparse since the searches only go up the hierarchy, so they are kept in maps
+        let mut forward: Labels = HashMap::default();
+        let mut backward: Labels = HashMap::default();
+        let mut forward_queue = BinaryHeap::new();
+        let mut backward_queue = BinaryHeap::new();
+
+        for &edge_id in graph.outgoing(from) {
+            let cost = graph.edges[edge_id].cost(self.metric);
+            if cost.is_finite() {
+                forward.insert(edge_id as u32, (cost, None));
+                forward_queue.push(QueueEntry { cost, id: edge_id });
+            }
+        }
+
+        for &edge_id in graph.incoming(to) {
+            backward.insert(edge_id as u32, (0.0, None));
+            backward_queue.push(QueueEntry {
+                cost: 0.0,
+                id: edge_id,
+            });
+        }
+
+        let mut best = f64::INFINITY;
+        let mut meeting = None;
+
+        loop {
+            let forward_min = forward_queue
+                .peek()
+                .map_or(f64::INFINITY, |entry| entry.cost);
+            let backward_min = backward_queue
+                .peek()
+                .map_or(f64::INFINITY, |entry| entry.cost);
+
+            // neither search can still find anything cheaper, which also holds once both have run out
+            if forward_min.min(backward_min) >= best {
+                break;
+            }
+
+            let forward_side = forward_min <= backward_min;
+            let (queue, labels, other, arcs, opposite) = if forward_side {
+                (
+                    &mut forward_queue,
+                    &mut forward,
+                    &backward,
+                    &self.up,
+                    &self.down,
+                )
+            } else {
+                (
+                    &mut backward_queue,
+                    &mut backward,
+                    &forward,
+                    &self.down,
+                    &self.up,
+                )
+            };
+
+            let QueueEntry { cost, id } = queue.pop().unwrap();
+            if cost > labels[&(id as u32)].0 {
+                continue;
+            }
+
+            if let Some(&(other_cost, _)) = other.get(&(id as u32)) {
+                if cost + other_cost < best {
+                    best = cost + other_cost;
+                    meeting = Some(id as u32);
+                }
+            }
+
+            // a more important edge already reached offers a cheaper way here, so nothing found from here is needed
+            let stalled = opposite[id].iter().any(|&arc_id| {
+                let arc = &self.arcs[arc_id as usize];
+                let previous = if forward_side { arc.from } else { arc.to };
+                labels
+                    .get(&previous)
+                    .is_some_and(|&(previous_cost, _)| previous_cost + arc.cost < cost)
+            });
+            if stalled {
+                continue;
+            }
+
+            for &arc_id in &arcs[id] {
+                let arc = &self.arcs[arc_id as usize];
+                let next = if forward_side { arc.to } else { arc.from };
+                let next_cost = cost + arc.cost;
+
+                if labels
+                    .get(&next)
+                    .map_or(true, |&(current, _)| next_cost < current)
+                {
+                    labels.insert(next, (next_cost, Some(arc_id)));
+                    queue.push(QueueEntry {
+                        cost: next_cost,
+                        id: next as usize,
+                    });
+                }
+            }
+        }
+
+        let meeting = meeting?;
+
+        // arcs from the start to the meeting edge, then on to the end
+        let mut path = Vec::new();
+        let mut node = meeting;
+        while let Some(arc_id) = forward[&node].1 {
+            path.push(arc_id);
+            node = self.arcs[arc_id as usize].from;
+        }
+        let first = node;
+        path.reverse();
+
+        let mut node = meeting;
+        while let Some(arc_id) = backward[&node].1 {
+            path.push(arc_id);
+            node = self.arcs[arc_id as usize].to;
+        }
+
+        let mut edges = vec![first as usize];
+        for arc_id in path {
+            self.unpack(arc_id, &mut edges);
+        }
+
+        Some(Route::from_edges(graph, from, edges))
+    }
+
+    /// Loads the hierarchy stored at `path` if it still matches the graph, and otherwise builds it and stores it
+    /// there for next time
+    ///
+    /// Example
+    /// ```rust
+    /// let path = get_paths().data_file(&ContractionHierarchy::file_name("car", Metric::Time));
+    /// let hierarchy = ContractionHierarchy::load_or_build(&graph, Metric::Time, &path);
+    /// ```
+    pub fn load_or_build(graph: &Graph, metric: Metric, path: &Path) -> ContractionHierarchy {
+        let logger = get_logger();
+
+        match ContractionHierarchy::load(path) {
+            Ok(hierarchy) if hierarchy.is_valid_for(graph, metric) => {
+                logger.debug(&format!("(hierarchy) loaded {}", path.display()));
+                return hierarchy;
+            }
+            Ok(_) => logger.info(&format!(
+                "(hierarchy) {} is out of date, rebuilding",
+                path.display()
+            )),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                logger.info(&format!("(hierarchy) building {}", path.display()))
+            }
+            Err(err) => logger.warn(&format!(
+                "(hierarchy) unable to load {}, rebuilding: {}",
+                path.display(),
+                err
+            )),
+        }
+
+        let hierarchy = ContractionHierarchy::build(graph, metric);
+        if let Err(err) = hierarchy.store(path) {
+            logger.error(&format!(
+                "(hierarchy) unable to store {}: {}",
+                path.display(),
+                err
+            ));
+        }
+
+        hierarchy
+    }
+
+    /// Returns the name of the file the hierarchy for a routing profile and metric is stored in, next to the data
+    /// files
+    ///
+    /// Example
+    /// ```rust
+    /// let name = ContractionHierarchy::file_name("car", Metric::Time); // "hierarchy_car_time.bin"
+    /// ```
+    pub fn file_name(profile: &str, metric: Metric) -> String {
+        let metric = match metric {
+            Metric::Distance => "distance",
+            Metric::Time => "time",
+        };
+
+        format!("hierarchy_{}_{}.bin", profile, metric)
+    }
+
+    /// Writes the hierarchy to a file
+    ///
+    /// Example
+    /// ```rust
+    /// hierarchy.store(&get_paths().data_file(&ContractionHierarchy::file_name("car", hierarchy.metric)))?;
+    /// ```
+    pub fn store(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&[self.metric as u8])?;
+        writer.write_all(&self.fingerprint.to_le_bytes())?;
+        writer.write_all(&(self.rank.len() as u32).to_le_bytes())?;
+        writer.write_all(&(self.arcs.len() as u32).to_le_bytes())?;
+
+        for rank in &self.rank {
+            writer.write_all(&rank.to_le_bytes())?;
+        }
+
+        for arc in &self.arcs {
+            let (first, second) = arc.children.unwrap_or((NO_ARC, NO_ARC));
+            writer.write_all(&arc.from.to_le_bytes())?;
+            writer.write_all(&arc.to.to_le_bytes())?;
+            writer.write_all(&arc.cost.to_le_bytes())?;
+            writer.write_all(&first.to_le_bytes())?;
+            writer.write_all(&second.to_le_bytes())?;
+        }
+
+        writer.flush()
+    }
+
+    /// Reads a hierarchy written by `store`. Files with arcs or shortcuts pointing out of range are rejected as
+    /// `InvalidData`.
+    ///
+    /// Example
+    /// ```rust
+    /// let hierarchy = ContractionHierarchy::load(Path::new("data/hierarchy_time.bin"))?;
+    /// ```
+    pub fn load(path: &Path) -> io::Result<ContractionHierarchy> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let invalid =
+            |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC || read_u32(&mut reader)? != VERSION {
+            return Err(invalid("not a contraction hierarchy of this version"));
+        }
+
+        let mut metric = [0; 1];
+        reader.read_exact(&mut metric)?;
+        let metric = match metric[0] {
+            0 => Metric::Distance,
+            1 => Metric::Time,
+            _ => return Err(invalid("unknown metric")),
+        };
+
+        let fingerprint = read_u64(&mut reader)?;
+        let nodes = read_u32(&mut reader)? as usize;
+        let arc_count = read_u32(&mut reader)? as usize;
+
+        let rank = (0..nodes)
+            .map(|_| read_u32(&mut reader))
+            .collect::<io::Result<Vec<u32>>>()?;
+
+        let arcs = (0..arc_count)
+            .map(|index| {
+                let from = read_u32(&mut reader)?;
+                let to = read_u32(&mut reader)?;
+                let cost = f64::from_bits(read_u64(&mut reader)?);
+                let first = read_u32(&mut reader)?;
+                let second = read_u32(&mut reader)?;
+
+                if from as usize >= nodes || to as usize >= nodes {
+                    return Err(invalid("arc out of range"));
+                }
+
+                // shortcuts are stored after the arcs they replace, which also keeps unpacking from looping
+                if first != NO_ARC && (first as usize >= index || second as usize >= index) {
+                    return Err(invalid("shortcut out of range"));
+                }
+
+                Ok(Arc {
+                    from,
+                    to,
+                    cost,
+                    children: (first != NO_ARC).then_some((first, second)),
+                })
+            })
+            .collect::<io::Result<Vec<Arc>>>()?;
+
+        let mut hierarchy = ContractionHierarchy {
+            metric,
+            fingerprint,
+            rank,
+            arcs,
+            up: Vec::new(),
+            down: Vec::new(),
+        };
+        hierarchy.index_arcs();
+
+        Ok(hierarchy)
+    }
+
+    /// Sorts the arcs into the upward and downward lists the queries search
+    ///
+    /// @internal
+    fn index_arcs(&mut self) {
+        self.up = vec![Vec::new(); self.rank.len()];
+        self.down = vec![Vec::new(); self.rank.len()];
+
+        for (index, arc) in self.arcs.iter().enumerate() {
+            let (from, to) = (arc.from as usize, arc.to as usize);
+
+            if self.rank[from] < self.rank[to] {
+                self.up[from].push(index as u32);
+            } else {
+                self.down[to].push(index as u32);
+            }
+        }
+    }
+
+    /// Appends the edges an arc leads through to `edges`, expanding shortcuts into the arcs they replace
+    ///
+    /// @internal
+    fn unpack(&self, arc_id: u32, edges: &mut Vec<usize>) {
+        let mut stack = vec![arc_id];
+
+        while let Some(arc_id) = stack.pop() {
+            let arc = &self.arcs[arc_id as usize];
+
+            match arc.children {
+                Some((first, second)) => {
+                    stack.push(second);
+                    stack.push(first);
+                }
+                None => edges.push(arc.to as usize),
+            }
+        }
+    }
+}
+
+/// Cost and arriving arc of the edges reached by a query
+///
+/// @internal
+type Labels = HashMap<u32, (f64, Option<u32>), BuildHasherDefault<EdgeHasher>>;
+
+/// Hashes edge ids with a single multiplication, much cheaper than the default hasher for the many small lookups of
+/// a query
+///
+/// @internal
+#[derive(Default)]
+struct EdgeHasher(u64);
+
+impl Hasher for EdgeHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0.rotate_left(5) ^ byte as u64).wrapping_mul(0x517c_c1b7_2722_0a95);
+        }
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.0 = (value as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// The state of the graph while edges are being contracted
+///
+/// @internal
+struct Contraction {
+    /// Arcs leaving and arriving at each edge, between edges that have not been contracted yet
+    outgoing: Vec<Vec<u32>>,
+    incoming: Vec<Vec<u32>>,
+    contracted: Vec<bool>,
+    /// Number of contracted neighbours of each edge, which spreads the contraction evenly over the graph
+    neighbours: Vec<i64>,
+    /// How many contracted edges deep each edge sits, which keeps the hierarchy shallow
+    levels: Vec<i64>,
+    /// Costs found by the current witness search, and the edges they were found for
+    witness: Vec<f64>,
+    touched: Vec<usize>,
+}
+
+impl Contraction {
+    /// How attractive it is to contract a node next, lower is better: mostly the shortcuts it needs minus the arcs
+    /// it removes, plus its contracted neighbours and its level
+    fn priority(&self, node: usize, shortcuts: usize) -> i64 {
+        let shortcuts = shortcuts as i64;
+        let removed = (self.incoming[node].len() + self.outgoing[node].len()) as i64;
+        2 * shortcuts - removed + self.neighbours[node] + self.levels[node]
+    }
+
+    /// Returns the shortcuts needed to contract a node: one for every pair of arcs through it that is the only
+    /// cheapest way between its ends
+    fn shortcuts(&mut self, arcs: &[Arc], node: usize) -> Vec<Arc> {
+        let incoming = cheapest(arcs, &self.incoming[node], |arc| arc.from);
+        let outgoing = cheapest(arcs, &self.outgoing[node], |arc| arc.to);
+        let mut shortcuts = Vec::new();
+
+        for &(from, in_arc) in &incoming {
+            let in_cost = arcs[in_arc as usize].cost;
+            let limit = outgoing
+                .iter()
+                .filter(|&&(to, _)| to != from)
+                .map(|&(_, out_arc)| in_cost + arcs[out_arc as usize].cost)
+                .fold(f64::NEG_INFINITY, f64::max);
+
+            if limit == f64::NEG_INFINITY {
+                continue;
+            }
+
+            self.witness_search(arcs, from as usize, node, limit);
+
+            for &(to, out_arc) in &outgoing {
+                let cost = in_cost + arcs[out_arc as usize].cost;
+
+                if to != from && self.witness[to as usize] > cost {
+                    shortcuts.push(Arc {
+                        from,
+                        to,
+                        cost,
+                        children: Some((in_arc, out_arc)),
+                    });
+                }
+            }
+        }
+
+        shortcuts
+    }
+
+    /// Finds the cheapest paths from `from` that avoid `skip` and cost at most `limit`, leaving them in `witness`
+    fn witness_search(&mut self, arcs: &[Arc], from: usize, skip: usize, limit: f64) {
+        for &node in &self.touched {
+            self.witness[node] = f64::INFINITY;
+        }
+        self.touched.clear();
+
+        let mut queue = BinaryHeap::new();
+        let mut settled = 0;
+
+        self.witness[from] = 0.0;
+        self.touched.push(from);
+        queue.push(QueueEntry {
+            cost: 0.0,
+            id: from,
+        });
+
+        while let Some(QueueEntry { cost, id }) = queue.pop() {
+            if cost > self.witness[id] {
+                continue;
+            }
+
+            settled += 1;
+            if settled > WITNESS_LIMIT {
+                break;
+            }
+
+            for &arc_id in &self.outgoing[id] {
+                let arc = &arcs[arc_id as usize];
+                let next = arc.to as usize;
+                let next_cost = cost + arc.cost;
+
+                if next == skip || next_cost > limit || next_cost >= self.witness[next] {
+                    continue;
+                }
+
+                if self.witness[next].is_infinite() {
+                    self.touched.push(next);
+                }
+                self.witness[next] = next_cost;
+                queue.push(QueueEntry {
+                    cost: next_cost,
+                    id: next,
+                });
+            }
+        }
+    }
+
+    /// Marks a node as contracted and drops its arcs from its neighbours
+    fn remove(&mut self, arcs: &[Arc], node: usize) {
+        self.contracted[node] = true;
+
+        for arc_id in std::mem::take(&mut self.incoming[node]) {
+            let from = arcs[arc_id as usize].from as usize;
+            self.outgoing[from].retain(|&other| arcs[other as usize].to as usize != node);
+            self.neighbours[from] += 1;
+            self.levels[from] = self.levels[from].max(self.levels[node] + 1);
+        }
+
+        for arc_id in std::mem::take(&mut self.outgoing[node]) {
+            let to = arcs[arc_id as usize].to as usize;
+            self.incoming[to].retain(|&other| arcs[other as usize].from as usize != node);
+            self.neighbours[to] += 1;
+            self.levels[to] = self.levels[to].max(self.levels[node] + 1);
+        }
+    }
+}
+
+/// Keeps only the cheapest of parallel arcs, by the node at their other end
+///
+/// @internal
+fn cheapest(arcs: &[Arc], ids: &[u32], end: impl Fn(&Arc) -> u32) -> Vec<(u32, u32)> {
+    let mut cheapest: Vec<(u32, u32)> = Vec::new();
+
+    for &arc_id in ids {
+        let arc = &arcs[arc_id as usize];
+        let other = end(arc);
+
+        match cheapest.iter_mut().find(|(node, _)| *node == other) {
+            Some(entry) if arcs[entry.1 as usize].cost > arc.cost => entry.1 = arc_id,
+            Some(_) => {}
+            None => cheapest.push((other, arc_id)),
+        }
+    }
+
+    cheapest
+}
+
+/// Returns every allowed turn between two different edges with a finite cost, as the edge turned from, the edge
+/// turned onto and the cost of the turn plus that of the edge turned onto
+///
+/// @internal
+fn turns(graph: &Graph, metric: Metric) -> impl Iterator<Item = (usize, usize, f64)> + '_ {
+    (0..graph.edges.len()).flat_map(move |from| {
+        graph
+            .outgoing(graph.edges[from].to)
+            .iter()
+            .filter(move |&&to| to != from)
+            .filter_map(move |&to| {
+                let turn = graph.turn_cost(from, to, metric)?;
+                Some((from, to, turn + graph.edges[to].cost(metric)))
+            })
+            .filter(|(_, _, cost)| cost.is_finite())
+    })
+}
+
+/// 64-bit FNV-1a, a hash that stays the same across builds so fingerprints stored on disk remain comparable
+///
+/// @internal
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Fnv {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// @internal
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+/// @internal
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::rng::Rng;
+    use crate::routing::dijkstra::shortest_path;
+    use crate::routing::testing::{assert_same_cost, network, queries};
+
+    fn path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("route_rs_{}_{}.bin", name, std::process::id()))
+    }
+
+    #[test]
+    fn query_costs_the_same_as_dijkstra() {
+        let mut rng = Rng::new(0x2545_f491_4f6c_dd1d);
+        let (_, graph, unreachable) = network(&mut rng);
+
+        for metric in [Metric::Distance, Metric::Time] {
+            let hierarchy = ContractionHierarchy::build(&graph, metric);
+
+            for (from, to) in queries(&mut rng, &graph, unreachable, 300) {
+                assert_same_cost(
+                    "hierarchy",
+                    &graph,
+                    shortest_path(&graph, from, to, metric),
+                    hierarchy.query(&graph, from, to),
+                    metric,
+                    (from, to),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn stored_hierarchy_answers_the_same() {
+        let mut rng = Rng::new(0x9e37_79b9_7f4a_7c15);
+        let (_, graph, _) = network(&mut rng);
+        let hierarchy = ContractionHierarchy::build(&graph, Metric::Time);
+
+        let path = path("stored");
+        hierarchy.store(&path).unwrap();
+        let loaded = ContractionHierarchy::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+
+        assert!(loaded.is_valid_for(&graph, Metric::Time));
+        assert_eq!(loaded.rank, hierarchy.rank);
+        assert_eq!(loaded.arcs, hierarchy.arcs);
+
+        for _ in 0..100 {
+            let (from, to) = (rng.below(graph.nodes.len()), rng.below(graph.nodes.len()));
+            assert_same_cost(
+                "stored hierarchy",
+                &graph,
+                hierarchy.query(&graph, from, to),
+                loaded.query(&graph, from, to),
+                Metric::Time,
+                (from, to),
+            );
+        }
+    }
+
+    #[test]
+    fn load_rejects_shortcuts_out_of_range() {
+        let mut rng = Rng::new(0x2545_f491_4f6c_dd1d);
+        let (_, graph, _) = network(&mut rng);
+        let mut hierarchy = ContractionHierarchy::build(&graph, Metric::Distance);
+
+        let arc_count = hierarchy.arcs.len() as u32;
+        let shortcut = hierarchy
+            .arcs
+            .iter()
+            .position(|arc| arc.children.is_some())
+            .unwrap();
+
+        let path = path("out_of_range");
+        for children in [(arc_count, 0), (0, shortcut as u32)] {
+            hierarchy.arcs[shortcut].children = Some(children);
+            hierarchy.store(&path).unwrap();
+
+            let loaded = ContractionHierarchy::load(&path);
+            assert_eq!(loaded.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+use crate::managers::road::Road;
+use crate::routing::graph::Graph;
+use crate::routing::Route;
+
+/// What a driver does at the start of a step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Maneuver {
+    Depart,
+    Straight,
+    SlightLeft,
+    Left,
+    SharpLeft,
+    SlightRight,
+    Right,
+    SharpRight,
+    UTurn,
+    Arrive,
+}
+
+impl Maneuver {
+    /// Returns the maneuver for a turn angle in degrees, positive to the right
+    ///
+    /// Example
+    /// ```rust
+    /// let maneuver = Maneuver::from_angle(-90.0); // Maneuver::Left
+    /// ```
+    pub fn from_angle(angle: f64) -> Maneuver {
+        match angle {
+            a if a.abs() < 20.0 => Maneuver::Straight,
+            a if a.abs() >= 170.0 => Maneuver::UTurn,
+            a if a <= -135.0 => Maneuver::SharpLeft,
+            a if a <= -45.0 => Maneuver::Left,
+            a if a < 0.0 => Maneuver::SlightLeft,
+            a if a < 45.0 => Maneuver::SlightRight,
+            a if a < 135.0 => Maneuver::Right,
+            _ => Maneuver::SharpRight,
+        }
+    }
+}
+
+/// One instruction of a route: a maneuver followed by driving along one or more roads of the same name
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Step {
+    pub maneuver: Maneuver,
+    /// Angle of the turn at the start of the step in degrees, positive to the right as seen in the window. 0 when
+    /// departing and arriving.
+    pub angle: f64,
+    /// Direction of travel at the start of the step in degrees clockwise from the top of the window
+    pub heading: f64,
+    /// Name of the roads driven, empty for unnamed roads
+    pub name: String,
+    /// The ids of the roads driven, in driving order
+    pub roads: Vec<i32>,
+    /// The intersection the maneuver is made at, if the route passes one there
+    pub intersection: Option<i32>,
+    /// Length in meters
+    pub distance: f64,
+}
+
+impl Step {
+    /// Returns the step as a sentence, e.g. "Turn left onto Main St, continue 350 m"
+    pub fn text(&self) -> String {
+        let onto = if self.name.is_empty() {
+            String::new()
+        } else {
+            format!(" onto {}", self.name)
+        };
+
+        let action = match self.maneuver {
+            Maneuver::Depart if self.name.is_empty() => {
+                format!("Head {}", compass(self.heading))
+            }
+            Maneuver::Depart => format!("Head {} on {}", compass(self.heading), self.name),
+            Maneuver::Arrive => {
+                return match self.intersection {
+                    Some(id) => format!("Arrive at intersection {}", id),
+                    None => "Arrive at your destination".to_string(),
+                }
+            }
+            Maneuver::Straight => format!("Go straight{}", onto),
+            Maneuver::SlightLeft => format!("Bear left{}", onto),
+            Maneuver::Left => format!("Turn left{}", onto),
+            Maneuver::SharpLeft => format!("Turn sharp left{}", onto),
+            Maneuver::SlightRight => format!("Bear right{}", onto),
+            Maneuver::Right => format!("Turn right{}", onto),
+            Maneuver::SharpRight => format!("Turn sharp right{}", onto),
+            Maneuver::UTurn => format!("Make a U-turn{}", onto),
+        };
+
+        format!("{}, continue {}", action, format_distance(self.distance))
+    }
+}
+
+impl std::fmt::Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text())
+    }
+}
+
+/// Turns a route into driving instructions. The turn at every node is measured between the directions of the roads
+/// meeting there, taken from their start and stop points in `roads`, and consecutive roads with the same name are
+/// merged into one step unless the route makes a U-turn between them. The last step is always `Maneuver::Arrive`.
+/// Returns no steps for an empty route.
+///
+/// Example
+/// ```rust
+/// for step in instructions(&graph, road_manager.cache.as_deref().unwrap_or_default(), &route) {
+///     println!("{}", step); // "Turn left onto Main St, continue 350 m"
+/// }
+/// ```
+pub fn instructions(graph: &Graph, roads: &[Road], route: &Route) -> Vec<Step> {
+    let roads: HashMap<i32, &Road> = roads
+        .iter()
+        .filter_map(|road| road._id.map(|id| (id, road)))
+        .collect();
+    let name = |edge_id: usize| {
+        roads
+            .get(&graph.edges[edge_id].road)
+            .map_or(String::new(), |road| road.name.trim().to_string())
+    };
+
+    let mut steps: Vec<Step> = Vec::new();
+
+    for (i, &edge_id) in route.edges.iter().enumerate() {
+        let edge = &graph.edges[edge_id];
+        let heading = direction(graph, &roads, edge_id);
+        let edge_name = name(edge_id);
+
+        let maneuver = if i == 0 {
+            (Maneuver::Depart, 0.0)
+        } else {
+            let angle = turn_angle(direction(graph, &roads, route.edges[i - 1]), heading);
+            (Maneuver::from_angle(angle), angle)
+        };
+
+        match steps.last_mut() {
+            Some(step)
+                if !edge_name.is_empty()
+                    && edge_name == step.name
+                    && maneuver.0 != Maneuver::UTurn =>
+            {
+                step.roads.push(edge.road);
+                step.distance += edge.length;
+            }
+            _ => steps.push(Step {
+                maneuver: maneuver.0,
+                angle: maneuver.1,
+                heading: bearing(heading),
+                name: edge_name,
+                roads: vec![edge.road],
+                intersection: graph.nodes[edge.from].intersection,
+                distance: edge.length,
+            }),
+        }
+    }
+
+    if let Some(&last) = route.edges.last() {
+        let edge = &graph.edges[last];
+        steps.push(Step {
+            maneuver: Maneuver::Arrive,
+            angle: 0.0,
+            heading: bearing(direction(graph, &roads, last)),
+            name: name(last),
+            roads: Vec::new(),
+            intersection: graph.nodes[edge.to].intersection,
+            distance: 0.0,
+        });
+    }
+
+    steps
+}
+
+/// Writes the steps as numbered lines of text
+///
+/// Example
+/// ```rust
+/// write_text(&steps, std::io::stdout())?;
+/// ```
+pub fn write_text<W: Write>(steps: &[Step], mut writer: W) -> std::io::Result<()> {
+    for (i, step) in steps.iter().enumerate() {
+        writeln!(writer, "{}. {}", i + 1, step)?;
+    }
+
+    Ok(())
+}
+
+/// Returns a distance in meters the way instructions read it: whole meters up to 100 m, then tens of meters, then
+/// kilometers with one decimal
+///
+/// Example
+/// ```rust
+/// let text = format_distance(1234.0); // "1.2 km"
+/// ```
+pub fn format_distance(meters: f64) -> String {
+    if meters < 100.0 {
+        format!("{:.0} m", meters)
+    } else if meters < 995.0 {
+        format!("{:.0} m", (meters / 10.0).round() * 10.0)
+    } else {
+        format!("{:.1} km", meters / 1000.0)
+    }
+}
+
+/// The direction an edge is traversed in, as a vector in window coordinates
+///
+/// @internal
+fn direction(graph: &Graph, roads: &HashMap<i32, &Road>, edge_id: usize) -> [f64; 2] {
+    let edge = &graph.edges[edge_id];
+
+    match roads.get(&edge.road) {
+        Some(road) if edge.forward => [
+            road.stop_lat - road.start_lat,
+            road.stop_lon - road.start_lon,
+        ],
+        Some(road) => [
+            road.start_lat - road.stop_lat,
+            road.start_lon - road.stop_lon,
+        ],
+        None => {
+            let (from, to) = (&graph.nodes[edge.from], &graph.nodes[edge.to]);
+            [to.lat - from.lat, to.lon - from.lon]
+        }
+    }
+}
+
+/// Signed angle in degrees from one direction to the next. The y axis of the window points down, so a positive
+/// cross product is a turn to the right.
+///
+/// @internal
+fn turn_angle(from: [f64; 2], to: [f64; 2]) -> f64 {
+    let cross = from[0] * to[1] - from[1] * to[0];
+    let dot = from[0] * to[0] + from[1] * to[1];
+
+    cross.atan2(dot).to_degrees()
+}
+
+/// Degrees clockwise from the top of the window
+///
+/// @internal
+fn bearing(direction: [f64; 2]) -> f64 {
+    direction[0]
+        .atan2(-direction[1])
+        .to_degrees()
+        .rem_euclid(360.0)
+}
+
+/// @internal
+fn compass(bearing: f64) -> &'static str {
+    const POINTS: [&str; 8] = [
+        "north",
+        "northeast",
+        "east",
+        "southeast",
+        "south",
+        "southwest",
+        "west",
+        "northwest",
+    ];
+
+    POINTS[((bearing / 45.0).round() as usize) % 8]
+}
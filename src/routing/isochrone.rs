@@ -0,0 +1,321 @@
+use std::io::Write;
+
+use crate::routing::dijkstra::shortest_tree;
+use crate::routing::graph::Graph;
+use crate::routing::Metric;
+
+/// Points closest to a hull edge that are tried when digging it in
+const DIG_CANDIDATES: usize = 8;
+
+/// The part of an edge that can be reached within a budget, from the start of the edge up to `end`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Segment {
+    pub road: i32,
+    pub edge: usize,
+    pub start: [f64; 2],
+    pub end: [f64; 2],
+    /// How much of the edge is reached, 1 for the whole edge
+    pub fraction: f64,
+}
+
+/// Everything reachable within one budget
+#[derive(Debug, Clone, PartialEq)]
+pub struct Band {
+    /// Seconds or meters, depending on the metric
+    pub budget: f64,
+    pub segments: Vec<Segment>,
+}
+
+impl Band {
+    /// Returns the ids of the roads that are at least partially reached, in ascending order
+    pub fn roads(&self) -> Vec<i32> {
+        let mut roads: Vec<i32> = self.segments.iter().map(|segment| segment.road).collect();
+        roads.sort_unstable();
+        roads.dedup();
+        roads
+    }
+
+    /// Returns the outline of the band as a polygon hugging the reached roads, with no boundary edge longer than
+    /// `max_edge` where the points allow it. The first point is not repeated at the end.
+    ///
+    /// Example
+    /// ```rust
+    /// let polygon = band.polygon(20.0);
+    /// ```
+    pub fn polygon(&self, max_edge: f64) -> Vec<[f64; 2]> {
+        let mut points = Vec::new();
+
+        for segment in &self.segments {
+            let length = distance(segment.start, segment.end);
+            let steps = (length / (max_edge / 2.0)).ceil().max(1.0) as usize;
+
+            for step in 0..=steps {
+                points.push(lerp(segment.start, segment.end, step as f64 / steps as f64));
+            }
+        }
+
+        concave_hull(points, max_edge)
+    }
+}
+
+/// Finds what can be reached from a node within each budget. Roads reached only partway are clipped at the exact
+/// point the budget runs out. Budgets are in seconds when optimizing for time and in meters for distance.
+///
+/// Example
+/// ```rust
+/// let bands = isochrone(&graph, start, Metric::Time, &[120.0, 300.0]); // within 2 and 5 minutes
+/// ```
+pub fn isochrone(graph: &Graph, from: usize, metric: Metric, budgets: &[f64]) -> Vec<Band> {
+    let edge_costs: Vec<f64> = graph.edges.iter().map(|edge| edge.cost(metric)).collect();
+    let tree = shortest_tree(graph, from, &edge_costs, metric);
+
+    budgets
+        .iter()
+        .map(|&budget| {
+            let segments = graph
+                .edges
+                .iter()
+                .enumerate()
+                .filter_map(|(edge_id, edge)| {
+                    let end_cost = tree.cost[edge_id];
+                    let start_cost = end_cost - edge_costs[edge_id];
+
+                    if !end_cost.is_finite() || start_cost >= budget {
+                        return None;
+                    }
+
+                    let fraction = if end_cost <= budget {
+                        1.0
+                    } else {
+                        (budget - start_cost) / edge_costs[edge_id]
+                    };
+                    let from = &graph.nodes[edge.from];
+                    let to = &graph.nodes[edge.to];
+                    let start = [from.lat, from.lon];
+
+                    Some(Segment {
+                        road: edge.road,
+                        edge: edge_id,
+                        start,
+                        end: lerp(start, [to.lat, to.lon], fraction),
+                        fraction,
+                    })
+                })
+                .collect();
+
+            Band { budget, segments }
+        })
+        .collect()
+}
+
+/// Writes the bands as a GeoJSON feature collection with one polygon per band. Every feature has the `budget`, the
+/// `metric` and the ids of the `roads` reached as properties. Positions are written as `[lat, lon]`, the x and y of
+/// the window.
+///
+/// Example
+/// ```rust
+/// write_geojson(&bands, Metric::Time, 20.0, File::create("isochrone.geojson")?)?;
+/// ```
+pub fn write_geojson<W: Write>(
+    bands: &[Band],
+    metric: Metric,
+    max_edge: f64,
+    writer: W,
+) -> serde_json::Result<()> {
+    let features: Vec<serde_json::Value> = bands
+        .iter()
+        .map(|band| {
+            let mut ring = band.polygon(max_edge);
+            if let Some(&first) = ring.first() {
+                ring.push(first);
+            }
+
+            serde_json::json!({
+                "type": "Feature",
+                "properties": {
+                    "budget": band.budget,
+                    "metric": metric,
+                    "roads": band.roads(),
+                },
+                "geometry": {
+                    "type": "Polygon",
+                    "coordinates": [ring],
+                },
+            })
+        })
+        .collect();
+
+    serde_json::to_writer(
+        writer,
+        &serde_json::json!({
+            "type": "FeatureCollection",
+            "features": features,
+        }),
+    )
+}
+
+/// Builds a concave hull by starting from the convex hull and repeatedly digging its longest edge in towards the
+/// nearest point inside, as long as the outline stays simple and every point stays inside it
+///
+/// @internal
+fn concave_hull(mut points: Vec<[f64; 2]>, max_edge: f64) -> Vec<[f64; 2]> {
+    points.sort_by(|a, b| a[0].total_cmp(&b[0]).then(a[1].total_cmp(&b[1])));
+    points.dedup();
+
+    let mut hull = convex_hull(&points);
+    if hull.len() < 3 {
+        return hull;
+    }
+
+    let mut inside: Vec<[f64; 2]> = points
+        .into_iter()
+        .filter(|point| !hull.contains(point))
+        .collect();
+    // edges that cannot be dug into any further, by their start point
+    let mut settled: Vec<[f64; 2]> = Vec::new();
+
+    loop {
+        let longest = (0..hull.len())
+            .map(|i| (i, hull[i], hull[(i + 1) % hull.len()]))
+            .filter(|(_, a, b)| distance(*a, *b) > max_edge && !settled.contains(a))
+            .max_by(|x, y| distance(x.1, x.2).total_cmp(&distance(y.1, y.2)));
+
+        let Some((index, a, b)) = longest else {
+            break;
+        };
+
+        let length = distance(a, b);
+        let mut candidates: Vec<(usize, f64)> = inside
+            .iter()
+            .map(|&point| segment_distance(point, a, b))
+            .enumerate()
+            .filter(|&(_, to_edge)| to_edge < length / 2.0)
+            .collect();
+        candidates.sort_by(|x, y| x.1.total_cmp(&y.1));
+
+        let dug = candidates
+            .into_iter()
+            .take(DIG_CANDIDATES)
+            .map(|(candidate, _)| candidate)
+            .find(|&candidate| {
+                let point = inside[candidate];
+
+                distance(a, point) < length
+                    && distance(point, b) < length
+                    && !crosses_hull(&hull, index, a, point)
+                    && !crosses_hull(&hull, index, point, b)
+                    && !inside
+                        .iter()
+                        .any(|&other| other != point && in_triangle(other, a, point, b))
+            });
+
+        match dug {
+            Some(candidate) => {
+                let point = inside.swap_remove(candidate);
+                hull.insert(index + 1, point);
+            }
+            None => settled.push(a),
+        }
+    }
+
+    hull
+}
+
+/// Returns the convex hull of points sorted by x and then y, counterclockwise
+///
+/// @internal
+fn convex_hull(points: &[[f64; 2]]) -> Vec<[f64; 2]> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let mut hull: Vec<[f64; 2]> = Vec::new();
+
+    for pass in 0..2 {
+        let start = hull.len();
+
+        let ordered: Box<dyn Iterator<Item = &[f64; 2]>> = if pass == 0 {
+            Box::new(points.iter())
+        } else {
+            Box::new(points.iter().rev())
+        };
+
+        for &point in ordered {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+
+        // the last point of each half is the first of the other
+        hull.pop();
+    }
+
+    hull
+}
+
+/// Whether the segment from `a` to `b` crosses an edge of the hull other than the one at `skip` and its neighbours
+///
+/// @internal
+fn crosses_hull(hull: &[[f64; 2]], skip: usize, a: [f64; 2], b: [f64; 2]) -> bool {
+    (0..hull.len()).filter(|&i| i != skip).any(|i| {
+        let (c, d) = (hull[i], hull[(i + 1) % hull.len()]);
+        c != a && c != b && d != a && d != b && segments_cross(a, b, c, d)
+    })
+}
+
+/// @internal
+fn segments_cross(a: [f64; 2], b: [f64; 2], c: [f64; 2], d: [f64; 2]) -> bool {
+    let side = |p, q, r| cross(p, q, r).signum();
+
+    side(a, b, c) * side(a, b, d) < 0.0 && side(c, d, a) * side(c, d, b) < 0.0
+}
+
+/// Whether a point lies inside the triangle `a`, `b`, `c` or on its edges, but is none of its corners
+///
+/// @internal
+fn in_triangle(point: [f64; 2], a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> bool {
+    if point == a || point == b || point == c {
+        return false;
+    }
+
+    let (d1, d2, d3) = (cross(a, b, point), cross(b, c, point), cross(c, a, point));
+    let negative = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+    let positive = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+
+    !(negative && positive)
+}
+
+/// Twice the signed area of the triangle `o`, `a`, `b`, positive when counterclockwise
+///
+/// @internal
+fn cross(o: [f64; 2], a: [f64; 2], b: [f64; 2]) -> f64 {
+    (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0])
+}
+
+/// Distance from a point to the segment from `a` to `b`
+///
+/// @internal
+fn segment_distance(point: [f64; 2], a: [f64; 2], b: [f64; 2]) -> f64 {
+    let length = distance(a, b).powi(2);
+    if length == 0.0 {
+        return distance(point, a);
+    }
+
+    let t = (((point[0] - a[0]) * (b[0] - a[0]) + (point[1] - a[1]) * (b[1] - a[1])) / length)
+        .clamp(0.0, 1.0);
+
+    distance(point, lerp(a, b, t))
+}
+
+/// @internal
+fn distance(a: [f64; 2], b: [f64; 2]) -> f64 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt()
+}
+
+/// @internal
+fn lerp(a: [f64; 2], b: [f64; 2], t: f64) -> [f64; 2] {
+    [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t]
+}
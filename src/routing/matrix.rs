@@ -0,0 +1,127 @@
+use std::io::Write;
+use std::thread;
+
+use crate::routing::dijkstra::costs_from;
+use crate::routing::graph::Graph;
+use crate::routing::{Metric, RoutingError};
+
+/// Distances and travel times between every pair of a set of intersections. Entry `[i][j]` is for the route from
+/// `ids[i]` to `ids[j]`, `None` if there is no route.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Matrix {
+    pub ids: Vec<i32>,
+    /// Route lengths in meters
+    pub distances: Vec<Vec<Option<f64>>>,
+    /// Travel times in seconds
+    pub times: Vec<Vec<Option<f64>>>,
+}
+
+/// One pair of a matrix as written to csv
+///
+/// @internal
+#[derive(serde::Serialize)]
+struct MatrixRow {
+    from: i32,
+    to: i32,
+    distance: Option<f64>,
+    time: Option<f64>,
+}
+
+impl Matrix {
+    /// Writes the matrix as csv with one `from,to,distance,time` row per pair. Pairs without a route have empty
+    /// `distance` and `time` fields.
+    ///
+    /// Example
+    /// ```rust
+    /// matrix.write_csv(std::io::stdout())?;
+    /// ```
+    pub fn write_csv<W: Write>(&self, writer: W) -> csv::Result<()> {
+        let mut wtr = csv::Writer::from_writer(writer);
+
+        for (i, &from) in self.ids.iter().enumerate() {
+            for (j, &to) in self.ids.iter().enumerate() {
+                wtr.serialize(MatrixRow {
+                    from,
+                    to,
+                    distance: self.distances[i][j],
+                    time: self.times[i][j],
+                })?;
+            }
+        }
+
+        wtr.flush()?;
+        Ok(())
+    }
+
+    /// Writes the matrix as a JSON object with `ids`, `distances` and `times`, where missing routes are `null`
+    ///
+    /// Example
+    /// ```rust
+    /// matrix.write_json(File::create("matrix.json")?)?;
+    /// ```
+    pub fn write_json<W: Write>(&self, writer: W) -> serde_json::Result<()> {
+        serde_json::to_writer(writer, self)
+    }
+}
+
+/// Computes the distance and travel time matrix between intersections. Routes are optimized for `metric`, and the
+/// searches from the different intersections run on all available cores.
+///
+/// Example
+/// ```rust
+/// let matrix = matrix(&graph, &[1, 2, 3], Metric::Time)?;
+/// println!("{:?}", matrix.times[0][2]); // travel time from intersection 1 to 3
+/// ```
+pub fn matrix(graph: &Graph, ids: &[i32], metric: Metric) -> Result<Matrix, RoutingError> {
+    let nodes = ids
+        .iter()
+        .map(|&id| {
+            graph
+                .intersection_node(id)
+                .ok_or(RoutingError::UnknownIntersection(id))
+        })
+        .collect::<Result<Vec<usize>, RoutingError>>()?;
+
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+    let chunk_size = ((nodes.len() + threads - 1) / threads).max(1);
+
+    let rows: Vec<Vec<Option<(f64, f64)>>> = thread::scope(|scope| {
+        let workers: Vec<_> = nodes
+            .chunks(chunk_size)
+            .map(|sources| {
+                let nodes = &nodes;
+
+                scope.spawn(move || {
+                    sources
+                        .iter()
+                        .map(|&source| {
+                            let reached = costs_from(graph, source, metric);
+                            nodes.iter().map(|&node| reached[node]).collect()
+                        })
+                        .collect::<Vec<Vec<Option<(f64, f64)>>>>()
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    });
+
+    Ok(Matrix {
+        ids: ids.to_vec(),
+        distances: rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell.map(|(distance, _)| distance))
+                    .collect()
+            })
+            .collect(),
+        times: rows
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.map(|(_, time)| time)).collect())
+            .collect(),
+    })
+}
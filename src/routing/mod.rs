@@ -0,0 +1,172 @@
+pub mod alternatives;
+pub mod assignment;
+pub mod astar;
+pub mod bidirectional;
+pub mod dijkstra;
+pub mod graph;
+pub mod hierarchy;
+pub mod instructions;
+pub mod isochrone;
+pub mod matrix;
+pub mod options;
+pub mod profile;
+pub mod snap;
+#[cfg(test)]
+pub(crate) mod testing;
+pub mod tour;
+pub mod vrp;
+
+use graph::Graph;
+
+/// What a route is optimized for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Metric {
+    /// The shortest route, in meters
+    Distance,
+    /// The fastest route at the speed limit or the speeds of a routing profile, in seconds
+    Time,
+}
+
+/// A path through the graph
+#[derive(Debug, Clone, Default)]
+pub struct Route {
+    /// The nodes visited, including the start and end
+    pub nodes: Vec<usize>,
+    /// The edges traversed, in driving order
+    pub edges: Vec<usize>,
+    /// The ids of the roads traversed, in driving order
+    pub roads: Vec<i32>,
+    /// Total length in meters
+    pub distance: f64,
+    /// Total travel time in seconds
+    pub time: f64,
+}
+
+impl Route {
+    /// Builds a route from the edges it traverses. The time includes the expected delays at the intersections
+    /// passed on the way.
+    ///
+    /// Example
+    /// ```rust
+    /// let route = Route::from_edges(&graph, start, vec![3, 7]);
+    /// ```
+    pub fn from_edges(graph: &Graph, from: usize, edges: Vec<usize>) -> Route {
+        Route::from_edges_departing(graph, from, edges, None)
+    }
+
+    /// Builds a route from the edges it traverses. If a departure time is given, the time includes the actual waits
+    /// at traffic signals when they are reached instead of the expected delays.
+    ///
+    /// Example
+    /// ```rust
+    /// let route = Route::from_edges_departing(&graph, start, vec![3, 7], Some(30.0));
+    /// ```
+    pub fn from_edges_departing(
+        graph: &Graph,
+        from: usize,
+        edges: Vec<usize>,
+        depart: Option<f64>,
+    ) -> Route {
+        let mut route = Route {
+            nodes: vec![from],
+            ..Route::default()
+        };
+
+        for (i, &edge_id) in edges.iter().enumerate() {
+            let edge = &graph.edges[edge_id];
+            route.nodes.push(edge.to);
+            route.roads.push(edge.road);
+            route.distance += edge.length;
+
+            if i > 0 {
+                let turn = match depart {
+                    Some(depart) => {
+                        graph.turn_cost_at(edges[i - 1], edge_id, Metric::Time, depart + route.time)
+                    }
+                    None => graph.turn_cost(edges[i - 1], edge_id, Metric::Time),
+                };
+                route.time += turn.unwrap_or(0.0);
+            }
+
+            route.time += edge.travel_time();
+        }
+
+        route.edges = edges;
+        route
+    }
+
+    /// Builds a route from the edges it traverses, departing at `depart` seconds since 1970-01-01 00:00. The time
+    /// follows the speed factors of the roads and the actual waits at traffic signals, and is infinite if the route
+    /// reaches a road while it is closed.
+    ///
+    /// Example
+    /// ```rust
+    /// let route = Route::from_edges_at(&graph, start, vec![3, 7], parse_datetime("2026-10-19 08:15")?);
+    /// ```
+    pub fn from_edges_at(graph: &Graph, from: usize, edges: Vec<usize>, depart: f64) -> Route {
+        let mut route = Route {
+            nodes: vec![from],
+            ..Route::default()
+        };
+
+        for (i, &edge_id) in edges.iter().enumerate() {
+            let edge = &graph.edges[edge_id];
+            route.nodes.push(edge.to);
+            route.roads.push(edge.road);
+            route.distance += edge.length;
+
+            if i > 0 {
+                route.time += graph
+                    .turn_cost_at(edges[i - 1], edge_id, Metric::Time, depart + route.time)
+                    .unwrap_or(0.0);
+            }
+
+            route.time += graph
+                .travel_time_at(edge_id, depart + route.time)
+                .unwrap_or(f64::INFINITY);
+        }
+
+        route.edges = edges;
+        route
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RoutingError {
+    /// The intersection does not exist
+    UnknownIntersection(i32),
+    /// There is no path between the two intersections
+    NoRoute { from: i32, to: i32 },
+}
+
+impl std::fmt::Display for RoutingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RoutingError::UnknownIntersection(id) => write!(f, "unknown intersection {}", id),
+            RoutingError::NoRoute { from, to } => {
+                write!(f, "no route from intersection {} to {}", from, to)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RoutingError {}
+
+/// Finds the best route between two intersections
+///
+/// Example
+/// ```rust
+/// let route = route(&graph, 1, 3, Metric::Time)?;
+/// println!("{:?} takes {}s", route.roads, route.time);
+/// ```
+pub fn route(graph: &Graph, from: i32, to: i32, metric: Metric) -> Result<Route, RoutingError> {
+    let start = graph
+        .intersection_node(from)
+        .ok_or(RoutingError::UnknownIntersection(from))?;
+    let end = graph
+        .intersection_node(to)
+        .ok_or(RoutingError::UnknownIntersection(to))?;
+
+    astar::astar(graph, start, end, metric).ok_or(RoutingError::NoRoute { from, to })
+}
@@ -0,0 +1,173 @@
+use crate::managers::road::Road;
+
+/// The roads a route option applies to
+#[derive(Debug, Clone, PartialEq)]
+pub enum RoadFilter {
+    /// Roads of a `road_type`
+    RoadType(String),
+    /// Roads with a `lane_count` below this
+    LanesBelow(f64),
+    /// Roads with one of these ids
+    Roads(Vec<i32>),
+    /// Roads with this `name`, ignoring case
+    Name(String),
+}
+
+impl RoadFilter {
+    /// Returns whether the filter applies to a road
+    pub fn matches(&self, road: &Road) -> bool {
+        match self {
+            RoadFilter::RoadType(road_type) => road.road_type == *road_type,
+            RoadFilter::LanesBelow(lanes) => road.lane_count < *lanes,
+            RoadFilter::Roads(ids) => road._id.is_some_and(|id| ids.contains(&id)),
+            RoadFilter::Name(name) => road.name.trim().eq_ignore_ascii_case(name.trim()),
+        }
+    }
+}
+
+impl std::str::FromStr for RoadFilter {
+    type Err = String;
+
+    /// Parses `type=<road type>`, `lanes<<count>`, `road=<id>,<id>,...` or `name=<road name>`
+    ///
+    /// Example
+    /// ```rust
+    /// let filter: RoadFilter = "lanes<2".parse()?; // RoadFilter::LanesBelow(2.0)
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(lanes) = s.strip_prefix("lanes<") {
+            return lanes
+                .trim()
+                .parse()
+                .map(RoadFilter::LanesBelow)
+                .map_err(|_| format!("Invalid lane count in `{}`", s));
+        }
+
+        match s.split_once('=') {
+            Some(("type", road_type)) => Ok(RoadFilter::RoadType(road_type.trim().to_string())),
+            Some(("name", name)) => Ok(RoadFilter::Name(name.trim().to_string())),
+            Some(("road", ids)) => ids
+                .split(',')
+                .map(|id| id.trim().parse())
+                .collect::<Result<Vec<i32>, _>>()
+                .map(RoadFilter::Roads)
+                .map_err(|_| format!("Invalid road id in `{}`", s)),
+            _ => Err(format!(
+                "Unknown road filter `{}`, expected `type=`, `lanes<`, `road=` or `name=`",
+                s
+            )),
+        }
+    }
+}
+
+/// Multiplies the cost of the roads a filter applies to by `factor`: above 1 to avoid them where there is another
+/// way, below 1 to prefer them, and infinite to never use them
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub filter: RoadFilter,
+    pub factor: f64,
+}
+
+impl Rule {
+    /// Parses a filter as understood by `RoadFilter`, optionally followed by `*<factor>`. Without a factor the rule
+    /// gets `default_factor`.
+    ///
+    /// Example
+    /// ```rust
+    /// let rule = Rule::parse("type=gravel*3", f64::INFINITY)?; // gravel roads cost three times as much
+    /// let rule = Rule::parse("type=dirt", f64::INFINITY)?; // dirt roads are never used
+    /// ```
+    pub fn parse(text: &str, default_factor: f64) -> Result<Rule, String> {
+        let (filter, factor) = match text.rsplit_once('*') {
+            Some((filter, factor)) => (
+                filter,
+                factor
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("Invalid factor in `{}`", text))?,
+            ),
+            None => (text, default_factor),
+        };
+
+        if factor.is_nan() || factor <= 0.0 {
+            return Err(format!("The factor in `{}` must be above 0", text));
+        }
+
+        Ok(Rule {
+            filter: filter.parse()?,
+            factor,
+        })
+    }
+}
+
+/// Constraints on the roads a route may take, applied to a graph with `Graph::apply_options` to explore what-if
+/// routes without editing the network
+///
+/// Example
+/// ```rust
+/// let options = RouteOptions::default()
+///     .avoid(RoadFilter::RoadType("dirt".to_string()))
+///     .avoid(RoadFilter::LanesBelow(2.0))
+///     .prefer(RoadFilter::Name("Main St".to_string()), 0.8);
+/// graph.apply_options(road_manager.cache.as_deref().unwrap_or_default(), &options);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RouteOptions {
+    pub rules: Vec<Rule>,
+}
+
+impl RouteOptions {
+    /// Builds options from rules as understood by `Rule::parse`. Roads in `avoid` are never used and roads in
+    /// `prefer` get `prefer_factor`, unless a rule gives its own factor.
+    ///
+    /// Example
+    /// ```rust
+    /// // avoid gravel where it saves less than doubling the cost, and roads with fewer than two lanes entirely
+    /// let options = RouteOptions::from_rules(&["type=gravel*2".into(), "lanes<2".into()], &[], 0.8)?;
+    /// ```
+    pub fn from_rules(
+        avoid: &[String],
+        prefer: &[String],
+        prefer_factor: f64,
+    ) -> Result<RouteOptions, String> {
+        let avoided = avoid.iter().map(|rule| Rule::parse(rule, f64::INFINITY));
+        let preferred = prefer.iter().map(|rule| Rule::parse(rule, prefer_factor));
+
+        Ok(RouteOptions {
+            rules: avoided
+                .chain(preferred)
+                .collect::<Result<Vec<Rule>, String>>()?,
+        })
+    }
+
+    /// Never uses the roads the filter applies to
+    pub fn avoid(self, filter: RoadFilter) -> RouteOptions {
+        self.weigh(filter, f64::INFINITY)
+    }
+
+    /// Makes the roads the filter applies to cheaper by `factor`, e.g. 0.8 to take them if a route over them costs
+    /// at most a quarter more
+    pub fn prefer(self, filter: RoadFilter, factor: f64) -> RouteOptions {
+        self.weigh(filter, factor)
+    }
+
+    /// Multiplies the cost of the roads the filter applies to by `factor`
+    pub fn weigh(mut self, filter: RoadFilter, factor: f64) -> RouteOptions {
+        self.rules.push(Rule { filter, factor });
+        self
+    }
+
+    /// Returns the factor the cost of a road is multiplied by: the product of the factors of all rules that apply to
+    /// it, infinite for roads that may not be used
+    pub fn factor(&self, road: &Road) -> f64 {
+        self.rules
+            .iter()
+            .filter(|rule| rule.filter.matches(road))
+            .map(|rule| rule.factor)
+            .product()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
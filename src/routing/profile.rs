@@ -0,0 +1,104 @@
+use std::collections::BTreeMap;
+
+/// Names of the profiles that are always available, in the order the window cycles through them
+pub const BUILTIN_PROFILES: [&str; 3] = ["car", "bicycle", "pedestrian"];
+
+/// How a kind of traveller uses the network: which roads it may take and how fast, whether it has to follow one-way
+/// roads and how long it is held up at intersections. Profiles from the `[profiles]` table of the config start out as
+/// the car profile, so only the settings that differ need to be given.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    /// Speed in km/h on each road type the profile may use. Roads of other types are left out of the graph.
+    pub speeds: BTreeMap<String, f64>,
+    /// Whether the speeds are capped at the speed limit of the road
+    pub speed_limit: bool,
+    /// Whether one-way roads may only be used in their direction of travel
+    pub oneway: bool,
+    /// Factor the expected wait at stop signs, yield signs, signals and roundabouts is multiplied by
+    pub delay_factor: f64,
+    /// Seconds added for passing through any intersection, e.g. to look out for traffic before crossing
+    pub intersection_penalty: f64,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Profile::car()
+    }
+}
+
+impl Profile {
+    /// Driving at the speed limit, slower on unpaved roads
+    pub fn car() -> Profile {
+        Profile {
+            speeds: speeds(&[("asphalt", 130.0), ("gravel", 60.0), ("dirt", 30.0)]),
+            speed_limit: true,
+            oneway: true,
+            delay_factor: 1.0,
+            intersection_penalty: 0.0,
+        }
+    }
+
+    /// Cycling, which follows one-way roads and waits at intersections like a car does
+    pub fn bicycle() -> Profile {
+        Profile {
+            speeds: speeds(&[("asphalt", 18.0), ("gravel", 14.0), ("dirt", 10.0)]),
+            speed_limit: true,
+            oneway: true,
+            delay_factor: 1.0,
+            intersection_penalty: 2.0,
+        }
+    }
+
+    /// Walking, in both directions of one-way roads. Stop and yield signs do not hold pedestrians up, but crossing
+    /// a road takes a moment.
+    pub fn pedestrian() -> Profile {
+        Profile {
+            speeds: speeds(&[("asphalt", 5.0), ("gravel", 5.0), ("dirt", 4.5)]),
+            speed_limit: false,
+            oneway: false,
+            delay_factor: 0.5,
+            intersection_penalty: 5.0,
+        }
+    }
+
+    /// Returns the built-in profile with the given name
+    ///
+    /// Example
+    /// ```rust
+    /// let walking = Profile::builtin("pedestrian").unwrap();
+    /// ```
+    pub fn builtin(name: &str) -> Option<Profile> {
+        match name {
+            "car" => Some(Profile::car()),
+            "bicycle" => Some(Profile::bicycle()),
+            "pedestrian" => Some(Profile::pedestrian()),
+            _ => None,
+        }
+    }
+
+    /// Returns the speed in km/h the profile travels a road at, or `None` if the road may not be used
+    ///
+    /// Example
+    /// ```rust
+    /// let speed = Profile::car().speed("gravel", 80.0); // Some(60.0)
+    /// ```
+    pub fn speed(&self, road_type: &str, speed_limit: f64) -> Option<f64> {
+        let speed = *self.speeds.get(road_type)?;
+        let speed = if self.speed_limit {
+            speed.min(speed_limit)
+        } else {
+            speed
+        };
+
+        Some(speed).filter(|speed| *speed > 0.0)
+    }
+}
+
+/// @internal
+fn speeds(entries: &[(&str, f64)]) -> BTreeMap<String, f64> {
+    entries
+        .iter()
+        .map(|&(road_type, speed)| (road_type.to_string(), speed))
+        .collect()
+}
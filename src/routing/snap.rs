@@ -0,0 +1,101 @@
+use crate::managers::road::Road;
+use crate::routing::astar::astar;
+use crate::routing::graph::Graph;
+use crate::routing::{Metric, Route};
+
+/// A point on a road: the road and how far along it the point is
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct Position {
+    pub road: i32,
+    /// How far along the road the point is, from 0 at its start point to 1 at its stop point
+    pub offset: f64,
+    /// The point on the road
+    pub lat: f64,
+    pub lon: f64,
+    /// Straight-line distance from the point that was snapped to the road, 0 for positions made with `on_road`
+    pub distance: f64,
+}
+
+impl Position {
+    /// Returns the position a fraction along a road, `None` for roads without an id. The offset is clamped to the
+    /// road.
+    ///
+    /// Example
+    /// ```rust
+    /// let position = Position::on_road(&road, 0.25); // a quarter of the way from the start point
+    /// ```
+    pub fn on_road(road: &Road, offset: f64) -> Option<Position> {
+        let offset = offset.clamp(0.0, 1.0);
+
+        Some(Position {
+            road: road._id?,
+            offset,
+            lat: road.start_lat + (road.stop_lat - road.start_lat) * offset,
+            lon: road.start_lon + (road.stop_lon - road.start_lon) * offset,
+            distance: 0.0,
+        })
+    }
+}
+
+/// Finds the position on the roads closest to a point, if it is within `radius` of it. Roads at the same distance
+/// go to the one listed first.
+///
+/// Example
+/// ```rust
+/// let position = snap(road_manager.cache.as_deref().unwrap_or_default(), mouse_pos[0], mouse_pos[1], 5.0);
+/// ```
+pub fn snap(roads: &[Road], lat: f64, lon: f64, radius: f64) -> Option<Position> {
+    roads
+        .iter()
+        .filter_map(|road| {
+            let [start_lat, start_lon, stop_lat, stop_lon] = road.get_points();
+            let offset = project([start_lat, start_lon], [stop_lat, stop_lon], [lat, lon]);
+            let mut position = Position::on_road(road, offset)?;
+            position.distance =
+                ((position.lat - lat).powi(2) + (position.lon - lon).powi(2)).sqrt();
+            Some(position)
+        })
+        .filter(|position| position.distance <= radius)
+        .min_by(|a, b| a.distance.total_cmp(&b.distance))
+}
+
+/// Finds the best route between two positions, starting and ending partway along their roads. Both positions are
+/// inserted into the graph with `Graph::insert_position`, so the edges of the route and its distance and time only
+/// count the parts of the first and last road that are driven. Positions within `snap_radius` of a node start or end
+/// there.
+///
+/// Example
+/// ```rust
+/// let from = snap(roads, 120.0, 80.0, 5.0).unwrap();
+/// let to = snap(roads, 410.0, 230.0, 5.0).unwrap();
+/// let route = route_between(&mut graph, &from, &to, Metric::Time, 6.0);
+/// ```
+pub fn route_between(
+    graph: &mut Graph,
+    from: &Position,
+    to: &Position,
+    metric: Metric,
+    snap_radius: f64,
+) -> Option<Route> {
+    let start = graph.insert_position(from, snap_radius)?;
+    let end = graph.insert_position(to, snap_radius)?;
+
+    astar(graph, start, end, metric)
+}
+
+/// Returns how far along the segment from `a` to `b` the point closest to `point` is, from 0 to 1
+///
+/// Example
+/// ```rust
+/// let fraction = project([0.0, 0.0], [100.0, 0.0], [25.0, 40.0]); // 0.25
+/// ```
+pub fn project(a: [f64; 2], b: [f64; 2], point: [f64; 2]) -> f64 {
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    let squared = dx * dx + dy * dy;
+
+    if squared <= 0.0 {
+        return 0.0;
+    }
+
+    (((point[0] - a[0]) * dx + (point[1] - a[1]) * dy) / squared).clamp(0.0, 1.0)
+}
@@ -0,0 +1,222 @@
+//! Generated networks for the tests of the searches
+
+use crate::helpers::rng::Rng;
+use crate::managers::intersection::{Control, Intersection, RestrictionKind, TurnRestriction};
+use crate::managers::road::{Direction, Road};
+use crate::routing::graph::Graph;
+use crate::routing::options::{RoadFilter, RouteOptions};
+use crate::routing::{Metric, Route};
+
+pub const SPACING: f64 = 100.0;
+
+/// Generates a grid of `side` by `side` intersections connected by roads to their right and lower neighbours, like
+/// the routing bench. Every third row and column is a faster arterial, a few streets are one-way and the
+/// intersections have every kind of control. A road far off the grid is only connected to itself, so its ends cannot
+/// be reached from the grid.
+pub fn grid(rng: &mut Rng, side: usize) -> (Vec<Road>, Vec<Intersection>) {
+    let mut intersections = Vec::new();
+    let mut roads = Vec::new();
+
+    for x in 0..side {
+        for y in 0..side {
+            intersections.push(Intersection {
+                _id: Some((x * side + y) as i32 + 1),
+                lat: x as f64 * SPACING,
+                lon: y as f64 * SPACING,
+                control: Control::ALL[rng.below(Control::ALL.len())],
+                signal_offset: 0.0,
+            });
+        }
+    }
+
+    for x in 0..side {
+        for y in 0..side {
+            let neighbours = [(x + 1, y, y % 3 == 0), (x, y + 1, x % 3 == 0)];
+
+            for (to_x, to_y, arterial) in neighbours {
+                if to_x == side || to_y == side {
+                    continue;
+                }
+
+                let direction = match (arterial, rng.below(6)) {
+                    (false, 0) => Direction::Forward,
+                    (false, 1) => Direction::Backward,
+                    _ => Direction::Both,
+                };
+
+                roads.push(road(
+                    roads.len() as i32 + 1,
+                    [x as f64 * SPACING, y as f64 * SPACING],
+                    [to_x as f64 * SPACING, to_y as f64 * SPACING],
+                    if arterial { 70.0 } else { 30.0 },
+                    direction,
+                ));
+            }
+        }
+    }
+
+    let far = (side + 5) as f64 * SPACING;
+    roads.push(road(
+        roads.len() as i32 + 1,
+        [far, far],
+        [far + SPACING, far],
+        50.0,
+        Direction::Both,
+    ));
+
+    (roads, intersections)
+}
+
+/// Builds a grid with one-way roads, turn delays at its intersections and forbidden and mandatory turns. Returns the
+/// roads, the graph and a node that cannot be reached from the grid.
+pub fn network(rng: &mut Rng) -> (Vec<Road>, Graph, usize) {
+    let side = 8;
+    let (roads, intersections) = grid(rng, side);
+    let mut graph = Graph::build(&roads, &intersections, 6.0);
+    let restrictions = restrictions(rng, &graph, 24);
+    graph.set_turn_restrictions(&restrictions);
+
+    let far = (side + 5) as f64 * SPACING;
+    let unreachable = graph.nearest_node(far, far, 1.0).unwrap();
+
+    (roads, graph, unreachable)
+}
+
+/// Random pairs of nodes, followed by a query from a node to itself and queries to and from a node that cannot be
+/// reached
+pub fn queries(
+    rng: &mut Rng,
+    graph: &Graph,
+    unreachable: usize,
+    count: usize,
+) -> Vec<(usize, usize)> {
+    let mut queries: Vec<(usize, usize)> = (0..count)
+        .map(|_| (rng.below(graph.nodes.len()), rng.below(graph.nodes.len())))
+        .collect();
+    queries.extend([(5, 5), (0, unreachable), (unreachable, 0)]);
+
+    queries
+}
+
+/// Route options to search with: none, preferring some roads, and preferring some while avoiding others
+pub fn options() -> Vec<RouteOptions> {
+    vec![
+        RouteOptions::default(),
+        RouteOptions::default().prefer(RoadFilter::RoadType("residential".to_string()), 0.5),
+        RouteOptions::default()
+            .prefer(RoadFilter::RoadType("primary".to_string()), 0.6)
+            .weigh(RoadFilter::Roads(vec![3, 17, 40]), 4.0)
+            .avoid(RoadFilter::Roads(vec![8, 25])),
+    ]
+}
+
+/// A road between two points. Roads at 50 km/h or more are primary roads with two lanes.
+pub fn road(
+    id: i32,
+    start: [f64; 2],
+    stop: [f64; 2],
+    speed_limit: f64,
+    direction: Direction,
+) -> Road {
+    let primary = speed_limit >= 50.0;
+
+    Road {
+        _id: Some(id),
+        name: format!("Road {}", id),
+        start_lat: start[0],
+        start_lon: start[1],
+        stop_lat: stop[0],
+        stop_lon: stop[1],
+        speed_limit,
+        lane_count: if primary { 2.0 } else { 1.0 },
+        road_type: if primary { "primary" } else { "residential" }.to_string(),
+        direction,
+        lanes_forward: None,
+        lanes_backward: None,
+    }
+}
+
+/// Picks `count` turns at intersections of the graph and restricts them, alternating between forbidden and mandatory
+/// turns. Some of the forbidden turns are U-turns.
+pub fn restrictions(rng: &mut Rng, graph: &Graph, count: usize) -> Vec<TurnRestriction> {
+    let mut restrictions = Vec::new();
+
+    while restrictions.len() < count {
+        let node = rng.below(graph.nodes.len());
+        let (incoming, outgoing) = (graph.incoming(node), graph.outgoing(node));
+
+        let intersection = match graph.nodes[node].intersection {
+            Some(intersection) if !incoming.is_empty() && !outgoing.is_empty() => intersection,
+            _ => continue,
+        };
+
+        let kind = if restrictions.len() % 2 == 0 {
+            RestrictionKind::Forbidden
+        } else {
+            RestrictionKind::Mandatory
+        };
+        let from_road = graph.edges[incoming[rng.below(incoming.len())]].road;
+        let to_road = graph.edges[outgoing[rng.below(outgoing.len())]].road;
+
+        restrictions.push(TurnRestriction {
+            intersection,
+            from_road,
+            to_road,
+            kind,
+        });
+    }
+
+    restrictions
+}
+
+/// Returns what a route costs in the graph: the weighted cost of its edges and of the turns between them, infinite if
+/// it takes a turn that is not allowed
+pub fn cost(graph: &Graph, route: &Route, metric: Metric) -> f64 {
+    let edges = route
+        .edges
+        .iter()
+        .map(|&edge_id| graph.edges[edge_id].cost(metric));
+    let turns = route.edges.windows(2).map(|pair| {
+        graph
+            .turn_cost(pair[0], pair[1], metric)
+            .unwrap_or(f64::INFINITY)
+    });
+
+    edges.chain(turns).sum()
+}
+
+/// Asserts that two searches found routes of the same cost, or both found none. `search` names the second one in
+/// the failure message.
+pub fn assert_same_cost(
+    search: &str,
+    graph: &Graph,
+    expected: Option<Route>,
+    found: Option<Route>,
+    metric: Metric,
+    query: (usize, usize),
+) {
+    match (expected, found) {
+        (Some(expected), Some(found)) => {
+            let (expected, found) = (cost(graph, &expected, metric), cost(graph, &found, metric));
+            assert!(
+                (expected - found).abs() < 1e-6,
+                "{} {:?} from {} to {}: expected {}, found {}",
+                search,
+                metric,
+                query.0,
+                query.1,
+                expected,
+                found
+            );
+        }
+        (expected, found) => assert_eq!(
+            expected.is_some(),
+            found.is_some(),
+            "{} {:?} from {} to {}: only one of the searches found a route",
+            search,
+            metric,
+            query.0,
+            query.1
+        ),
+    }
+}
@@ -0,0 +1,261 @@
+use std::io::Write;
+
+use crate::routing::astar::astar;
+use crate::routing::graph::Graph;
+use crate::routing::matrix::matrix;
+use crate::routing::{Metric, Route, RoutingError};
+
+/// The part of a tour between two consecutive stops
+#[derive(Debug, Clone)]
+pub struct Leg {
+    pub from: i32,
+    pub to: i32,
+    pub route: Route,
+}
+
+/// A route through several intersections in order
+#[derive(Debug, Clone)]
+pub struct Tour {
+    /// The intersections visited, in visiting order
+    pub stops: Vec<i32>,
+    pub legs: Vec<Leg>,
+    /// The ids of the roads traversed by all legs, in driving order
+    pub roads: Vec<i32>,
+    /// Total length in meters
+    pub distance: f64,
+    /// Total travel time in seconds, without any time spent at the stops
+    pub time: f64,
+}
+
+/// One leg of a tour as written to csv
+///
+/// @internal
+#[derive(serde::Serialize)]
+struct LegRow {
+    from: i32,
+    to: i32,
+    distance: f64,
+    time: f64,
+    /// Ids of the roads driven, separated by spaces
+    roads: String,
+}
+
+impl Tour {
+    /// Writes the legs as csv with one `from,to,distance,time,roads` row per leg, the road ids separated by spaces
+    ///
+    /// Example
+    /// ```rust
+    /// tour.write_csv(std::io::stdout())?;
+    /// ```
+    pub fn write_csv<W: Write>(&self, writer: W) -> csv::Result<()> {
+        let mut wtr = csv::Writer::from_writer(writer);
+
+        for leg in &self.legs {
+            wtr.serialize(LegRow {
+                from: leg.from,
+                to: leg.to,
+                distance: leg.route.distance,
+                time: leg.route.time,
+                roads: leg
+                    .route
+                    .roads
+                    .iter()
+                    .map(|road| road.to_string())
+                    .collect::<Vec<String>>()
+                    .join(" "),
+            })?;
+        }
+
+        wtr.flush()?;
+        Ok(())
+    }
+
+    /// Writes the tour as a JSON object with the `stops`, the combined `roads`, `distance` and `time`, and the
+    /// `legs` with their own `from`, `to`, `roads`, `distance` and `time`
+    ///
+    /// Example
+    /// ```rust
+    /// tour.write_json(File::create("tour.json")?)?;
+    /// ```
+    pub fn write_json<W: Write>(&self, writer: W) -> serde_json::Result<()> {
+        let legs: Vec<serde_json::Value> = self
+            .legs
+            .iter()
+            .map(|leg| {
+                serde_json::json!({
+                    "from": leg.from,
+                    "to": leg.to,
+                    "roads": leg.route.roads,
+                    "distance": leg.route.distance,
+                    "time": leg.route.time,
+                })
+            })
+            .collect();
+
+        serde_json::to_writer(
+            writer,
+            &serde_json::json!({
+                "stops": self.stops,
+                "roads": self.roads,
+                "distance": self.distance,
+                "time": self.time,
+                "legs": legs,
+            }),
+        )
+    }
+}
+
+/// Finds the best route through intersections in the given order, from the first to the last
+///
+/// Example
+/// ```rust
+/// let tour = route_via(&graph, &[1, 4, 2], Metric::Time)?; // from 1 to 2 by way of 4
+/// println!("{} m over roads {:?}", tour.distance, tour.roads);
+/// ```
+pub fn route_via(graph: &Graph, stops: &[i32], metric: Metric) -> Result<Tour, RoutingError> {
+    let nodes = stops
+        .iter()
+        .map(|&stop| {
+            graph
+                .intersection_node(stop)
+                .ok_or(RoutingError::UnknownIntersection(stop))
+        })
+        .collect::<Result<Vec<usize>, RoutingError>>()?;
+
+    let mut tour = Tour {
+        stops: stops.to_vec(),
+        legs: Vec::new(),
+        roads: Vec::new(),
+        distance: 0.0,
+        time: 0.0,
+    };
+
+    for i in 1..stops.len() {
+        let (from, to) = (stops[i - 1], stops[i]);
+        let route = astar(graph, nodes[i - 1], nodes[i], metric)
+            .ok_or(RoutingError::NoRoute { from, to })?;
+
+        tour.roads.extend(&route.roads);
+        tour.distance += route.distance;
+        tour.time += route.time;
+        tour.legs.push(Leg { from, to, route });
+    }
+
+    Ok(tour)
+}
+
+/// Finds a short route from `start` through every one of `stops` in any order, ending at `end` if given and at the
+/// last stop visited otherwise. The order is built by always driving to the nearest stop not visited yet, then
+/// improved by reversing parts of it (2-opt) for as long as that makes the tour cheaper. This is a heuristic, so the
+/// order is good but not always the best possible.
+///
+/// Example
+/// ```rust
+/// let tour = optimize_order(&graph, 1, &[7, 3, 9], Some(1), Metric::Time)?; // a round trip from 1
+/// println!("visiting {:?}", tour.stops);
+/// ```
+pub fn optimize_order(
+    graph: &Graph,
+    start: i32,
+    stops: &[i32],
+    end: Option<i32>,
+    metric: Metric,
+) -> Result<Tour, RoutingError> {
+    let mut ids = vec![start];
+    ids.extend(stops);
+    ids.extend(end);
+
+    let matrix = matrix(graph, &ids, metric)?;
+    let costs = match metric {
+        Metric::Distance => &matrix.distances,
+        Metric::Time => &matrix.times,
+    };
+    let cost = |from: usize, to: usize| costs[from][to].unwrap_or(f64::INFINITY);
+
+    // indices into `ids`, the start and end stay in place
+    let mut order = vec![0];
+    let mut unvisited: Vec<usize> = (1..=stops.len()).collect();
+
+    while !unvisited.is_empty() {
+        let last = *order.last().unwrap();
+        let nearest = (0..unvisited.len())
+            .min_by(|&a, &b| cost(last, unvisited[a]).total_cmp(&cost(last, unvisited[b])))
+            .unwrap();
+        order.push(unvisited.swap_remove(nearest));
+    }
+
+    if end.is_some() {
+        order.push(ids.len() - 1);
+    }
+
+    // costs can differ per direction because of one-way roads, so every reversal is priced in full
+    let total =
+        |order: &[usize]| -> f64 { order.windows(2).map(|pair| cost(pair[0], pair[1])).sum() };
+    let last_movable = if end.is_some() {
+        order.len() - 2
+    } else {
+        order.len() - 1
+    };
+    let mut best = total(&order);
+    let mut improved = true;
+
+    while improved {
+        improved = false;
+
+        for i in 1..=last_movable {
+            for j in i + 1..=last_movable {
+                order[i..=j].reverse();
+                let candidate = total(&order);
+
+                if candidate < best - 1e-9 {
+                    best = candidate;
+                    improved = true;
+                } else {
+                    order[i..=j].reverse();
+                }
+            }
+        }
+    }
+
+    let visiting: Vec<i32> = order.iter().map(|&i| ids[i]).collect();
+    route_via(graph, &visiting, metric)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::rng::Rng;
+    use crate::routing::testing::grid;
+
+    #[test]
+    fn optimized_order_is_no_worse_and_ends_at_end() {
+        let (roads, intersections) = grid(&mut Rng::new(0x9e37_79b9_7f4a_7c15), 6);
+        let graph = Graph::build(&roads, &intersections, 6.0);
+
+        // zigzags between opposite corners of the grid
+        let (start, stops, end) = (1, [36, 2, 35, 7, 30, 6], 31);
+
+        for metric in [Metric::Distance, Metric::Time] {
+            let mut given = vec![start];
+            given.extend(stops);
+            given.push(end);
+            let given = route_via(&graph, &given, metric).unwrap();
+
+            let optimized = optimize_order(&graph, start, &stops, Some(end), metric).unwrap();
+            let cost = |tour: &Tour| match metric {
+                Metric::Distance => tour.distance,
+                Metric::Time => tour.time,
+            };
+
+            assert!(cost(&optimized) <= cost(&given) + 1e-6, "{:?}", metric);
+            assert_eq!(optimized.stops.first(), Some(&start));
+            assert_eq!(optimized.stops.last(), Some(&end));
+
+            let mut visited = optimized.stops[1..optimized.stops.len() - 1].to_vec();
+            visited.sort();
+            let mut expected = stops.to_vec();
+            expected.sort();
+            assert_eq!(visited, expected);
+        }
+    }
+}
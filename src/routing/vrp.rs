@@ -0,0 +1,707 @@
+use std::io::Write;
+use std::path::Path;
+
+use crate::config::VrpConfig;
+use crate::get_logger;
+use crate::routing::graph::Graph;
+use crate::routing::matrix::matrix;
+use crate::routing::tour::{route_via, Tour};
+use crate::routing::{Metric, RoutingError};
+
+/// A delivery vehicle, one row of `vehicles.csv`
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Vehicle {
+    pub id: String,
+    /// How much the vehicle can carry, in the same unit as the demand of the stops
+    pub capacity: f64,
+}
+
+/// A delivery to make, one row of `stops.csv`. Times are in seconds after the vehicles leave the depot.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Stop {
+    pub intersection: i32,
+    pub demand: f64,
+    /// Vehicles arriving earlier wait until then, no waiting when empty
+    pub earliest: Option<f64>,
+    /// Service starting later than this counts as late, never late when empty
+    pub latest: Option<f64>,
+    /// Seconds spent at the stop
+    #[serde(default)]
+    pub service: f64,
+}
+
+/// Loads the vehicles of a fleet file
+///
+/// Example
+/// ```rust
+/// let vehicles = load_vehicles(&get_paths().data_file("vehicles.csv"))?;
+/// ```
+pub fn load_vehicles(path: &Path) -> csv::Result<Vec<Vehicle>> {
+    csv::Reader::from_path(path)?.deserialize().collect()
+}
+
+/// Loads the stops of a deliveries file
+///
+/// Example
+/// ```rust
+/// let stops = load_stops(&get_paths().data_file("stops.csv"))?;
+/// ```
+pub fn load_stops(path: &Path) -> csv::Result<Vec<Stop>> {
+    csv::Reader::from_path(path)?.deserialize().collect()
+}
+
+/// A stop as served by a vehicle
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct Visit {
+    pub intersection: i32,
+    /// Seconds after leaving the depot the vehicle arrives, starts serving and leaves again
+    pub arrival: f64,
+    pub start: f64,
+    pub departure: f64,
+    /// What the vehicle still carries after the stop
+    pub load: f64,
+    /// Seconds the service started after the latest time of the stop
+    pub lateness: f64,
+}
+
+/// The stops one vehicle serves, leaving from and returning to the depot
+#[derive(Debug, Clone)]
+pub struct VehicleTour {
+    pub vehicle: String,
+    pub visits: Vec<Visit>,
+    /// The route from the depot through the stops back to the depot
+    pub route: Tour,
+    /// What the vehicle carries when leaving the depot
+    pub load: f64,
+    /// Length of the route in meters
+    pub distance: f64,
+    /// Seconds until the vehicle is back at the depot, including waiting and service
+    pub time: f64,
+    pub lateness: f64,
+}
+
+/// The tours of a fleet and the stops it cannot serve
+#[derive(Debug, Clone)]
+pub struct Solution {
+    pub depot: i32,
+    /// One tour for every vehicle that leaves the depot
+    pub tours: Vec<VehicleTour>,
+    /// Stops that fit no vehicle, cannot be reached or would be served more than `max_lateness` late
+    pub unserved: Vec<Stop>,
+    pub distance: f64,
+    pub time: f64,
+    pub lateness: f64,
+}
+
+/// One visit of a solution as written to csv
+///
+/// @internal
+#[derive(serde::Serialize)]
+struct VisitRow<'a> {
+    vehicle: Option<&'a str>,
+    sequence: Option<usize>,
+    intersection: i32,
+    arrival: Option<f64>,
+    start: Option<f64>,
+    departure: Option<f64>,
+    load: Option<f64>,
+    lateness: Option<f64>,
+}
+
+impl Solution {
+    /// Writes the solution as csv with one `vehicle,sequence,intersection,arrival,start,departure,load,lateness` row
+    /// per visit. Every tour ends with a row for the return to the depot, and unserved stops are listed last with
+    /// only their intersection.
+    ///
+    /// Example
+    /// ```rust
+    /// solution.write_csv(std::io::stdout())?;
+    /// ```
+    pub fn write_csv<W: Write>(&self, writer: W) -> csv::Result<()> {
+        let mut wtr = csv::Writer::from_writer(writer);
+
+        for tour in &self.tours {
+            for (i, visit) in tour.visits.iter().enumerate() {
+                wtr.serialize(VisitRow {
+                    vehicle: Some(&tour.vehicle),
+                    sequence: Some(i + 1),
+                    intersection: visit.intersection,
+                    arrival: Some(visit.arrival),
+                    start: Some(visit.start),
+                    departure: Some(visit.departure),
+                    load: Some(visit.load),
+                    lateness: Some(visit.lateness),
+                })?;
+            }
+
+            wtr.serialize(VisitRow {
+                vehicle: Some(&tour.vehicle),
+                sequence: Some(tour.visits.len() + 1),
+                intersection: self.depot,
+                arrival: Some(tour.time),
+                start: None,
+                departure: None,
+                load: Some(0.0),
+                lateness: Some(0.0),
+            })?;
+        }
+
+        for stop in &self.unserved {
+            wtr.serialize(VisitRow {
+                vehicle: None,
+                sequence: None,
+                intersection: stop.intersection,
+                arrival: None,
+                start: None,
+                departure: None,
+                load: None,
+                lateness: None,
+            })?;
+        }
+
+        wtr.flush()?;
+        Ok(())
+    }
+
+    /// Writes the solution as a JSON object with the `depot`, the total `distance`, `time` and `lateness`, the
+    /// `tours` with their visits, legs and road ids, and the `unserved` stops
+    ///
+    /// Example
+    /// ```rust
+    /// solution.write_json(File::create("tours.json")?)?;
+    /// ```
+    pub fn write_json<W: Write>(&self, writer: W) -> serde_json::Result<()> {
+        let tours: Vec<serde_json::Value> = self
+            .tours
+            .iter()
+            .map(|tour| {
+                let legs: Vec<serde_json::Value> = tour
+                    .route
+                    .legs
+                    .iter()
+                    .map(|leg| {
+                        serde_json::json!({
+                            "from": leg.from,
+                            "to": leg.to,
+                            "roads": leg.route.roads,
+                            "distance": leg.route.distance,
+                            "time": leg.route.time,
+                        })
+                    })
+                    .collect();
+
+                serde_json::json!({
+                    "vehicle": tour.vehicle,
+                    "load": tour.load,
+                    "distance": tour.distance,
+                    "time": tour.time,
+                    "lateness": tour.lateness,
+                    "visits": tour.visits,
+                    "roads": tour.route.roads,
+                    "legs": legs,
+                })
+            })
+            .collect();
+
+        serde_json::to_writer(
+            writer,
+            &serde_json::json!({
+                "depot": self.depot,
+                "distance": self.distance,
+                "time": self.time,
+                "lateness": self.lateness,
+                "tours": tours,
+                "unserved": self.unserved,
+            }),
+        )
+    }
+}
+
+/// Plans the tours of a fleet that delivers to every stop from a depot, respecting the capacities of the vehicles
+/// and, as far as possible, the time windows of the stops. All vehicles leave the depot at the same time.
+///
+/// The tours are built by repeatedly inserting the stop that adds the least cost wherever it adds it, then improved
+/// by moving stops within and between tours, swapping stops between tours and reversing parts of tours for as long
+/// as that helps. The cost of a tour is the time until it is back at the depot plus `lateness_penalty` for every
+/// second a stop is served late.
+///
+/// Example
+/// ```rust
+/// let solution = solve(&graph, 1, &vehicles, &stops, &config.vrp)?;
+/// println!("{} tours, {} stops unserved", solution.tours.len(), solution.unserved.len());
+/// ```
+pub fn solve(
+    graph: &Graph,
+    depot: i32,
+    vehicles: &[Vehicle],
+    stops: &[Stop],
+    config: &VrpConfig,
+) -> Result<Solution, RoutingError> {
+    let mut ids = vec![depot];
+    ids.extend(stops.iter().map(|stop| stop.intersection));
+    let matrix = matrix(graph, &ids, Metric::Time)?;
+
+    let planner = Planner {
+        times: &matrix.times,
+        vehicles,
+        stops,
+        config,
+    };
+
+    let (routes, unrouted) = planner.plan();
+
+    let mut solution = Solution {
+        depot,
+        tours: Vec::new(),
+        unserved: unrouted.iter().map(|&stop| stops[stop]).collect(),
+        distance: 0.0,
+        time: 0.0,
+        lateness: 0.0,
+    };
+
+    for (vehicle, route) in vehicles.iter().zip(&routes) {
+        if route.is_empty() {
+            continue;
+        }
+
+        let visits = planner.schedule(vehicle, route).unwrap_or_default();
+        let mut intersections = vec![depot];
+        intersections.extend(route.iter().map(|&stop| stops[stop].intersection));
+        intersections.push(depot);
+
+        let tour = route_via(graph, &intersections, Metric::Time)?;
+        let load = route.iter().map(|&stop| stops[stop].demand).sum();
+        let time = visits.last().map_or(0.0, |visit| visit.departure)
+            + tour.legs.last().map_or(0.0, |leg| leg.route.time);
+        let lateness: f64 = visits.iter().map(|visit| visit.lateness).sum();
+
+        solution.distance += tour.distance;
+        solution.time += time;
+        solution.lateness += lateness;
+        solution.tours.push(VehicleTour {
+            vehicle: vehicle.id.clone(),
+            visits,
+            load,
+            distance: tour.distance,
+            time,
+            lateness,
+            route: tour,
+        });
+    }
+
+    if !solution.unserved.is_empty() {
+        get_logger().warn(&format!(
+            "(router) {} of {} stops cannot be served",
+            solution.unserved.len(),
+            stops.len()
+        ));
+    }
+
+    Ok(solution)
+}
+
+/// Evaluates and improves tours given as the indices of their stops, without leaving the travel time matrix
+///
+/// @internal
+struct Planner<'a> {
+    /// Travel times between the depot, at index 0, and the stops, at their index plus one
+    times: &'a [Vec<Option<f64>>],
+    vehicles: &'a [Vehicle],
+    stops: &'a [Stop],
+    config: &'a VrpConfig,
+}
+
+impl Planner<'_> {
+    /// Builds and improves the tours, returning the stops of every vehicle and the stops no vehicle can serve
+    fn plan(&self) -> (Vec<Vec<usize>>, Vec<usize>) {
+        let mut routes: Vec<Vec<usize>> = vec![Vec::new(); self.vehicles.len()];
+        let mut unrouted: Vec<usize> = (0..self.stops.len()).collect();
+        self.insert(&mut routes, &mut unrouted);
+
+        for _ in 0..self.config.iterations {
+            let improved =
+                self.relocate(&mut routes) | self.swap(&mut routes) | self.reverse(&mut routes);
+
+            // improvements can free up room for stops that did not fit before
+            let inserted = self.insert(&mut routes, &mut unrouted);
+
+            if !improved && !inserted {
+                break;
+            }
+        }
+
+        (routes, unrouted)
+    }
+
+    /// Returns the visits of a tour, or `None` if it is over capacity, cannot be driven or serves a stop more than
+    /// `max_lateness` late
+    fn schedule(&self, vehicle: &Vehicle, route: &[usize]) -> Option<Vec<Visit>> {
+        let mut load: f64 = route.iter().map(|&stop| self.stops[stop].demand).sum();
+        if load > vehicle.capacity {
+            return None;
+        }
+
+        let mut visits = Vec::with_capacity(route.len());
+        let mut position = 0;
+        let mut time = 0.0;
+
+        for &stop_id in route {
+            let stop = &self.stops[stop_id];
+            let arrival = time + self.times[position][stop_id + 1]?;
+            let start = stop.earliest.map_or(arrival, |earliest| arrival.max(earliest));
+            let lateness = stop.latest.map_or(0.0, |latest| (start - latest).max(0.0));
+
+            if lateness > self.config.max_lateness {
+                return None;
+            }
+
+            load -= stop.demand;
+            time = start + stop.service;
+            position = stop_id + 1;
+
+            visits.push(Visit {
+                intersection: stop.intersection,
+                arrival,
+                start,
+                departure: time,
+                load,
+                lateness,
+            });
+        }
+
+        // the way back has to exist too
+        self.times[position][0]?;
+
+        Some(visits)
+    }
+
+    /// The cost of a tour, infinite if it is not feasible
+    fn cost(&self, vehicle: usize, route: &[usize]) -> f64 {
+        if route.is_empty() {
+            return 0.0;
+        }
+
+        match self.schedule(&self.vehicles[vehicle], route) {
+            Some(visits) => {
+                let last = visits.last().unwrap();
+                let back = self.times[route[route.len() - 1] + 1][0].unwrap_or(f64::INFINITY);
+                let lateness: f64 = visits.iter().map(|visit| visit.lateness).sum();
+
+                last.departure + back + self.config.lateness_penalty * lateness
+            }
+            None => f64::INFINITY,
+        }
+    }
+
+    /// Inserts unrouted stops one by one where they add the least cost, until none fits anywhere. Returns whether any
+    /// stop was inserted.
+    fn insert(&self, routes: &mut [Vec<usize>], unrouted: &mut Vec<usize>) -> bool {
+        let mut costs: Vec<f64> = (0..routes.len())
+            .map(|vehicle| self.cost(vehicle, &routes[vehicle]))
+            .collect();
+        let mut inserted = false;
+
+        loop {
+            // (added cost, index into unrouted, vehicle, position)
+            let mut best: Option<(f64, usize, usize, usize)> = None;
+
+            for (i, &stop) in unrouted.iter().enumerate() {
+                for vehicle in 0..routes.len() {
+                    for position in 0..=routes[vehicle].len() {
+                        let mut candidate = routes[vehicle].clone();
+                        candidate.insert(position, stop);
+                        let added = self.cost(vehicle, &candidate) - costs[vehicle];
+
+                        if added.is_finite() && best.map_or(true, |(least, ..)| added < least) {
+                            best = Some((added, i, vehicle, position));
+                        }
+                    }
+                }
+            }
+
+            let Some((_, i, vehicle, position)) = best else {
+                return inserted;
+            };
+
+            let stop = unrouted.swap_remove(i);
+            routes[vehicle].insert(position, stop);
+            costs[vehicle] = self.cost(vehicle, &routes[vehicle]);
+            inserted = true;
+        }
+    }
+
+    /// Moves single stops to the position, in any tour, where they cost the least. Returns whether anything moved.
+    fn relocate(&self, routes: &mut [Vec<usize>]) -> bool {
+        let mut improved = false;
+
+        for from in 0..routes.len() {
+            let mut i = 0;
+
+            while i < routes[from].len() {
+                let mut shortened = routes[from].clone();
+                let stop = shortened.remove(i);
+                let saved = self.cost(from, &routes[from]) - self.cost(from, &shortened);
+
+                // (added cost, vehicle, position)
+                let mut best: Option<(f64, usize, usize)> = None;
+
+                for (to, route) in routes.iter().enumerate() {
+                    let base = if to == from { &shortened } else { route };
+                    let base_cost = self.cost(to, base);
+
+                    for position in 0..=base.len() {
+                        if to == from && position == i {
+                            continue;
+                        }
+
+                        let mut candidate = base.clone();
+                        candidate.insert(position, stop);
+                        let added = self.cost(to, &candidate) - base_cost;
+
+                        if added < saved - 1e-9 && best.map_or(true, |(least, ..)| added < least) {
+                            best = Some((added, to, position));
+                        }
+                    }
+                }
+
+                match best {
+                    Some((_, to, position)) => {
+                        routes[from] = shortened;
+                        routes[to].insert(position, stop);
+                        improved = true;
+                    }
+                    None => i += 1,
+                }
+            }
+        }
+
+        improved
+    }
+
+    /// Swaps pairs of stops between tours where that lowers the cost of both together. Returns whether any were
+    /// swapped.
+    fn swap(&self, routes: &mut [Vec<usize>]) -> bool {
+        let mut improved = false;
+
+        for a in 0..routes.len() {
+            for b in a + 1..routes.len() {
+                for i in 0..routes[a].len() {
+                    for j in 0..routes[b].len() {
+                        let before = self.cost(a, &routes[a]) + self.cost(b, &routes[b]);
+                        let (mut first, mut second) = (routes[a].clone(), routes[b].clone());
+                        std::mem::swap(&mut first[i], &mut second[j]);
+
+                        if self.cost(a, &first) + self.cost(b, &second) < before - 1e-9 {
+                            routes[a] = first;
+                            routes[b] = second;
+                            improved = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        improved
+    }
+
+    /// Reverses parts of tours (2-opt) where that lowers their cost. Returns whether any tour changed.
+    fn reverse(&self, routes: &mut [Vec<usize>]) -> bool {
+        let mut improved = false;
+
+        for (vehicle, route) in routes.iter_mut().enumerate() {
+            let mut best = self.cost(vehicle, route);
+
+            for i in 0..route.len() {
+                for j in i + 1..route.len() {
+                    route[i..=j].reverse();
+                    let candidate = self.cost(vehicle, route);
+
+                    if candidate < best - 1e-9 {
+                        best = candidate;
+                        improved = true;
+                    } else {
+                        route[i..=j].reverse();
+                    }
+                }
+            }
+        }
+
+        improved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stop(intersection: i32, demand: f64) -> Stop {
+        Stop {
+            intersection,
+            demand,
+            earliest: None,
+            latest: None,
+            service: 0.0,
+        }
+    }
+
+    fn vehicle(id: &str, capacity: f64) -> Vehicle {
+        Vehicle {
+            id: id.to_string(),
+            capacity,
+        }
+    }
+
+    /// Travel times between the depot and three stops on a line, 60 s apart: depot, 1, 2, 3
+    fn line() -> Vec<Vec<Option<f64>>> {
+        (0..4)
+            .map(|from: i32| {
+                (0..4)
+                    .map(|to: i32| Some(60.0 * (from - to).abs() as f64))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn stops_over_capacity_are_unserved() {
+        let times = line();
+        let vehicles = [vehicle("van", 10.0), vehicle("bike", 2.0)];
+        let stops = [stop(2, 6.0), stop(3, 12.0), stop(4, 4.0)];
+        let config = VrpConfig::default();
+        let planner = Planner {
+            times: &times,
+            vehicles: &vehicles,
+            stops: &stops,
+            config: &config,
+        };
+
+        let (routes, unrouted) = planner.plan();
+
+        assert_eq!(unrouted, vec![1]);
+        for (vehicle, route) in vehicles.iter().zip(&routes) {
+            let load: f64 = route.iter().map(|&stop| stops[stop].demand).sum();
+            assert!(load <= vehicle.capacity, "{} carries {}", vehicle.id, load);
+        }
+        assert_eq!(routes.iter().map(Vec::len).sum::<usize>(), 2);
+        assert_eq!(planner.schedule(&vehicles[0], &[0, 1]), None);
+    }
+
+    #[test]
+    fn early_arrivals_wait() {
+        let times = line();
+        let vehicles = [vehicle("van", 10.0)];
+        let stops = [
+            Stop {
+                earliest: Some(300.0),
+                service: 30.0,
+                ..stop(2, 1.0)
+            },
+            Stop {
+                earliest: Some(100.0),
+                ..stop(3, 1.0)
+            },
+        ];
+        let config = VrpConfig::default();
+        let planner = Planner {
+            times: &times,
+            vehicles: &vehicles,
+            stops: &stops,
+            config: &config,
+        };
+
+        let visits = planner.schedule(&vehicles[0], &[0, 1]).unwrap();
+
+        assert_eq!(
+            (visits[0].arrival, visits[0].start, visits[0].departure),
+            (60.0, 300.0, 330.0)
+        );
+        // arrives after its earliest time, so there is no waiting
+        assert_eq!((visits[1].arrival, visits[1].start), (390.0, 390.0));
+        assert_eq!(visits[0].load, 1.0);
+        assert_eq!(visits[1].load, 0.0);
+        assert!(visits.iter().all(|visit| visit.lateness == 0.0));
+    }
+
+    #[test]
+    fn lateness_above_the_maximum_is_rejected() {
+        let times = line();
+        let vehicles = [vehicle("van", 10.0)];
+        let stops = [
+            Stop {
+                latest: Some(100.0),
+                ..stop(2, 1.0)
+            },
+            Stop {
+                latest: Some(90.0),
+                ..stop(3, 1.0)
+            },
+        ];
+        let config = VrpConfig {
+            max_lateness: 60.0,
+            ..VrpConfig::default()
+        };
+        let planner = Planner {
+            times: &times,
+            vehicles: &vehicles,
+            stops: &stops,
+            config: &config,
+        };
+
+        let visits = planner.schedule(&vehicles[0], &[0, 1]).unwrap();
+        assert_eq!(visits[0].lateness, 0.0);
+        assert_eq!(visits[1].lateness, 30.0);
+        // the far stop first makes the near one 80 s late
+        assert_eq!(planner.schedule(&vehicles[0], &[1, 0]), None);
+
+        // the far stop is too late even when it is served first
+        let (routes, unrouted) = Planner {
+            config: &VrpConfig {
+                max_lateness: 20.0,
+                ..VrpConfig::default()
+            },
+            ..planner
+        }
+        .plan();
+        assert_eq!(routes, vec![vec![0]]);
+        assert_eq!(unrouted, vec![1]);
+    }
+
+    #[test]
+    fn solution_lists_the_unserved_stops() {
+        use crate::managers::intersection::{Control, Intersection};
+        use crate::managers::road::Direction;
+        use crate::routing::testing::road;
+
+        let intersections: Vec<Intersection> = (0..4)
+            .map(|index| Intersection {
+                _id: Some(index + 1),
+                lat: 0.0,
+                lon: index as f64 * 100.0,
+                control: Control::Uncontrolled,
+                signal_offset: 0.0,
+            })
+            .collect();
+        let roads: Vec<_> = (1..4)
+            .map(|id| {
+                let (from, to) = ((id - 1) as f64 * 100.0, id as f64 * 100.0);
+                road(id, [0.0, from], [0.0, to], 36.0, Direction::Both)
+            })
+            .collect();
+        let graph = Graph::build(&roads, &intersections, 6.0);
+
+        let vehicles = [vehicle("van", 10.0)];
+        let stops = [stop(2, 4.0), stop(3, 11.0), stop(4, 4.0)];
+        let solution = solve(&graph, 1, &vehicles, &stops, &VrpConfig::default()).unwrap();
+
+        assert_eq!(solution.unserved, vec![stops[1]]);
+        assert_eq!(solution.tours.len(), 1);
+        let tour = &solution.tours[0];
+        let mut served: Vec<i32> = tour.visits.iter().map(|visit| visit.intersection).collect();
+        served.sort();
+        assert_eq!(served, vec![2, 4]);
+        assert_eq!(tour.load, 8.0);
+        // 300 m out and back at 10 m/s
+        assert_eq!(tour.time, 60.0);
+        assert_eq!(tour.distance, 600.0);
+    }
+}
@@ -0,0 +1,60 @@
+/// The simulation clock. It advances in fixed steps of simulated time, independently of how often the window
+/// renders, so the simulation behaves the same at any frame rate.
+#[derive(Debug, Clone)]
+pub struct SimClock {
+    /// Simulated seconds since the start of the simulation
+    pub time: f64,
+    /// Simulated seconds per step
+    pub step: f64,
+    /// Simulated seconds per real second
+    pub speed: f64,
+    pub paused: bool,
+    accumulator: f64,
+}
+
+impl SimClock {
+    /// The most steps taken for a single `advance`, so a long frame cannot stall the window
+    const MAX_STEPS: usize = 100;
+
+    pub fn new(step: f64, speed: f64) -> SimClock {
+        SimClock {
+            time: 0.0,
+            step,
+            speed,
+            paused: false,
+            accumulator: 0.0,
+        }
+    }
+
+    /// Adds the real time elapsed since the last call and returns how many steps the simulation should take
+    ///
+    /// Example
+    /// ```rust
+    /// for _ in 0..clock.advance(update_args.dt) {
+    ///     // step the simulation by clock.step seconds
+    ///     clock.tick();
+    /// }
+    /// ```
+    pub fn advance(&mut self, real_dt: f64) -> usize {
+        if self.paused || self.step <= 0.0 {
+            return 0;
+        }
+
+        self.accumulator += real_dt * self.speed;
+
+        let steps = (self.accumulator / self.step).floor() as usize;
+        self.accumulator -= steps as f64 * self.step;
+
+        if steps > Self::MAX_STEPS {
+            self.accumulator = 0.0;
+            return Self::MAX_STEPS;
+        }
+
+        steps
+    }
+
+    /// Moves the clock forward by one step
+    pub fn tick(&mut self) {
+        self.time += self.step;
+    }
+}
@@ -0,0 +1,334 @@
+pub mod clock;
+
+use std::collections::HashMap;
+
+use crate::config::SimulationConfig;
+use crate::helpers::rng::Rng;
+use crate::managers::signal::SignalState;
+use crate::routing::astar::astar_departing;
+use crate::routing::graph::Graph;
+use crate::routing::Metric;
+
+use clock::SimClock;
+
+/// Length of a vehicle in meters
+pub const VEHICLE_LENGTH: f64 = 5.0;
+/// Distance in meters a vehicle keeps to the one in front when standing still
+pub const MIN_GAP: f64 = 2.0;
+/// Comfortable acceleration in m/s²
+pub const ACCELERATION: f64 = 2.5;
+/// Comfortable deceleration in m/s²
+pub const DECELERATION: f64 = 4.5;
+/// Distance in meters a vehicle stops before the end of its edge at a red light
+pub const STOP_LINE_MARGIN: f64 = 1.0;
+/// Width of a lane in pixels, used to draw vehicles side by side
+pub const LANE_WIDTH: f64 = 3.0;
+
+/// A vehicle driving along its route
+#[derive(Debug, Clone)]
+pub struct Vehicle {
+    pub id: usize,
+    /// The edges of the route, in driving order
+    pub edges: Vec<usize>,
+    /// Index into `edges` of the edge the vehicle is on
+    pub edge_index: usize,
+    /// Distance in meters from the start of the edge to the front of the vehicle
+    pub position: f64,
+    /// Speed in m/s
+    pub speed: f64,
+    /// Lane of the edge, 0 being the rightmost
+    pub lane: usize,
+}
+
+impl Vehicle {
+    /// Returns the id of the edge the vehicle is on
+    pub fn edge(&self) -> usize {
+        self.edges[self.edge_index]
+    }
+
+    /// Returns whether the vehicle is on the last edge of its route
+    pub fn on_last_edge(&self) -> bool {
+        self.edge_index + 1 == self.edges.len()
+    }
+}
+
+/// An agent-based traffic simulation. Vehicles spawn at random intersections, drive the fastest route to another
+/// random intersection at up to the speed limit, queue behind each other per lane and stop at red lights.
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    pub graph: Graph,
+    pub clock: SimClock,
+    pub vehicles: Vec<Vehicle>,
+    /// Number of vehicles that reached their destination
+    pub arrived: usize,
+    origins: Vec<usize>,
+    spawn_interval: f64,
+    max_vehicles: usize,
+    next_spawn: f64,
+    next_id: usize,
+    rng: Rng,
+}
+
+impl Simulation {
+    /// Creates a simulation on a graph. Vehicles travel between the nodes of intersections, or between any nodes
+    /// if there are fewer than two intersections.
+    ///
+    /// Example
+    /// ```rust
+    /// let graph = Graph::from_managers(&road_manager, &intersection_manager, config.routing.snap_radius);
+    /// let mut simulation = Simulation::new(graph, &config.simulation);
+    /// ```
+    pub fn new(graph: Graph, config: &SimulationConfig) -> Simulation {
+        let connected =
+            |node: &usize| !graph.outgoing(*node).is_empty() || !graph.incoming(*node).is_empty();
+
+        let mut origins: Vec<usize> = (0..graph.nodes.len())
+            .filter(|&node| graph.nodes[node].intersection.is_some())
+            .filter(connected)
+            .collect();
+
+        if origins.len() < 2 {
+            origins = (0..graph.nodes.len()).filter(connected).collect();
+        }
+
+        Simulation {
+            graph,
+            clock: SimClock::new(config.step, config.speed),
+            vehicles: Vec::new(),
+            arrived: 0,
+            origins,
+            spawn_interval: config.spawn_interval,
+            max_vehicles: config.max_vehicles,
+            next_spawn: 0.0,
+            next_id: 0,
+            rng: Rng::new(config.seed),
+        }
+    }
+
+    /// Advances the simulation by the real time elapsed since the last update, in as many fixed steps as the
+    /// clock asks for
+    ///
+    /// Example
+    /// ```rust
+    /// if let Some(args) = e.update_args() {
+    ///     simulation.update(args.dt);
+    /// }
+    /// ```
+    pub fn update(&mut self, real_dt: f64) {
+        for _ in 0..self.clock.advance(real_dt) {
+            self.step();
+            self.clock.tick();
+        }
+    }
+
+    /// Advances the simulation by a single step of the clock
+    pub fn step(&mut self) {
+        if self.clock.time >= self.next_spawn {
+            self.spawn();
+            self.next_spawn += self.spawn_interval;
+        }
+
+        let dt = self.clock.step;
+        let queues = self.queues();
+
+        let speeds: Vec<f64> = (0..self.vehicles.len())
+            .map(|i| self.next_speed(i, &queues, dt))
+            .collect();
+
+        let mut arrived = vec![false; self.vehicles.len()];
+
+        for (i, vehicle) in self.vehicles.iter_mut().enumerate() {
+            vehicle.speed = speeds[i];
+            vehicle.position += vehicle.speed * dt;
+
+            while vehicle.position >= self.graph.edges[vehicle.edge()].length {
+                if vehicle.on_last_edge() {
+                    arrived[i] = true;
+                    break;
+                }
+
+                vehicle.position -= self.graph.edges[vehicle.edge()].length;
+                vehicle.edge_index += 1;
+                vehicle.lane = entry_lane(&self.graph, vehicle.edge(), &queues).0;
+            }
+        }
+
+        let mut index = 0;
+        self.vehicles.retain(|_| {
+            index += 1;
+            !arrived[index - 1]
+        });
+        self.arrived += arrived.iter().filter(|&&arrived| arrived).count();
+    }
+
+    /// Returns the screen position of a vehicle, offset to the right of the road by its lane
+    pub fn vehicle_position(&self, vehicle: &Vehicle) -> [f64; 2] {
+        let edge = &self.graph.edges[vehicle.edge()];
+        let from = &self.graph.nodes[edge.from];
+        let to = &self.graph.nodes[edge.to];
+
+        let dx = to.lat - from.lat;
+        let dy = to.lon - from.lon;
+        let length = (dx.powi(2) + dy.powi(2)).sqrt();
+        if length == 0.0 {
+            return [from.lat, from.lon];
+        }
+
+        let t = if edge.length > 0.0 {
+            (vehicle.position / edge.length).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        let offset = (vehicle.lane as f64 + 0.5) * LANE_WIDTH;
+
+        [
+            from.lat + dx * t - dy / length * offset,
+            from.lon + dy * t + dx / length * offset,
+        ]
+    }
+
+    /// Sends a new vehicle from a random origin to a random destination, if there is room for it
+    ///
+    /// @internal
+    fn spawn(&mut self) {
+        if self.vehicles.len() >= self.max_vehicles || self.origins.len() < 2 {
+            return;
+        }
+
+        let from = self.origins[self.rng.below(self.origins.len())];
+        let to = self.origins[self.rng.below(self.origins.len())];
+
+        let route = match astar_departing(&self.graph, from, to, Metric::Time, self.clock.time) {
+            Some(route) if !route.edges.is_empty() => route,
+            _ => return,
+        };
+
+        let first = route.edges[0];
+        let lane = (0..lane_count(&self.graph, first)).find(|&lane| {
+            self.vehicles
+                .iter()
+                .filter(|vehicle| vehicle.edge() == first && vehicle.lane == lane)
+                .all(|vehicle| vehicle.position - VEHICLE_LENGTH >= MIN_GAP)
+        });
+
+        if let Some(lane) = lane {
+            self.vehicles.push(Vehicle {
+                id: self.next_id,
+                edges: route.edges,
+                edge_index: 0,
+                position: 0.0,
+                speed: 0.0,
+                lane,
+            });
+            self.next_id += 1;
+        }
+    }
+
+    /// Returns the vehicles on every lane, as (position, index) pairs sorted from the start of the edge
+    ///
+    /// @internal
+    fn queues(&self) -> HashMap<(usize, usize), Vec<(f64, usize)>> {
+        let mut queues: HashMap<(usize, usize), Vec<(f64, usize)>> = HashMap::new();
+
+        for (i, vehicle) in self.vehicles.iter().enumerate() {
+            queues
+                .entry((vehicle.edge(), vehicle.lane))
+                .or_default()
+                .push((vehicle.position, i));
+        }
+
+        for queue in queues.values_mut() {
+            queue.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        }
+
+        queues
+    }
+
+    /// Returns the speed of a vehicle for the next step: as fast as the speed limit and its acceleration allow,
+    /// but slow enough to stop behind the vehicle in front, at a red light or before a full road
+    ///
+    /// @internal
+    fn next_speed(
+        &self,
+        i: usize,
+        queues: &HashMap<(usize, usize), Vec<(f64, usize)>>,
+        dt: f64,
+    ) -> f64 {
+        let vehicle = &self.vehicles[i];
+        let edge = &self.graph.edges[vehicle.edge()];
+        let queue = &queues[&(vehicle.edge(), vehicle.lane)];
+
+        let place = queue.iter().position(|&(_, j)| j == i).unwrap();
+        let gap = match queue.get(place + 1) {
+            Some(&(leader, _)) => leader - VEHICLE_LENGTH - vehicle.position - MIN_GAP,
+            None if vehicle.on_last_edge() => f64::INFINITY,
+            None => {
+                let to_line = edge.length - vehicle.position;
+
+                if self.must_stop(vehicle, to_line) {
+                    to_line - STOP_LINE_MARGIN
+                } else {
+                    let next = vehicle.edges[vehicle.edge_index + 1];
+                    to_line + entry_lane(&self.graph, next, queues).1 - MIN_GAP
+                }
+            }
+        };
+
+        let gap = gap.max(0.0);
+        let limit = edge.speed_limit / 3.6;
+
+        (vehicle.speed + ACCELERATION * dt)
+            .min(limit)
+            .min((2.0 * DECELERATION * gap).sqrt())
+            .min(gap / dt)
+    }
+
+    /// Returns whether a vehicle `to_line` meters before the end of its edge has to stop for the signal there.
+    /// Vehicles stop at red, and at amber if they can still brake comfortably.
+    ///
+    /// @internal
+    fn must_stop(&self, vehicle: &Vehicle, to_line: f64) -> bool {
+        let edge = &self.graph.edges[vehicle.edge()];
+
+        let state = self
+            .graph
+            .signal_plan(edge.to)
+            .and_then(|plan| plan.state_at(edge.road, self.clock.time));
+
+        match state {
+            Some(SignalState::Red) => true,
+            Some(SignalState::Amber) => to_line > vehicle.speed.powi(2) / (2.0 * DECELERATION),
+            _ => false,
+        }
+    }
+}
+
+/// Returns the number of lanes of an edge, at least one
+///
+/// @internal
+fn lane_count(graph: &Graph, edge: usize) -> usize {
+    graph.edges[edge].lanes.round().max(1.0) as usize
+}
+
+/// Returns the lane of an edge with the most free space at its start, and that space in meters
+///
+/// @internal
+fn entry_lane(
+    graph: &Graph,
+    edge: usize,
+    queues: &HashMap<(usize, usize), Vec<(f64, usize)>>,
+) -> (usize, f64) {
+    (0..lane_count(graph, edge))
+        .map(|lane| {
+            let space = queues
+                .get(&(edge, lane))
+                .and_then(|queue| queue.first())
+                .map_or(graph.edges[edge].length, |&(position, _)| {
+                    position - VEHICLE_LENGTH
+                });
+
+            (lane, space)
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))
+        .unwrap()
+}
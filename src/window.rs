@@ -4,27 +4,37 @@ use piston::input::*;
 use piston_window::*;
 use sdl2_window::Sdl2Window as Window;
 
-use std::sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
-
-use crate::constants::colors::*;
-use crate::get_history_manager;
-use crate::managers::intersection::Intersection;
-use crate::managers::road::Road;
-
-#[derive(PartialEq)]
-enum States {
-    DrawRoad,
-    DrawIntersection,
-    Destroy,
+use std::time::{Duration, Instant};
+
+use crate::cli::Args;
+use crate::config::{Config, ConfigWatcher};
+use crate::editor::session::{self, Player, Recorder};
+use crate::editor::{load_sample_data, Editor, InputEvent, States};
+use crate::helpers::logger::{LogEntry, LogLevel};
+use crate::helpers::paths::EMBEDDED_FONT;
+use crate::managers::intersection::{Control, Intersection, RestrictionKind, TurnRestriction};
+use crate::managers::road::{Direction, Road};
+use crate::managers::signal::{SignalPlan, SignalState};
+
+const CONSOLE_FONT_SIZE: u32 = 14;
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+const TOAST_FADE: Duration = Duration::from_millis(500);
+const MAX_TOASTS: usize = 4;
+
+/// A warning or error shown on the canvas for a few seconds
+struct Toast {
+    entry: LogEntry,
+    shown_at: Instant,
 }
 
-/// initializes the window and runs the simulation
+/// initializes the window and runs the simulation. With `--record` the input is written to a session file, with
+/// `--replay` the input is read from one instead.
 ///
 /// Example
 /// ```rust
-/// init(); // initializes the window and runs the simulation
+/// init(&args); // initializes the window and runs the simulation
 /// ```
-pub fn init() {
+pub fn init(args: &Args) {
     let opengl = OpenGL::V3_2;
 
     let logger = crate::get_logger();
@@ -36,248 +46,760 @@ pub fn init() {
         .build()
         .unwrap();
 
-    let mut state_counter = 1;
-    let mut state = States::DrawRoad;
-    let mut draw_road = false;
-    let mut draw_intersection = false;
-    let mut start_point: Option<[f64; 2]> = None;
-    let mut road_to_draw: [f64; 4] = [0.0, 0.0, 0.0, 0.0];
-    let mut intersection_to_draw: [f64; 2] = [0.0, 0.0];
-    let mut latest_mouse_pos: [f64; 2] = [0.0, 0.0];
+    let mut editor = Editor::new();
+    let mut console_open = false;
+    let mut toasts: Vec<Toast> = vec![];
+    let mut last_log_id = 0;
+
+    let paths = crate::get_paths();
+
+    let mut config = crate::get_config().read().unwrap().clone();
+    let mut config_watcher = ConfigWatcher::new(&paths.config_file);
+
+    // sessions hold the config file they were recorded with, so replays behave the same when it changes later
+    let config_file = std::fs::read_to_string(&paths.config_file).unwrap_or_default();
+    let mut recorder =
+        args.record
+            .as_deref()
+            .and_then(|path| match Recorder::create(path, &config_file) {
+                Ok(recorder) => {
+                    logger.info(&format!("(session) recording to {}", path.display()));
+                    Some(recorder)
+                }
+                Err(err) => {
+                    logger.error(&format!(
+                        "(session) unable to record to {}: {}",
+                        path.display(),
+                        err
+                    ));
+                    None
+                }
+            });
 
-    let font = "assets/FiraSans-Regular.ttf";
-    let mut glyphs = GlyphCache::new(font, (), TextureSettings::new()).unwrap();
+    let mut player = args
+        .replay
+        .as_deref()
+        .and_then(|path| match session::load(path) {
+            Ok(session) => {
+                logger.info(&format!(
+                    "(session) replaying {} events from {}",
+                    session.events.len(),
+                    path.display()
+                ));
+                Some((session.config(), Player::new(session.events)))
+            }
+            Err(err) => {
+                logger.error(&format!(
+                    "(session) unable to replay {}: {}",
+                    path.display(),
+                    err
+                ));
+                None
+            }
+        });
+
+    // a replay keeps the undo history as deep as it was when the session was recorded
+    let depth = match &player {
+        Some((replay_config, _)) => replay_config.editor.history_depth,
+        None => config.editor.history_depth,
+    };
+    crate::get_history_manager()
+        .lock()
+        .unwrap()
+        .set_depth(depth);
+
+    let mut glyphs = match paths.font_file() {
+        Some(font) => GlyphCache::new(font, (), TextureSettings::new()).unwrap(),
+        None => {
+            logger.debug("(window) font not found on disk, using embedded font");
+            GlyphCache::from_bytes(EMBEDDED_FONT, (), TextureSettings::new()).unwrap()
+        }
+    };
 
     let mut gl = GlGraphics::new(opengl);
 
-    logger.trace("(roadmanager) init road manager");
-    let mut road_manager = crate::get_road_manager().write().unwrap();
-
-    logger.trace("(roadmanager) load roads");
-    road_manager.load(Some("sample/roads.csv"));
-
-    logger.trace("(intersectionmanager) init intersection manager");
-    let mut intersection_manager = crate::get_intersection_manager().lock().unwrap();
-
-    logger.trace("(intersectionmanager) load intersections");
-    intersection_manager.load(Some("sample/intersections.csv"));
+    load_sample_data();
 
     logger.info("(*) start render loop");
 
     let mut events = Events::new(EventSettings::new());
 
     while let Some(e) = events.next(&mut window) {
-        if let Some(button) = e.press_args() {
-            if button == Button::Mouse(MouseButton::Left) {
-                if state_counter >= std::usize::MAX - 10 {
-                    state_counter = 1;
+        if let Some(args) = e.update_args() {
+            if let Some(new_config) = config_watcher.poll() {
+                config = new_config;
+
+                if player.is_none() {
+                    crate::get_history_manager()
+                        .lock()
+                        .unwrap()
+                        .set_depth(config.editor.history_depth);
                 }
 
-                state_counter += 1;
+                if recorder.is_some() {
+                    logger.warn(
+                        "(session) the config changed while recording, the session replays with the config it started with",
+                    );
+                }
+            }
+
+            editor.update(args.dt);
+
+            if let Some((replay_config, replay)) = &mut player {
+                for event in replay.due(editor.time) {
+                    editor.handle(event, replay_config);
+                }
+
+                if replay.finished() {
+                    logger.info("(session) replay finished");
+                    player = None;
+                }
             }
-        };
 
-        if let Some(pos) = e.mouse_cursor_args() {
-            latest_mouse_pos = pos;
+            for entry in logger.recent(last_log_id) {
+                last_log_id = entry.id;
+
+                if entry.level >= LogLevel::Warn {
+                    toasts.push(Toast {
+                        entry,
+                        shown_at: Instant::now(),
+                    });
+                }
+            }
+
+            toasts.retain(|toast| toast.shown_at.elapsed() < TOAST_DURATION);
+            if toasts.len() > MAX_TOASTS {
+                toasts.drain(..toasts.len() - MAX_TOASTS);
+            }
         }
 
-        if let Some(button) = e.release_args() {
-            if button == Button::Mouse(MouseButton::Left) {
-                match state {
-                    States::DrawRoad => {
-                        if state_counter % 2 == 0 {
-                            draw_road = true;
-                            start_point = e.mouse_cursor_args();
-                        } else {
-                            road_manager.create(Road {
-                                _id: None,
-                                name: "test".to_string(),
-                                start_lat: road_to_draw[0],
-                                start_lon: road_to_draw[1],
-                                stop_lat: road_to_draw[2],
-                                stop_lon: road_to_draw[3],
-                                lane_count: 1.0,
-                                speed_limit: 50.0,
-                                road_type: "asphalt".to_string(),
-                            });
-
-                            draw_road = false;
-                            start_point = None;
-                        }
-                    }
-                    States::DrawIntersection => {
-                        if state_counter % 2 == 0 {
-                            draw_intersection = true;
-                        } else {
-                            intersection_manager.create(Intersection {
-                                _id: None,
-                                lat: intersection_to_draw[0],
-                                lon: intersection_to_draw[1],
-                                traffic_lights: false,
-                            });
-
-                            draw_intersection = false;
-                        }
-                    }
-                    States::Destroy => {
-                        let mut roads_to_destroy = vec![];
+        if let Some(Button::Keyboard(key)) = e.press_args() {
+            if key == config.keybindings.toggle_console {
+                console_open = !console_open;
+            }
+        }
 
-                        for road in road_manager.cache.as_ref().unwrap().iter() {
-                            let segments = road.segment(10.0);
+        // while replaying, the session drives the editor instead of the user
+        if let Some(input) = InputEvent::from_event(&e).filter(|_| player.is_none()) {
+            if let Some(session) = &mut recorder {
+                if let Err(err) = session.record(editor.time, input) {
+                    logger.error(&format!("(session) unable to record: {}", err));
+                    recorder = None;
+                }
+            }
 
-                            for segment in segments {
-                                let dx = segment.0 - latest_mouse_pos[0];
-                                let dy = segment.1 - latest_mouse_pos[1];
-                                let distance = (dx.powi(2) + dy.powi(2)).sqrt();
+            editor.handle(input, &config);
+        }
 
-                                if distance <= 5.0 {
-                                    roads_to_destroy.push(road._id.unwrap());
-                                    break;
-                                }
-                            }
-                        }
+        if let Some(r) = e.render_args() {
+            let road_manager = crate::get_road_manager().read().unwrap();
+            let intersection_manager = crate::get_intersection_manager().lock().unwrap();
+            let now = editor.now();
 
-                        for road_id in roads_to_destroy {
-                            road_manager.destroy(road_id);
-                        }
+            gl.draw(r.viewport(), |c, gl| {
+                clear(config.colors.background, gl);
+
+                if editor.road_to_draw != [0.0, 0.0, 0.0, 0.0] {
+                    line(
+                        config.colors.road(&config.editor.road_type),
+                        5.0,
+                        editor.road_to_draw,
+                        c.transform,
+                        gl,
+                    );
+                }
 
-                        let mut intersections_to_destroy = vec![];
+                if editor.intersection_to_draw != [0.0, 0.0] {
+                    draw_intersection_glyph(
+                        editor.intersection_to_draw,
+                        config.editor.intersection_control,
+                        &config,
+                        c,
+                        gl,
+                    );
+                }
 
-                        for intersection in intersection_manager.cache.as_ref().unwrap().iter() {
-                            let dx = intersection.lat - latest_mouse_pos[0];
-                            let dy = intersection.lon - latest_mouse_pos[1];
-                            let distance = (dx.powi(2) + dy.powi(2)).sqrt();
+                for road in road_manager.cache.as_ref().unwrap() {
+                    let flow = editor.heatmap.as_ref().and_then(|heatmap| {
+                        heatmap.iter().find(|flow| Some(flow.road) == road._id)
+                    });
+                    let color = match flow {
+                        Some(flow) => config.colors.volume_capacity(flow.volume_capacity),
+                        None => config.colors.road(&road.road_type),
+                    };
 
-                            if distance <= 6.0 {
-                                intersections_to_destroy.push(intersection._id.unwrap());
-                                break;
-                            }
-                        }
+                    line(color, 5.0, road.get_points(), c.transform, gl);
 
-                        for intersection_id in intersections_to_destroy {
-                            intersection_manager.destroy(intersection_id);
-                        }
+                    if road.direction != Direction::Both {
+                        draw_direction_arrows(road, config.colors.arrow, c, gl);
                     }
-                    _ => panic!("invalid state"),
                 }
-            }
-        };
 
-        if draw_road {
-            if let Some(pos) = e.mouse_cursor_args() {
-                if !start_point.is_some() {
-                    start_point = Some(pos);
+                if let Some(bands) = &editor.isochrone {
+                    // the largest band first, so the smaller ones are drawn on top of it
+                    for (index, band) in bands.iter().enumerate().rev() {
+                        let color = config.colors.isochrone_band(index, bands.len());
+
+                        for segment in &band.segments {
+                            line(
+                                color,
+                                3.0,
+                                [
+                                    segment.start[0],
+                                    segment.start[1],
+                                    segment.end[0],
+                                    segment.end[1],
+                                ],
+                                c.transform,
+                                gl,
+                            );
+                        }
+                    }
                 }
-                road_to_draw = [
-                    start_point.unwrap()[0],
-                    start_point.unwrap()[1],
-                    pos[0],
-                    pos[1],
-                ];
-            }
-        } else {
-            road_to_draw = [0.0, 0.0, 0.0, 0.0];
-        }
 
-        if draw_intersection {
-            if let Some(pos) = e.mouse_cursor_args() {
-                intersection_to_draw = pos;
-            }
-        } else {
-            intersection_to_draw = [0.0, 0.0];
-        }
+                if let Some(deliveries) = &editor.deliveries {
+                    let position = |id: i32| {
+                        intersection_manager
+                            .cache
+                            .iter()
+                            .flatten()
+                            .find(|intersection| intersection._id == Some(id))
+                            .map(|intersection| [intersection.lat, intersection.lon])
+                    };
+
+                    for (i, tour) in deliveries.tours.iter().enumerate() {
+                        let color = config.colors.delivery_tour(i);
+
+                        for road_id in &tour.route.roads {
+                            let road = road_manager
+                                .cache
+                                .iter()
+                                .flatten()
+                                .find(|road| road._id == Some(*road_id));
+
+                            if let Some(road) = road {
+                                line(color, 3.0, road.get_points(), c.transform, gl);
+                            }
+                        }
 
-        if let Some(Button::Keyboard(key)) = e.press_args() {
-            if key == Key::Space {
-                state_counter = 1;
-                draw_road = false;
-                draw_intersection = false;
-                start_point = None;
-
-                match state {
-                    States::DrawRoad => {
-                        state = States::DrawIntersection;
+                        for visit in &tour.visits {
+                            if let Some([x, y]) = position(visit.intersection) {
+                                ellipse(color, [x - 5.0, y - 5.0, 10.0, 10.0], c.transform, gl);
+                            }
+                        }
                     }
-                    States::DrawIntersection => {
-                        state = States::Destroy;
+
+                    for stop in &deliveries.unserved {
+                        if let Some([x, y]) = position(stop.intersection) {
+                            Ellipse::new_border(config.colors.unserved_stop, 2.0).draw(
+                                [x - 7.0, y - 7.0, 14.0, 14.0],
+                                &c.draw_state,
+                                c.transform,
+                                gl,
+                            );
+                        }
                     }
-                    States::Destroy => {
-                        state = States::DrawRoad;
+
+                    if let Some([x, y]) = position(deliveries.depot) {
+                        rectangle(
+                            config.colors.text,
+                            [x - 6.0, y - 6.0, 12.0, 12.0],
+                            c.transform,
+                            gl,
+                        );
                     }
                 }
-            }
 
-            if key == Key::Z {
-                println!("undo");
-                get_history_manager().lock().unwrap().undo();
-                // FIXME: UNDO DEADLOCK
-                // TODO: UNDO
-            }
-        }
+                for (i, route) in editor.alternatives.iter().enumerate() {
+                    if i == editor.selected_route {
+                        continue;
+                    }
 
-        if let Some(r) = e.render_args() {
-            gl.draw(r.viewport(), |c, gl| {
-                clear(COLOR_BACKGROUND, gl);
+                    for &edge_id in &route.edges {
+                        let edge = &editor.route_graph.edges[edge_id];
+                        let from = &editor.route_graph.nodes[edge.from];
+                        let to = &editor.route_graph.nodes[edge.to];
+
+                        line(
+                            config.colors.alternative_route(i),
+                            2.0,
+                            [from.lat, from.lon, to.lat, to.lon],
+                            c.transform,
+                            gl,
+                        );
+                    }
+                }
 
-                if road_to_draw != [0.0, 0.0, 0.0, 0.0] {
-                    line(COLOR_ASPHALT, 5.0, road_to_draw, c.transform, gl);
+                if let Some(route) = &editor.route {
+                    for &edge_id in &route.edges {
+                        let edge = &editor.route_graph.edges[edge_id];
+                        let from = &editor.route_graph.nodes[edge.from];
+                        let to = &editor.route_graph.nodes[edge.to];
+
+                        line(
+                            config.colors.route,
+                            3.0,
+                            [from.lat, from.lon, to.lat, to.lon],
+                            c.transform,
+                            gl,
+                        );
+                    }
                 }
 
-                if intersection_to_draw != [0.0, 0.0] {
-                    ellipse(
-                        COLOR_INTERSECTION,
-                        [
-                            intersection_to_draw[0] - 6.0,
-                            intersection_to_draw[1] - 6.0,
-                            12.0,
-                            12.0,
-                        ],
+                if let Some(start) = editor.route_start.filter(|_| editor.state == States::Route) {
+                    Ellipse::new_border(config.colors.route, 2.0).draw(
+                        [start.lat - 9.0, start.lon - 9.0, 18.0, 18.0],
+                        &c.draw_state,
                         c.transform,
                         gl,
                     );
                 }
 
-                for road in road_manager.cache.as_ref().unwrap() {
-                    let color;
+                let roads = road_manager.cache.as_ref().unwrap();
+                let intersections = intersection_manager.cache.as_ref().unwrap();
 
-                    match road.road_type.as_str() {
-                        "asphalt" => color = COLOR_ASPHALT,
-                        "dirt" => color = COLOR_DIRT,
-                        "gravel" => color = COLOR_GRAVEL,
-                        _ => color = COLOR_SOLID_BLACK,
+                if let Some(selected) = editor
+                    .restrict_from
+                    .filter(|_| editor.state == States::Restrict)
+                {
+                    if let Some(road) = roads.iter().find(|road| road._id == Some(selected)) {
+                        line(config.colors.route, 3.0, road.get_points(), c.transform, gl);
                     }
+                }
 
-                    line(color, 5.0, road.get_points(), c.transform, gl);
+                for restriction in &intersection_manager.restrictions {
+                    draw_turn_restriction(restriction, roads, intersections, &config, c, gl);
                 }
+
                 for intersection in intersection_manager.cache.as_ref().unwrap() {
-                    ellipse(
-                        COLOR_INTERSECTION,
-                        [intersection.lat - 6.0, intersection.lon - 6.0, 12.0, 12.0],
-                        c.transform,
+                    draw_intersection_glyph(
+                        [intersection.lat, intersection.lon],
+                        intersection.control,
+                        &config,
+                        c,
                         gl,
                     );
+
+                    if let Some(plan) = intersection
+                        ._id
+                        .and_then(|id| intersection_manager.signal_plan(id))
+                    {
+                        draw_signal_lights(
+                            [intersection.lat, intersection.lon],
+                            &plan,
+                            roads,
+                            now,
+                            &config,
+                            c,
+                            gl,
+                        );
+                    }
                 }
 
-                let state_text = match state {
-                    States::DrawRoad => "STATE: Road",
-                    States::DrawIntersection => "STATE: Intersection",
-                    States::Destroy => "STATE: Destroy",
+                if let Some(simulation) = &editor.simulation {
+                    for vehicle in &simulation.vehicles {
+                        let [x, y] = simulation.vehicle_position(vehicle);
+                        ellipse(
+                            config.colors.vehicle,
+                            [x - 2.5, y - 2.5, 5.0, 5.0],
+                            c.transform,
+                            gl,
+                        );
+                    }
+
+                    let status = format!(
+                        "{:.1} s  {} vehicles  {} arrived",
+                        simulation.clock.time,
+                        simulation.vehicles.len(),
+                        simulation.arrived
+                    );
+                    let status_width = glyphs.width(CONSOLE_FONT_SIZE, &status).unwrap_or(0.0);
+
+                    graphics::Text::new_color(config.colors.text, CONSOLE_FONT_SIZE)
+                        .draw(
+                            &status,
+                            &mut glyphs,
+                            &c.draw_state,
+                            c.transform
+                                .trans(c.get_view_size()[0] - status_width - 10.0, 24.0),
+                            gl,
+                        )
+                        .unwrap();
+                }
+
+                // with alternatives shown, list them in their colors with the picked one marked
+                if editor.alternatives.len() > 1 {
+                    for (i, route) in editor.alternatives.iter().enumerate() {
+                        let (marker, color) = if i == editor.selected_route {
+                            ('>', config.colors.route)
+                        } else {
+                            (' ', config.colors.alternative_route(i))
+                        };
+                        let summary = format!(
+                            "{} route {}: {:.0} m  {:.0} s",
+                            marker,
+                            i + 1,
+                            route.distance,
+                            route.time
+                        );
+
+                        graphics::Text::new_color(color, CONSOLE_FONT_SIZE)
+                            .draw(
+                                &summary,
+                                &mut glyphs,
+                                &c.draw_state,
+                                c.transform.trans(10.0, 24.0 + i as f64 * 18.0),
+                                gl,
+                            )
+                            .unwrap();
+                    }
+                }
+
+                let state_text = match editor.state {
+                    States::DrawRoad => "STATE: Road".to_string(),
+                    States::DrawIntersection => "STATE: Intersection".to_string(),
+                    States::Destroy => "STATE: Destroy".to_string(),
+                    States::Route => format!("STATE: Route ({})", editor.profile_name(&config)),
+                    States::Restrict => "STATE: Turn restriction".to_string(),
+                    States::Isochrone => "STATE: Isochrone".to_string(),
                 };
 
                 let mut text = graphics::Text::new(32);
 
-                let text_width = glyphs.width(32, state_text).unwrap_or(0.0) as f64;
-                let text_height = glyphs.character(32, 'M').unwrap().advance_height() as f64;
+                let text_width = glyphs.width(32, &state_text).unwrap_or(0.0);
+                let text_height = glyphs.character(32, 'M').unwrap().advance_height();
 
                 let transform = c.transform.trans(
                     c.viewport.unwrap().window_size[0] - text_width - 10.0,
                     c.viewport.unwrap().window_size[1] - text_height - 10.0,
                 );
 
-                text.color = [1.0, 1.0, 1.0, 1.0];
-                text.draw(state_text, &mut glyphs, &c.draw_state, transform, gl)
+                text.color = config.colors.text;
+                text.draw(&state_text, &mut glyphs, &c.draw_state, transform, gl)
                     .unwrap();
+
+                if console_open {
+                    draw_console(&logger.recent(0), &config, &mut glyphs, c, gl);
+                }
+
+                draw_toasts(&toasts, &config, &mut glyphs, c, gl);
             });
         }
     }
 }
+
+/// Draws chevrons along a one-way road pointing in its direction of travel
+///
+/// @internal
+fn draw_direction_arrows(road: &Road, color: [f32; 4], c: Context, gl: &mut GlGraphics) {
+    const SPACING: f64 = 30.0;
+    const SIZE: f64 = 5.0;
+
+    let length = road.length();
+    if length < SPACING {
+        return;
+    }
+
+    let [start_x, start_y, stop_x, stop_y] = road.get_points();
+    let (from, dir) = match road.direction {
+        Direction::Backward => ([stop_x, stop_y], [start_x - stop_x, start_y - stop_y]),
+        _ => ([start_x, start_y], [stop_x - start_x, stop_y - start_y]),
+    };
+    let dir = [dir[0] / length, dir[1] / length];
+    let perp = [-dir[1], dir[0]];
+
+    let mut offset = SPACING / 2.0;
+    while offset < length {
+        let tip = [from[0] + dir[0] * offset, from[1] + dir[1] * offset];
+
+        for side in [-1.0, 1.0] {
+            let tail = [
+                tip[0] - dir[0] * SIZE + perp[0] * SIZE * side,
+                tip[1] - dir[1] * SIZE + perp[1] * SIZE * side,
+            ];
+            line(
+                color,
+                1.0,
+                [tail[0], tail[1], tip[0], tip[1]],
+                c.transform,
+                gl,
+            );
+        }
+
+        offset += SPACING;
+    }
+}
+
+/// Draws an intersection with a glyph for its control type: a plain dot when uncontrolled, an octagon for stop
+/// signs (crossed by a bar for two-way stops), a triangle for yield signs, a signal head for traffic signals and a
+/// ring for roundabouts
+///
+/// @internal
+fn draw_intersection_glyph(
+    center: [f64; 2],
+    control: Control,
+    config: &Config,
+    c: Context,
+    gl: &mut GlGraphics,
+) {
+    let [x, y] = center;
+    let colors = &config.colors;
+
+    match control {
+        Control::Uncontrolled => {
+            ellipse(
+                colors.intersection,
+                [x - 6.0, y - 6.0, 12.0, 12.0],
+                c.transform,
+                gl,
+            );
+        }
+        Control::AllWayStop | Control::TwoWayStop => {
+            let octagon: Vec<[f64; 2]> = (0..8)
+                .map(|i| {
+                    let angle = std::f64::consts::PI / 8.0 + i as f64 * std::f64::consts::PI / 4.0;
+                    [x + 8.0 * angle.cos(), y + 8.0 * angle.sin()]
+                })
+                .collect();
+            polygon(colors.stop_sign, &octagon, c.transform, gl);
+
+            if control == Control::TwoWayStop {
+                line(
+                    colors.yield_sign,
+                    1.5,
+                    [x - 5.0, y, x + 5.0, y],
+                    c.transform,
+                    gl,
+                );
+            }
+        }
+        Control::Yield => {
+            polygon(
+                colors.stop_sign,
+                &[[x - 9.0, y - 7.0], [x + 9.0, y - 7.0], [x, y + 9.0]],
+                c.transform,
+                gl,
+            );
+            polygon(
+                colors.yield_sign,
+                &[[x - 5.0, y - 4.5], [x + 5.0, y - 4.5], [x, y + 4.5]],
+                c.transform,
+                gl,
+            );
+        }
+        Control::TrafficSignals => {
+            rectangle(
+                colors.signal_housing,
+                [x - 5.0, y - 12.0, 10.0, 24.0],
+                c.transform,
+                gl,
+            );
+
+            for (i, color) in [colors.signal_red, colors.signal_amber, colors.signal_green]
+                .into_iter()
+                .enumerate()
+            {
+                let light_y = y - 10.0 + i as f64 * 7.0;
+                ellipse(color, [x - 3.0, light_y, 6.0, 6.0], c.transform, gl);
+            }
+        }
+        Control::Roundabout => {
+            Ellipse::new_border(colors.intersection, 2.0).draw(
+                [x - 10.0, y - 10.0, 20.0, 20.0],
+                &c.draw_state,
+                c.transform,
+                gl,
+            );
+            ellipse(
+                colors.intersection,
+                [x - 3.0, y - 3.0, 6.0, 6.0],
+                c.transform,
+                gl,
+            );
+        }
+    }
+}
+
+/// Draws the light each signalized approach currently shows as a dot on the road, just before the intersection
+///
+/// @internal
+fn draw_signal_lights(
+    center: [f64; 2],
+    plan: &SignalPlan,
+    roads: &[Road],
+    time: f64,
+    config: &Config,
+    c: Context,
+    gl: &mut GlGraphics,
+) {
+    const DISTANCE: f64 = 16.0;
+
+    for road in roads {
+        let Some(signal) = road._id.and_then(|id| plan.state_at(id, time)) else {
+            continue;
+        };
+
+        let color = match signal {
+            SignalState::Green => config.colors.signal_green,
+            SignalState::Amber => config.colors.signal_amber,
+            SignalState::Red => config.colors.signal_red,
+        };
+
+        let [x, y] = point_towards(road, center, DISTANCE);
+        ellipse(color, [x - 4.0, y - 4.0, 8.0, 8.0], c.transform, gl);
+    }
+}
+
+/// Returns the point `distance` along a road, measured from its end nearest to `at`
+///
+/// @internal
+fn point_towards(road: &Road, at: [f64; 2], distance: f64) -> [f64; 2] {
+    let [start_x, start_y, stop_x, stop_y] = road.get_points();
+    let start_distance = (start_x - at[0]).powi(2) + (start_y - at[1]).powi(2);
+    let stop_distance = (stop_x - at[0]).powi(2) + (stop_y - at[1]).powi(2);
+
+    let (from, to) = if start_distance <= stop_distance {
+        ([start_x, start_y], [stop_x, stop_y])
+    } else {
+        ([stop_x, stop_y], [start_x, start_y])
+    };
+
+    let length = road.length();
+    if length == 0.0 {
+        return from;
+    }
+
+    let t = (distance / length).min(0.5);
+    [
+        from[0] + (to[0] - from[0]) * t,
+        from[1] + (to[1] - from[1]) * t,
+    ]
+}
+
+/// Draws a turn restriction as a line from the road it applies to, through the intersection, onto the road it
+/// forbids or mandates
+///
+/// @internal
+fn draw_turn_restriction(
+    restriction: &TurnRestriction,
+    roads: &[Road],
+    intersections: &[Intersection],
+    config: &Config,
+    c: Context,
+    gl: &mut GlGraphics,
+) {
+    const LENGTH: f64 = 18.0;
+
+    let find_road = |id: i32| roads.iter().find(|road| road._id == Some(id));
+    let intersection = intersections
+        .iter()
+        .find(|intersection| intersection._id == Some(restriction.intersection));
+
+    let (Some(intersection), Some(from_road), Some(to_road)) = (
+        intersection,
+        find_road(restriction.from_road),
+        find_road(restriction.to_road),
+    ) else {
+        return;
+    };
+
+    let center = [intersection.lat, intersection.lon];
+    let from = point_towards(from_road, center, LENGTH);
+    let to = point_towards(to_road, center, LENGTH);
+    let color = match restriction.kind {
+        RestrictionKind::Forbidden => config.colors.forbidden_turn,
+        RestrictionKind::Mandatory => config.colors.mandatory_turn,
+    };
+
+    line(
+        color,
+        2.0,
+        [from[0], from[1], center[0], center[1]],
+        c.transform,
+        gl,
+    );
+    line(
+        color,
+        2.0,
+        [center[0], center[1], to[0], to[1]],
+        c.transform,
+        gl,
+    );
+    ellipse(color, [to[0] - 3.0, to[1] - 3.0, 6.0, 6.0], c.transform, gl);
+}
+
+/// Draws the most recent log messages that fit in the top half of the window
+///
+/// @internal
+fn draw_console(
+    entries: &[LogEntry],
+    config: &Config,
+    glyphs: &mut GlyphCache,
+    c: Context,
+    gl: &mut GlGraphics,
+) {
+    let [width, height] = c.get_view_size();
+    let line_height = CONSOLE_FONT_SIZE as f64 * 1.4;
+    let console_height = (height / 2.0).floor();
+    let max_lines = ((console_height - 10.0) / line_height).floor().max(0.0) as usize;
+
+    rectangle(
+        config.colors.console_background,
+        [0.0, 0.0, width, console_height],
+        c.transform,
+        gl,
+    );
+
+    let start = entries.len().saturating_sub(max_lines);
+    for (i, entry) in entries[start..].iter().enumerate() {
+        let transform = c.transform.trans(10.0, 5.0 + line_height * (i + 1) as f64);
+
+        graphics::Text::new_color(config.colors.log_level(entry.level), CONSOLE_FONT_SIZE)
+            .draw(&entry.to_string(), glyphs, &c.draw_state, transform, gl)
+            .unwrap();
+    }
+}
+
+/// Draws the toasts stacked upwards from the bottom left corner, fading them out before they expire
+///
+/// @internal
+fn draw_toasts(
+    toasts: &[Toast],
+    config: &Config,
+    glyphs: &mut GlyphCache,
+    c: Context,
+    gl: &mut GlGraphics,
+) {
+    let height = c.get_view_size()[1];
+    let line_height = CONSOLE_FONT_SIZE as f64 * 2.0;
+
+    for (i, toast) in toasts.iter().rev().enumerate() {
+        let remaining = TOAST_DURATION.saturating_sub(toast.shown_at.elapsed());
+        let alpha = (remaining.as_secs_f32() / TOAST_FADE.as_secs_f32()).min(1.0);
+
+        let message = format!("{}: {}", toast.entry.level, toast.entry.message);
+        let text_width = glyphs.width(CONSOLE_FONT_SIZE, &message).unwrap_or(0.0);
+        let top = height - 10.0 - line_height * (i + 1) as f64;
+
+        let mut background = config.colors.toast_background;
+        background[3] *= alpha;
+        rectangle(
+            background,
+            [10.0, top, text_width + 20.0, line_height - 4.0],
+            c.transform,
+            gl,
+        );
+
+        let mut color = config.colors.log_level(toast.entry.level);
+        color[3] *= alpha;
+        graphics::Text::new_color(color, CONSOLE_FONT_SIZE)
+            .draw(
+                &message,
+                glyphs,
+                &c.draw_state,
+                c.transform.trans(20.0, top + line_height * 0.6),
+                gl,
+            )
+            .unwrap();
+    }
+}